@@ -0,0 +1,112 @@
+use chrono::Utc;
+use cosmic_applet_sys_status::chart::{ChartStyle, Point, UsageChart};
+use cosmic_applet_sys_status::config::ChartPalette;
+use cosmic_applet_sys_status::metrics::{Scale, SeriesId};
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use plotters::prelude::*;
+use plotters_iced::Chart;
+use std::time::{Duration, Instant};
+
+const STYLE: ChartStyle = ChartStyle {
+    accent: RGBColor(0x63, 0xd0, 0xdf),
+    grid: RGBColor(0x5c, 0x5c, 0x5c),
+    label: RGBColor(0xc4, 0xc4, 0xc4),
+    palette: ChartPalette::Accent,
+};
+const POINT_COUNTS: [usize; 3] = [60, 600, 3600];
+const WIDTH: u32 = 440;
+const HEIGHT: u32 = 180;
+
+/// A chart holding `points` samples spread evenly over the plotted minute.
+fn filled_chart(points: usize, start: Instant) -> UsageChart {
+    let mut chart = UsageChart::new(STYLE, Scale::percent());
+    let step = Duration::from_secs(60) / points as u32;
+    for i in 0..points {
+        chart.push_data(SeriesId::new("cpu"), point(start + step * i as u32, i));
+    }
+    chart
+}
+
+fn point(at: Instant, i: usize) -> Point {
+    Point {
+        at,
+        time: Utc::now(),
+        value: (i % 100) as f64,
+    }
+}
+
+fn push_data(c: &mut Criterion) {
+    let mut group = c.benchmark_group("push_data");
+    for points in POINT_COUNTS {
+        group.bench_with_input(
+            BenchmarkId::from_parameter(points),
+            &points,
+            |b, &points| {
+                b.iter_batched(
+                    || {
+                        let start = Instant::now();
+                        (filled_chart(points, start), start)
+                    },
+                    // Lands inside the window, nothing is trimmed
+                    |(mut chart, start)| {
+                        chart.push_data(
+                            SeriesId::new("cpu"),
+                            point(start + Duration::from_secs(60), points),
+                        );
+                        black_box(chart)
+                    },
+                    BatchSize::SmallInput,
+                )
+            },
+        );
+    }
+    group.finish();
+}
+
+fn retention(c: &mut Criterion) {
+    let mut group = c.benchmark_group("retention");
+    for points in POINT_COUNTS {
+        group.bench_with_input(
+            BenchmarkId::from_parameter(points),
+            &points,
+            |b, &points| {
+                b.iter_batched(
+                    || {
+                        let start = Instant::now();
+                        (filled_chart(points, start), start)
+                    },
+                    // Half a minute later half of the points fall out of the window
+                    |(mut chart, start)| {
+                        chart.push_data(
+                            SeriesId::new("cpu"),
+                            point(start + Duration::from_secs(90), points),
+                        );
+                        black_box(chart)
+                    },
+                    BatchSize::SmallInput,
+                )
+            },
+        );
+    }
+    group.finish();
+}
+
+fn build_chart(c: &mut Criterion) {
+    let mut group = c.benchmark_group("build_chart");
+    for points in POINT_COUNTS {
+        let chart = filled_chart(points, Instant::now());
+        let mut buffer = vec![0u8; (WIDTH * HEIGHT * 3) as usize];
+        group.bench_with_input(BenchmarkId::from_parameter(points), &chart, |b, chart| {
+            b.iter(|| {
+                let root =
+                    BitMapBackend::with_buffer(&mut buffer, (WIDTH, HEIGHT)).into_drawing_area();
+                chart.build_chart(&(), ChartBuilder::on(&root));
+                root.present().ok();
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, push_data, retention, build_chart);
+criterion_main!(benches);
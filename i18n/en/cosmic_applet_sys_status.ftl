@@ -0,0 +1,220 @@
+loading = Loading…
+metric-unavailable = { $metric } unavailable
+chart-summary = { $title }: { $current } now, lowest { $min }, highest { $max }, average { $average } over the last { $seconds } seconds
+
+series-download = Download
+series-upload = Upload
+series-used = Used
+series-total = Total
+series-average = Average
+series-longest = Longest
+
+show-details = Show details
+
+cpu = CPU
+vcpu = vCPU
+cpu-steal = CPU steal
+memory = Memory
+network = Network
+process-memory = Process memory
+frame-time = Frame time
+xruns = Audio XRuns
+page-faults = Major page faults
+huge-pages = Huge pages
+swap-activity = Swap activity (pages)
+series-swap-in = In
+series-swap-out = Out
+context-switches = Context switches
+interrupts = Interrupts
+run-queue = Run queue (per core)
+interrupts-per-cpu = Interrupts per CPU
+interrupt-rates = { $irqs } IRQ/s · { $softirqs } softirq/s
+interrupt-busiest-cpu = { $percent }% on CPU{ $cpu }
+
+compressed-swap = Compressed swap
+compressed-size = { $compressed } of { $original }, { $ratio }:1
+balloon = Balloon: { $inflated } returned to the host, { $total } left
+
+network-totals = Data transferred
+network-reset = Reset
+network-interface-totals = { $interface }: ↓ { $session_rx } ↑ { $session_tx } this session, ↓ { $boot_rx } ↑ { $boot_tx } since boot
+data-budget = Data budget ({ $interface }): { $usage }
+data-budget-usage-daily = { $used } of { $budget } today
+data-budget-usage-monthly = { $used } of { $budget } this month
+data-budget-alert = { $percent }% of the { $interface } data budget used
+
+resource-groups = Resource groups
+
+top-processes = Top processes
+process-group = { $name } ({ $count } processes)
+
+disks = Drives
+filesystems = Filesystems
+filesystem-usage = { $used } of { $total }
+days-until-full = ~{ $days } days until full
+filesystem-filling-up = { $mount_point } is filling up
+disk-busy = { $percent }% busy
+
+audio-quantum = Quantum { $quantum } at { $rate } Hz
+audio-quantum-forced = Quantum { $quantum } (forced) at { $rate } Hz
+
+peripherals = Peripheral batteries
+peripheral-unknown = Unknown device
+
+pods = Kubernetes pods
+pods-unavailable = Pod usage unavailable: { $error }
+pods-none = no pods are running
+containers = Containers
+containers-unavailable = Container stats unavailable: { $error }
+containers-none = no containers are running
+health-checks = Services
+health-unreadable = unexpected curl output
+systemd-units = Systemd units
+restart-unit = Restart
+clock = Clock
+clock-synchronized = synchronized
+clock-unsynchronized = not synchronized
+clock-ntp-off = NTP is off
+clock-unavailable = Synchronization status unavailable: { $error }
+clock-unsynchronized-alert = The system clock is no longer synchronized
+clock-unsynchronized-body = Check the network connection and the NTP service, the time may drift.
+reboot-required = Reboot required
+reboot-required-body = Updated packages take effect after restarting.
+security-updates = { $count ->
+    [one] 1 security update
+   *[other] { $count } security updates
+}
+security-updates-body = Install them with your software center or package manager.
+smart-health = Drive health
+smart-passed = healthy
+smart-failing = FAILING
+smart-reallocated = { $count } reallocated sectors
+smart-wear = { $percent }% worn
+smart-unavailable = SMART data unavailable: { $error }
+smart-no-drives = no drives reported SMART data, smartctl may need more privileges
+smart-failing-alert = Drive { $device } reports failing health
+
+raid-arrays = RAID arrays
+raid-clean = clean
+raid-degraded = DEGRADED
+raid-syncing = { $action } { $progress }%
+raid-degraded-alert = RAID array { $array } is degraded
+
+storage-pools = Storage pools
+pool-errors = { $count } device errors
+pool-scrubbing = scrub { $progress }%
+pool-unhealthy-alert = Storage pool { $pool } needs attention
+
+virtual-machines = Virtual machines
+vms-unavailable = libvirt unavailable: { $error }
+vm-start = Start
+vm-pause = Pause
+vm-resume = Resume
+vm-paused = paused
+vm-stopped = stopped
+
+user-sessions = Users
+
+temperatures = Temperatures
+temperature = Temperature
+rename-sensors = Rename
+done = Done
+sensor-group = Group
+temperature-alert = { $sensor } is at { $temperature }°C
+
+temperature-limit = { $sensor }, limit { $limit }°C
+
+cpu-frequency = CPU frequency
+cpu-governor = Governor: { $governor }
+cpu-smt = Simultaneous multithreading: { $state }
+cpu-offline = { $core }: offline
+cpus-offline = Offline: { $cores }
+board = Board
+soc-temperature = SoC temperature
+throttled = Throttled
+throttling-none = no throttling since boot
+throttling-now = now
+throttling-since-boot = since boot
+under-voltage = Under-voltage
+frequency-capped = Frequency capped
+throttling = Throttling
+soft-temperature-limit = Soft temperature limit
+smc = System Management Controller
+fan-rpm = { $rpm } RPM
+
+power-profile = Power profile
+power-saver = Power saver
+balanced = Balanced
+performance = Performance
+
+sort-cpu = CPU
+sort-memory = Memory
+sort-name = Name
+process-tree = Tree
+filter-processes = Filter by name or PID
+
+sort-disk = Disk
+disk-rates = R { $read }/s W { $write }/s
+process-network-rates = ↓{ $rx }/s ↑{ $tx }/s
+
+top-talkers = Top remote hosts
+connection-rates = ↑ { $sent }/s ↓ { $received }/s
+
+gpu = GPU
+gpu-processes = GPU processes
+gpu-fan = fan { $percent }%
+gpu-power = { $power } W
+gpu-power-limit = { $power } W of { $limit } W
+gpu-max-power-limit = (max { $limit } W)
+
+busy-periods = Busy periods
+busy-ongoing = now
+busy-period = { $start } – { $end }, peak { $peak }%
+
+daily-usage = Time above { $threshold }% CPU
+today = Today
+yesterday = Yesterday
+daily-busy-hours = { $busy } h of { $up } h
+weekly-report = Last 7 days
+weekly-summary = CPU { $cpu }% on average, memory peaked at { $memory }%, { $transferred } transferred
+
+events = Events
+note-placeholder = Add a note to the charts
+add-note = Add
+event-alert = Alert
+event-suspend = Suspended
+event-resume = Resumed
+event-note = Note
+
+booted = Up since { $time }
+booted-in = Up since { $time }, booted in { $duration }
+expand = Show charts
+tray-title = System status
+tray-quit = Quit
+collapse = Quick glance
+scroll-to-top = Top
+
+quick-actions = Actions
+
+copy-report = Copy report
+detect-hardware = Re-detect hardware
+hardware-found = Found: { $found }
+hardware-none = No optional hardware found
+hardware-battery = battery
+hardware-sensors = temperature sensors
+hardware-containers = containers
+hardware-libvirt = libvirt
+hardware-balloon = memory balloon
+profile = Profile
+profile-desktop = Desktop
+profile-laptop = Laptop
+profile-server = Server
+report-title = System status report
+report-metrics = Metrics
+report-unavailable = unavailable
+
+export-history = Export history…
+export-csv = CSV
+export-json = JSON
+export-done = Exported to { $path }
+export-failed = Export failed: { $error }
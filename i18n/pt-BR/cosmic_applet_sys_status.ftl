@@ -0,0 +1,220 @@
+loading = Carregando…
+metric-unavailable = { $metric } indisponível
+chart-summary = { $title }: { $current } agora, mínimo { $min }, máximo { $max }, média { $average } nos últimos { $seconds } segundos
+
+series-download = Download
+series-upload = Upload
+series-used = Usada
+series-total = Total
+series-average = Média
+series-longest = Máximo
+
+show-details = Mostrar detalhes
+
+cpu = CPU
+vcpu = vCPU
+cpu-steal = CPU roubada (steal)
+memory = Memória
+network = Rede
+process-memory = Memória por processo
+frame-time = Tempo de quadro
+xruns = XRuns de áudio
+page-faults = Falhas de página maiores
+huge-pages = Páginas enormes
+swap-activity = Atividade de swap (páginas)
+series-swap-in = Entrada
+series-swap-out = Saída
+context-switches = Trocas de contexto
+interrupts = Interrupções
+run-queue = Fila de execução (por núcleo)
+interrupts-per-cpu = Interrupções por CPU
+interrupt-rates = { $irqs } IRQ/s · { $softirqs } softirq/s
+interrupt-busiest-cpu = { $percent }% na CPU{ $cpu }
+
+compressed-swap = Swap comprimido
+compressed-size = { $compressed } de { $original }, { $ratio }:1
+balloon = Balão: { $inflated } devolvidos ao host, { $total } restantes
+
+network-totals = Dados transferidos
+network-reset = Zerar
+network-interface-totals = { $interface }: ↓ { $session_rx } ↑ { $session_tx } nesta sessão, ↓ { $boot_rx } ↑ { $boot_tx } desde o boot
+data-budget = Franquia de dados ({ $interface }): { $usage }
+data-budget-usage-daily = { $used } de { $budget } hoje
+data-budget-usage-monthly = { $used } de { $budget } neste mês
+data-budget-alert = { $percent }% da franquia de dados de { $interface } usada
+
+resource-groups = Grupos de recursos
+
+top-processes = Principais processos
+process-group = { $name } ({ $count } processos)
+
+disks = Discos
+filesystems = Sistemas de arquivos
+filesystem-usage = { $used } de { $total }
+days-until-full = ~{ $days } dias até encher
+filesystem-filling-up = { $mount_point } está enchendo
+disk-busy = { $percent }% ocupado
+
+audio-quantum = Quantum { $quantum } a { $rate } Hz
+audio-quantum-forced = Quantum { $quantum } (forçado) a { $rate } Hz
+
+peripherals = Baterias de periféricos
+peripheral-unknown = Dispositivo desconhecido
+
+pods = Pods do Kubernetes
+pods-unavailable = Uso dos pods indisponível: { $error }
+pods-none = nenhum pod em execução
+containers = Contêineres
+containers-unavailable = Estatísticas dos contêineres indisponíveis: { $error }
+containers-none = nenhum contêiner em execução
+health-checks = Serviços
+health-unreadable = saída inesperada do curl
+systemd-units = Unidades do systemd
+restart-unit = Reiniciar
+clock = Relógio
+clock-synchronized = sincronizado
+clock-unsynchronized = não sincronizado
+clock-ntp-off = NTP desativado
+clock-unavailable = Estado da sincronização indisponível: { $error }
+clock-unsynchronized-alert = O relógio do sistema não está mais sincronizado
+clock-unsynchronized-body = Verifique a conexão de rede e o serviço de NTP, o horário pode se desviar.
+reboot-required = Reinicialização necessária
+reboot-required-body = Os pacotes atualizados passam a valer após reiniciar.
+security-updates = { $count ->
+    [one] 1 atualização de segurança
+   *[other] { $count } atualizações de segurança
+}
+security-updates-body = Instale-as pela central de programas ou pelo gerenciador de pacotes.
+smart-health = Saúde dos discos
+smart-passed = saudável
+smart-failing = FALHANDO
+smart-reallocated = { $count } setores realocados
+smart-wear = { $percent }% de desgaste
+smart-unavailable = Dados SMART indisponíveis: { $error }
+smart-no-drives = nenhum disco informou dados SMART, o smartctl pode precisar de mais privilégios
+smart-failing-alert = O disco { $device } informa falha de saúde
+
+raid-arrays = Arrays RAID
+raid-clean = íntegro
+raid-degraded = DEGRADADO
+raid-syncing = { $action } { $progress }%
+raid-degraded-alert = O array RAID { $array } está degradado
+
+storage-pools = Pools de armazenamento
+pool-errors = { $count } erros de dispositivo
+pool-scrubbing = scrub { $progress }%
+pool-unhealthy-alert = O pool de armazenamento { $pool } precisa de atenção
+
+virtual-machines = Máquinas virtuais
+vms-unavailable = libvirt indisponível: { $error }
+vm-start = Iniciar
+vm-pause = Pausar
+vm-resume = Retomar
+vm-paused = pausada
+vm-stopped = desligada
+
+user-sessions = Usuários
+
+temperatures = Temperaturas
+temperature = Temperatura
+rename-sensors = Renomear
+done = Concluído
+sensor-group = Grupo
+temperature-alert = { $sensor } está a { $temperature }°C
+
+temperature-limit = { $sensor }, limite { $limit }°C
+
+cpu-frequency = Frequência da CPU
+cpu-governor = Governador: { $governor }
+cpu-smt = Multithreading simultâneo (SMT): { $state }
+cpu-offline = { $core }: desligado
+cpus-offline = Desligados: { $cores }
+board = Placa
+soc-temperature = Temperatura do SoC
+throttled = Limitado
+throttling-none = nenhuma limitação desde a inicialização
+throttling-now = agora
+throttling-since-boot = desde a inicialização
+under-voltage = Subtensão
+frequency-capped = Frequência limitada
+throttling = Limitação
+soft-temperature-limit = Limite suave de temperatura
+smc = Controlador de gerenciamento do sistema
+fan-rpm = { $rpm } RPM
+
+power-profile = Perfil de energia
+power-saver = Economia de energia
+balanced = Equilibrado
+performance = Desempenho
+
+sort-cpu = CPU
+sort-memory = Memória
+sort-name = Nome
+process-tree = Árvore
+filter-processes = Filtrar por nome ou PID
+
+sort-disk = Disco
+disk-rates = L { $read }/s E { $write }/s
+process-network-rates = ↓{ $rx }/s ↑{ $tx }/s
+
+top-talkers = Hosts remotos com mais tráfego
+connection-rates = ↑ { $sent }/s ↓ { $received }/s
+
+gpu = GPU
+gpu-processes = Processos na GPU
+gpu-fan = ventoinha { $percent }%
+gpu-power = { $power } W
+gpu-power-limit = { $power } W de { $limit } W
+gpu-max-power-limit = (máx. { $limit } W)
+
+busy-periods = Períodos ocupados
+busy-ongoing = agora
+busy-period = { $start } – { $end }, pico de { $peak }%
+
+daily-usage = Tempo acima de { $threshold }% de CPU
+today = Hoje
+yesterday = Ontem
+daily-busy-hours = { $busy } h de { $up } h
+weekly-report = Últimos 7 dias
+weekly-summary = CPU em { $cpu }% na média, memória chegou a { $memory }%, { $transferred } transferidos
+
+events = Eventos
+note-placeholder = Adicionar uma nota aos gráficos
+add-note = Adicionar
+event-alert = Alerta
+event-suspend = Suspenso
+event-resume = Retomado
+event-note = Nota
+
+booted = Ligado desde { $time }
+booted-in = Ligado desde { $time }, inicializou em { $duration }
+expand = Mostrar gráficos
+tray-title = Status do sistema
+tray-quit = Sair
+collapse = Visão rápida
+scroll-to-top = Topo
+
+quick-actions = Ações
+
+copy-report = Copiar relatório
+detect-hardware = Detectar hardware novamente
+hardware-found = Encontrado: { $found }
+hardware-none = Nenhum hardware opcional encontrado
+hardware-battery = bateria
+hardware-sensors = sensores de temperatura
+hardware-containers = contêineres
+hardware-libvirt = libvirt
+hardware-balloon = balão de memória
+profile = Perfil
+profile-desktop = Desktop
+profile-laptop = Notebook
+profile-server = Servidor
+report-title = Relatório do estado do sistema
+report-metrics = Métricas
+report-unavailable = indisponível
+
+export-history = Exportar histórico…
+export-csv = CSV
+export-json = JSON
+export-done = Exportado para { $path }
+export-failed = Falha ao exportar: { $error }
@@ -0,0 +1,38 @@
+use crate::config::QuickAction;
+use crate::fl;
+use crate::layout;
+use crate::window::Message;
+use cosmic::iced::{Alignment, Length};
+use cosmic::widget;
+use cosmic::Element;
+use std::process::Command;
+use std::thread;
+
+/// Runs the action's command through `sh -c` without waiting for it to finish.
+pub fn run(action: QuickAction) {
+    thread::spawn(
+        move || match Command::new("sh").arg("-c").arg(&action.command).status() {
+            Ok(status) if status.success() => {}
+            Ok(status) => tracing::warn!("quick action {:?} failed: {}", action.label, status),
+            Err(err) => tracing::warn!("failed to run quick action {:?}: {}", action.label, err),
+        },
+    );
+}
+
+pub fn view(actions: &[QuickAction]) -> Element<Message> {
+    let mut cells: Vec<Element<Message>> = vec![widget::text(fl!("quick-actions"))
+        .width(Length::Fill)
+        .horizontal_alignment(layout::start_alignment())
+        .into()];
+    for (index, action) in actions.iter().enumerate() {
+        cells.push(
+            widget::button::standard(action.label.as_str())
+                .on_press(Message::RunAction(index))
+                .into(),
+        );
+    }
+    layout::directional_row(cells)
+        .spacing(8)
+        .align_items(Alignment::Center)
+        .into()
+}
@@ -0,0 +1,380 @@
+use crate::config::{Config, QuietMode};
+use crate::fl;
+use chrono::NaiveTime;
+use notify_rust::{Notification, Urgency};
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::thread;
+use std::time::Duration;
+
+/// Action of the notifications whose alert has a section in the popup
+const SHOW_DETAILS: &str = "show-details";
+/// How long a notification's actions are listened for. Notification centers may keep a
+/// notification around without ever closing it, the waiting thread would never end.
+const ACTION_WAIT: Duration = Duration::from_secs(600);
+
+/// Where `AlertRaised` signals are emitted on the session bus
+const DBUS_PATH: &str = "/app/arara/CosmicAppletSysStatus";
+const DBUS_INTERFACE: &str = "app.arara.CosmicAppletSysStatus.Alerts";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Severity {
+    Warning,
+    Critical,
+}
+
+impl Severity {
+    fn as_str(self) -> &'static str {
+        match self {
+            Severity::Warning => "warning",
+            Severity::Critical => "critical",
+        }
+    }
+}
+
+/// Part of the popup an alert is about, shown first when opened from its notification.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Section {
+    Temperatures,
+    Smart,
+    Raid,
+    Pools,
+    DataBudget,
+}
+
+#[derive(Clone, Debug)]
+pub struct Alert {
+    /// Identifies the condition, an alert is only raised again after it was cleared
+    pub key: String,
+    pub summary: String,
+    pub body: String,
+    pub severity: Severity,
+}
+
+impl Alert {
+    /// The section of the popup with the details, derived from the alert's key.
+    pub fn section(&self) -> Option<Section> {
+        let key = self.key.as_str();
+        if key.starts_with(crate::sensors::ALERT_PREFIX) {
+            Some(Section::Temperatures)
+        } else if key.starts_with("smart-") {
+            Some(Section::Smart)
+        } else if key.starts_with("raid-") {
+            Some(Section::Raid)
+        } else if key.starts_with("pool-") {
+            Some(Section::Pools)
+        } else if key.starts_with("data-budget-") {
+            Some(Section::DataBudget)
+        } else {
+            None
+        }
+    }
+}
+
+/// Turns alert conditions into desktop notifications and D-Bus signals, once per occurrence.
+pub struct Alerts {
+    active: BTreeSet<String>,
+    /// Conditions reported by the latest `sync` of their monitor, also while quiet hours
+    /// suppress them
+    conditions: BTreeMap<String, Severity>,
+    /// Alerts notified since the last `take_raised`
+    raised: Vec<Alert>,
+    /// Sections whose notification's "Show details" was clicked, sent from the
+    /// notification threads
+    activated: Receiver<Section>,
+    activate: Sender<Section>,
+    /// Set while quiet hours are on
+    quiet: Option<QuietMode>,
+}
+
+impl Default for Alerts {
+    fn default() -> Self {
+        let (activate, activated) = mpsc::channel();
+        Self {
+            active: BTreeSet::new(),
+            conditions: BTreeMap::new(),
+            raised: Vec::new(),
+            activated,
+            activate,
+            quiet: None,
+        }
+    }
+}
+
+impl Alerts {
+    /// Notifies about `alert` unless its condition is already active.
+    pub fn raise(&mut self, alert: Alert) {
+        if self.quiet == Some(QuietMode::Suppress) || !self.active.insert(alert.key.clone()) {
+            return;
+        }
+
+        tracing::info!(key = alert.key, "alert raised: {}", alert.summary);
+        if self.quiet.is_none() {
+            notify(&alert, self.activate.clone());
+            emit_signal(&alert);
+        }
+        self.raised.push(alert);
+    }
+
+    /// Applies the configured quiet hours at `now`, call before raising alerts.
+    pub fn set_quiet_hours(&mut self, config: &Config, now: NaiveTime) {
+        self.quiet = QuietHours::parse(config)
+            .filter(|hours| hours.contains(now))
+            .map(|_| config.quiet_mode);
+    }
+
+    pub fn take_raised(&mut self) -> Vec<Alert> {
+        std::mem::take(&mut self.raised)
+    }
+
+    /// The section of the most recently clicked notification, if any was clicked.
+    pub fn take_activated(&mut self) -> Option<Section> {
+        self.activated.try_iter().last()
+    }
+
+    /// Raises `alerts` and clears the conditions under `prefix` that are no longer reported,
+    /// so they notify again when they come back.
+    pub fn sync(&mut self, prefix: &str, alerts: Vec<Alert>) {
+        let reported = alerts
+            .iter()
+            .map(|alert| alert.key.clone())
+            .collect::<BTreeSet<_>>();
+        self.active
+            .retain(|key| !key.starts_with(prefix) || reported.contains(key));
+        self.conditions.retain(|key, _| !key.starts_with(prefix));
+        for alert in alerts {
+            self.conditions.insert(alert.key.clone(), alert.severity);
+            self.raise(alert);
+        }
+    }
+
+    /// Number of conditions currently reported and the most severe of them, `None` once
+    /// all are back to normal. One-off alerts, like crossing a data budget threshold,
+    /// don't count.
+    pub fn ongoing(&self) -> Option<(usize, Severity)> {
+        let severity = if self
+            .conditions
+            .values()
+            .any(|severity| *severity == Severity::Critical)
+        {
+            Severity::Critical
+        } else if self.conditions.is_empty() {
+            return None;
+        } else {
+            Severity::Warning
+        };
+        Some((self.conditions.len(), severity))
+    }
+}
+
+/// Daily time range in which alerts don't notify.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct QuietHours {
+    start: NaiveTime,
+    end: NaiveTime,
+}
+
+impl QuietHours {
+    fn parse(config: &Config) -> Option<Self> {
+        let parse = |time: &str| NaiveTime::parse_from_str(time.trim(), "%H:%M").ok();
+        Some(Self {
+            start: parse(&config.quiet_hours_start)?,
+            end: parse(&config.quiet_hours_end)?,
+        })
+    }
+
+    fn contains(&self, time: NaiveTime) -> bool {
+        if self.start <= self.end {
+            self.start <= time && time < self.end
+        } else {
+            // Spans midnight, e.g. 22:00 to 08:00
+            time >= self.start || time < self.end
+        }
+    }
+}
+
+fn notify(alert: &Alert, activate: Sender<Section>) {
+    let section = alert.section();
+    let mut notification = Notification::new();
+    notification
+        .appname("System Status")
+        .icon(crate::window::ID)
+        .summary(&alert.summary)
+        .body(&alert.body)
+        .urgency(match alert.severity {
+            Severity::Warning => Urgency::Normal,
+            Severity::Critical => Urgency::Critical,
+        });
+
+    if section.is_some() {
+        // Clicking the notification itself, servers don't show its label
+        notification.action("default", "");
+        notification.action(SHOW_DETAILS, &fl!("show-details"));
+    }
+
+    // Showing blocks on the session bus, keep it away from the UI thread
+    thread::spawn(move || match notification.show() {
+        Ok(handle) => {
+            let Some(section) = section else {
+                return;
+            };
+            // Closing the notification once the wait is over ends `wait_for_action`
+            let id = handle.id();
+            let (done, waiting) = mpsc::channel::<()>();
+            thread::spawn(move || {
+                if waiting.recv_timeout(ACTION_WAIT) == Err(RecvTimeoutError::Timeout) {
+                    close_notification(id);
+                }
+            });
+            // Returns once the notification was clicked or closed
+            handle.wait_for_action(|action| {
+                if action == "default" || action == SHOW_DETAILS {
+                    let _ = activate.send(section);
+                }
+            });
+            drop(done);
+        }
+        Err(err) => tracing::warn!("failed to show notification: {}", err),
+    });
+}
+
+fn close_notification(id: u32) {
+    let result = zbus::blocking::Connection::session().and_then(|connection| {
+        connection
+            .call_method(
+                Some("org.freedesktop.Notifications"),
+                "/org/freedesktop/Notifications",
+                Some("org.freedesktop.Notifications"),
+                "CloseNotification",
+                &(id,),
+            )
+            .map(|_| ())
+    });
+    if let Err(err) = result {
+        tracing::warn!("failed to close notification: {}", err);
+    }
+}
+
+/// Emits `AlertRaised(key, summary, body, severity)` for scripts watching the session bus.
+fn emit_signal(alert: &Alert) {
+    let alert = alert.clone();
+    thread::spawn(move || {
+        let result = zbus::blocking::Connection::session().and_then(|connection| {
+            connection.emit_signal(
+                None::<&str>,
+                DBUS_PATH,
+                DBUS_INTERFACE,
+                "AlertRaised",
+                &(
+                    alert.key.as_str(),
+                    alert.summary.as_str(),
+                    alert.body.as_str(),
+                    alert.severity.as_str(),
+                ),
+            )
+        });
+        if let Err(err) = result {
+            tracing::warn!("failed to emit alert signal: {}", err);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn alert(key: &str) -> Alert {
+        Alert {
+            key: key.to_string(),
+            summary: String::new(),
+            body: String::new(),
+            severity: Severity::Warning,
+        }
+    }
+
+    #[test]
+    fn alerts_link_to_their_section() {
+        assert_eq!(
+            alert("temperature-k10temp Tctl-crit").section(),
+            Some(Section::Temperatures)
+        );
+        assert_eq!(alert("raid-md0").section(), Some(Section::Raid));
+        assert_eq!(
+            alert("data-budget-2026-10-01-80").section(),
+            Some(Section::DataBudget)
+        );
+        assert_eq!(alert("something-else").section(), None);
+    }
+
+    #[test]
+    fn quiet_hours_can_span_midnight() {
+        let at = |time: &str| NaiveTime::parse_from_str(time, "%H:%M").unwrap();
+        let config = Config {
+            quiet_hours_start: "22:00".to_string(),
+            quiet_hours_end: "08:00".to_string(),
+            ..Config::default()
+        };
+        let night = QuietHours::parse(&config).unwrap();
+        assert!(night.contains(at("23:30")));
+        assert!(night.contains(at("07:59")));
+        assert!(!night.contains(at("08:00")));
+        assert!(!night.contains(at("12:00")));
+
+        let lunch = QuietHours {
+            start: at("12:00"),
+            end: at("13:00"),
+        };
+        assert!(lunch.contains(at("12:30")));
+        assert!(!lunch.contains(at("23:30")));
+
+        assert_eq!(QuietHours::parse(&Config::default()), None);
+    }
+
+    #[test]
+    fn quiet_hours_downgrade_or_suppress() {
+        let config = Config {
+            quiet_hours_start: "00:00".to_string(),
+            quiet_hours_end: "23:59".to_string(),
+            quiet_mode: QuietMode::Suppress,
+            ..Config::default()
+        };
+        let noon = NaiveTime::from_hms_opt(12, 0, 0).unwrap();
+        let mut alerts = Alerts::default();
+
+        alerts.set_quiet_hours(&config, noon);
+        alerts.raise(alert("raid-md0"));
+        assert!(alerts.take_raised().is_empty());
+
+        alerts.set_quiet_hours(
+            &Config {
+                quiet_mode: QuietMode::PopupOnly,
+                ..config
+            },
+            noon,
+        );
+        alerts.raise(alert("raid-md0"));
+        assert_eq!(alerts.take_raised().len(), 1);
+    }
+
+    #[test]
+    fn ongoing_conditions_clear_with_their_monitor() {
+        let mut alerts = Alerts {
+            quiet: Some(QuietMode::PopupOnly),
+            ..Alerts::default()
+        };
+        let critical = Alert {
+            severity: Severity::Critical,
+            ..alert("raid-md0")
+        };
+
+        alerts.sync("raid-", vec![critical]);
+        alerts.sync("temperature-", vec![alert("temperature-nvme-high")]);
+        assert_eq!(alerts.ongoing(), Some((2, Severity::Critical)));
+        alerts.sync("raid-", Vec::new());
+        assert_eq!(alerts.ongoing(), Some((1, Severity::Warning)));
+        alerts.sync("temperature-", Vec::new());
+        assert_eq!(alerts.ongoing(), None);
+        alerts.raise(alert("data-budget-2026-10-01-80"));
+        assert_eq!(alerts.ongoing(), None);
+    }
+}
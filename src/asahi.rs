@@ -0,0 +1,152 @@
+use crate::fl;
+use crate::layout;
+use crate::window::Message;
+use cosmic::iced::Length;
+use cosmic::widget;
+use cosmic::Element;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const HWMON: &str = "/sys/class/hwmon";
+
+/// Fans and power rails of the Apple System Management Controller, as exposed by the
+/// `macsmc_hwmon` driver of Asahi Linux.
+pub struct SmcMonitor {
+    chip: PathBuf,
+    /// Label and speed in RPM
+    fans: Vec<(String, u64)>,
+    /// Label and power in watts
+    power: Vec<(String, f64)>,
+}
+
+impl SmcMonitor {
+    /// `None` on anything but an Apple Silicon Mac running Asahi.
+    pub fn detect() -> Option<Self> {
+        let chip = find_chip(Path::new(HWMON))?;
+        let mut monitor = Self {
+            chip,
+            fans: Vec::new(),
+            power: Vec::new(),
+        };
+        monitor.refresh();
+        Some(monitor)
+    }
+
+    pub fn refresh(&mut self) {
+        self.fans = read_inputs(&self.chip, "fan")
+            .into_iter()
+            .map(|(label, rpm)| (label, rpm.max(0) as u64))
+            .collect();
+        // hwmon reports microwatts
+        self.power = read_inputs(&self.chip, "power")
+            .into_iter()
+            .map(|(label, microwatts)| (label, microwatts as f64 / 1_000_000.0))
+            .collect();
+    }
+
+    pub fn view(&self) -> Element<Message> {
+        let mut column = widget::column()
+            .spacing(4)
+            .width(Length::Fill)
+            .push(widget::text(fl!("smc")));
+
+        for (label, rpm) in &self.fans {
+            column = column.push(
+                layout::directional_row(vec![
+                    widget::text(label.as_str())
+                        .width(Length::Fill)
+                        .horizontal_alignment(layout::start_alignment())
+                        .into(),
+                    widget::text(fl!("fan-rpm", rpm = *rpm)).into(),
+                ])
+                .spacing(8),
+            );
+        }
+        for (label, watts) in &self.power {
+            column = column.push(
+                layout::directional_row(vec![
+                    widget::text(label.as_str())
+                        .width(Length::Fill)
+                        .horizontal_alignment(layout::start_alignment())
+                        .into(),
+                    widget::text(format!("{:.1} W", watts)).into(),
+                ])
+                .spacing(8),
+            );
+        }
+
+        column.into()
+    }
+}
+
+/// Whether the hwmon chip is the SMC of an Apple Silicon Mac.
+pub fn is_smc(chip_name: &str) -> bool {
+    chip_name.starts_with("macsmc")
+}
+
+/// Names the sensors that the device tree left unlabelled, which the driver then labels
+/// with their raw SMC key, e.g. `Tp09`.
+pub fn sensor_name(key: &str) -> Option<String> {
+    if key.len() != 4 || !key.is_ascii() {
+        return None;
+    }
+    let name = match &key[..2] {
+        "Tp" => "CPU performance core",
+        "Te" => "CPU efficiency core",
+        "Tg" => "GPU",
+        "TB" => "Battery",
+        "TH" => "SSD",
+        "TW" => "Wi-Fi",
+        "Ts" => "Palm rest",
+        _ => return None,
+    };
+    Some(format!("{} ({})", name, key))
+}
+
+fn find_chip(hwmon: &Path) -> Option<PathBuf> {
+    fs::read_dir(hwmon).ok()?.flatten().find_map(|chip| {
+        let name = fs::read_to_string(chip.path().join("name")).ok()?;
+        is_smc(name.trim()).then(|| chip.path())
+    })
+}
+
+/// Every `<kind>*_input` of the chip with its label, sorted by label.
+fn read_inputs(chip: &Path, kind: &str) -> Vec<(String, i64)> {
+    let Ok(files) = fs::read_dir(chip) else {
+        return Vec::new();
+    };
+    let mut inputs = files
+        .flatten()
+        .filter_map(|file| {
+            let file_name = file.file_name();
+            let sensor = file_name
+                .to_str()?
+                .strip_suffix("_input")
+                .filter(|name| name.starts_with(kind))?
+                .to_string();
+            let value = fs::read_to_string(file.path()).ok()?.trim().parse().ok()?;
+            let label = fs::read_to_string(chip.join(format!("{}_label", sensor)))
+                .map(|label| label.trim().to_string())
+                .unwrap_or(sensor);
+            Some((label, value))
+        })
+        .collect::<Vec<_>>();
+    inputs.sort();
+    inputs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_raw_smc_keys() {
+        assert_eq!(
+            sensor_name("Tp09").as_deref(),
+            Some("CPU performance core (Tp09)")
+        );
+        assert_eq!(sensor_name("TB0T").as_deref(), Some("Battery (TB0T)"));
+        assert_eq!(sensor_name("Battery Hotspot"), None);
+        assert_eq!(sensor_name("Tx0Z"), None);
+    }
+}
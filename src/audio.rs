@@ -0,0 +1,133 @@
+use crate::fl;
+use crate::window::Message;
+use cosmic::widget;
+use cosmic::Element;
+use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// The clock settings only change when the user or an application asks for it
+const REFRESH_EVERY: Duration = Duration::from_secs(5);
+
+/// PipeWire clock settings, from `pw-metadata -n settings`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AudioStatus {
+    /// Buffer size in samples, `None` until PipeWire answered
+    quantum: Option<u64>,
+    /// Quantum set with `clock.force-quantum`, overriding the default one
+    forced: bool,
+    rate: Option<u64>,
+}
+
+/// Reads the PipeWire clock settings on a background thread, `pw-metadata` waits for
+/// the PipeWire daemon.
+#[derive(Default)]
+pub struct AudioMonitor {
+    status: Arc<Mutex<AudioStatus>>,
+    last_run: Option<Instant>,
+    /// Set while `pw-metadata` runs, so a stuck daemon doesn't pile up threads
+    querying: Arc<AtomicBool>,
+}
+
+impl AudioMonitor {
+    /// Starts a new query when due, the results show up once it finished.
+    pub fn refresh(&mut self) {
+        if self
+            .last_run
+            .is_some_and(|time| time.elapsed() < REFRESH_EVERY)
+        {
+            return;
+        }
+        if self.querying.swap(true, Ordering::AcqRel) {
+            return;
+        }
+        self.last_run = Some(Instant::now());
+
+        let status = self.status.clone();
+        let querying = self.querying.clone();
+        thread::spawn(move || {
+            let result = Command::new("pw-metadata")
+                .args(["-n", "settings"])
+                .output()
+                .ok()
+                .filter(|output| output.status.success())
+                .map(|output| parse(&String::from_utf8_lossy(&output.stdout)))
+                .unwrap_or_default();
+            if let Ok(mut status) = status.lock() {
+                *status = result;
+            }
+            querying.store(false, Ordering::Release);
+        });
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.status
+            .lock()
+            .map_or(true, |status| status.quantum.is_none())
+    }
+
+    pub fn view(&self) -> Element<Message> {
+        let status = self
+            .status
+            .lock()
+            .map(|status| status.clone())
+            .unwrap_or_default();
+        let quantum = status.quantum.unwrap_or_default();
+        let rate = status.rate.unwrap_or_default();
+        let text = if status.forced {
+            fl!("audio-quantum-forced", quantum = quantum, rate = rate)
+        } else {
+            fl!("audio-quantum", quantum = quantum, rate = rate)
+        };
+        widget::text(text).into()
+    }
+}
+
+/// Parses lines like `update: id:0 key:'clock.quantum' value:'1024' type:''`.
+fn parse(output: &str) -> AudioStatus {
+    let value = |key: &str| {
+        let key = format!("key:'{}'", key);
+        output.lines().find_map(|line| {
+            let (_, rest) = line.split_once(key.as_str())?;
+            let value = rest.trim().strip_prefix("value:'")?;
+            value.split('\'').next()?.parse::<u64>().ok()
+        })
+    };
+    let forced = value("clock.force-quantum").filter(|quantum| *quantum > 0);
+    AudioStatus {
+        quantum: forced.or_else(|| value("clock.quantum")),
+        forced: forced.is_some(),
+        rate: value("clock.force-rate")
+            .filter(|rate| *rate > 0)
+            .or_else(|| value("clock.rate")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn forced_quantum_overrides_the_default() {
+        let output = "\
+Found \"settings\" metadata 32
+update: id:0 key:'log.level' value:'2' type:''
+update: id:0 key:'clock.rate' value:'48000' type:''
+update: id:0 key:'clock.quantum' value:'1024' type:''
+update: id:0 key:'clock.force-quantum' value:'128' type:''
+update: id:0 key:'clock.force-rate' value:'0' type:''
+";
+
+        assert_eq!(
+            parse(output),
+            AudioStatus {
+                quantum: Some(128),
+                forced: true,
+                rate: Some(48000),
+            }
+        );
+        assert!(parse("").is_empty());
+    }
+}
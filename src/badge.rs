@@ -0,0 +1,129 @@
+//! A colored dot with a count over the corner of the panel icon.
+
+use crate::window::Message;
+use cosmic::iced::alignment::{Horizontal, Vertical};
+use cosmic::iced::{Color, Length, Point, Rectangle, Size};
+use cosmic::iced_core::renderer::{self, Quad, Renderer as _};
+use cosmic::iced_core::text::{self, LineHeight, Renderer as _, Shaping};
+use cosmic::iced_core::widget::Tree;
+use cosmic::iced_core::{layout, mouse, Background, Border, Layout, Pixels, Shadow, Widget};
+use cosmic::{Element, Renderer, Theme};
+
+/// Share of the icon's height the dot covers
+const DOT_SHARE: f32 = 0.5;
+/// Counts above this show as `9+`, more digits don't fit the dot
+const MAX_COUNT: usize = 9;
+
+/// Draws `content` with a dot in `color` over its top right corner, numbered when
+/// `count` is above one.
+pub struct Badge<'a> {
+    content: Element<'a, Message>,
+    count: usize,
+    color: Color,
+}
+
+impl<'a> Badge<'a> {
+    pub fn new(content: impl Into<Element<'a, Message>>, count: usize, color: Color) -> Self {
+        Self {
+            content: content.into(),
+            count,
+            color,
+        }
+    }
+}
+
+impl<'a> Widget<Message, Theme, Renderer> for Badge<'a> {
+    fn size(&self) -> Size<Length> {
+        self.content.as_widget().size()
+    }
+
+    fn children(&self) -> Vec<Tree> {
+        vec![Tree::new(&self.content)]
+    }
+
+    fn diff(&mut self, tree: &mut Tree) {
+        tree.diff_children(std::slice::from_mut(&mut self.content));
+    }
+
+    fn layout(
+        &self,
+        tree: &mut Tree,
+        renderer: &Renderer,
+        limits: &layout::Limits,
+    ) -> layout::Node {
+        // The dot is drawn over the content, it takes up no space of its own
+        self.content
+            .as_widget()
+            .layout(&mut tree.children[0], renderer, limits)
+    }
+
+    fn draw(
+        &self,
+        tree: &Tree,
+        renderer: &mut Renderer,
+        theme: &Theme,
+        style: &renderer::Style,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        viewport: &Rectangle,
+    ) {
+        self.content.as_widget().draw(
+            &tree.children[0],
+            renderer,
+            theme,
+            style,
+            layout,
+            cursor,
+            viewport,
+        );
+
+        let icon = layout.bounds();
+        let diameter = (icon.height * DOT_SHARE).round();
+        let dot = Rectangle {
+            x: icon.x + icon.width - diameter,
+            y: icon.y,
+            width: diameter,
+            height: diameter,
+        };
+        renderer.fill_quad(
+            Quad {
+                bounds: dot,
+                border: Border {
+                    radius: (diameter / 2.0).into(),
+                    ..Border::default()
+                },
+                shadow: Shadow::default(),
+            },
+            Background::Color(self.color),
+        );
+
+        if self.count > 1 {
+            let count = if self.count > MAX_COUNT {
+                format!("{}+", MAX_COUNT)
+            } else {
+                self.count.to_string()
+            };
+            renderer.fill_text(
+                text::Text {
+                    content: &count,
+                    bounds: dot.size(),
+                    size: Pixels(diameter * 0.75),
+                    line_height: LineHeight::default(),
+                    font: renderer.default_font(),
+                    horizontal_alignment: Horizontal::Center,
+                    vertical_alignment: Vertical::Center,
+                    shaping: Shaping::Basic,
+                },
+                Point::new(dot.center_x(), dot.center_y()),
+                Color::WHITE,
+                *viewport,
+            );
+        }
+    }
+}
+
+impl<'a> From<Badge<'a>> for Element<'a, Message> {
+    fn from(badge: Badge<'a>) -> Self {
+        Element::new(badge)
+    }
+}
@@ -0,0 +1,82 @@
+use crate::fl;
+use crate::window::Message;
+use chrono::{DateTime, Local, TimeZone};
+use cosmic::widget;
+use cosmic::Element;
+use std::process::Command;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use sysinfo::System;
+
+/// When the system booted and, once `systemd-analyze` answered, how long it took.
+pub struct BootInfo {
+    booted_at: Option<DateTime<Local>>,
+    duration: Arc<Mutex<Option<String>>>,
+}
+
+impl BootInfo {
+    /// Reads the boot time and asks `systemd-analyze` on a background thread, it can
+    /// take a moment and fails while the boot is still in progress.
+    pub fn load() -> Self {
+        let duration = Arc::new(Mutex::new(None));
+        let result = duration.clone();
+        thread::spawn(move || {
+            let Ok(output) = Command::new("systemd-analyze").output() else {
+                return;
+            };
+            if !output.status.success() {
+                return;
+            }
+            if let Ok(mut result) = result.lock() {
+                *result = parse_duration(&String::from_utf8_lossy(&output.stdout));
+            }
+        });
+
+        Self {
+            booted_at: Local.timestamp_opt(System::boot_time() as i64, 0).single(),
+            duration,
+        }
+    }
+
+    pub fn view(&self) -> Element<Message> {
+        let Some(booted_at) = self.booted_at else {
+            return widget::text("").into();
+        };
+        let booted_at = booted_at.format("%a %H:%M").to_string();
+        let duration = self
+            .duration
+            .lock()
+            .map(|duration| duration.clone())
+            .unwrap_or_default();
+        let text = match duration.as_deref() {
+            Some(duration) => fl!("booted-in", time = booted_at.as_str(), duration = duration),
+            None => fl!("booted", time = booted_at.as_str()),
+        };
+        widget::text(text).into()
+    }
+}
+
+/// Total from e.g. `Startup finished in 3.2s (firmware) + ... = 11.8s`.
+fn parse_duration(output: &str) -> Option<String> {
+    let line = output
+        .lines()
+        .find(|line| line.starts_with("Startup finished"))?;
+    let (_, total) = line.rsplit_once(" = ")?;
+    Some(total.trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_the_total_boot_duration() {
+        let output = "\
+Startup finished in 6.113s (firmware) + 1.218s (loader) + 1.727s (kernel) + 4.987s (userspace) = 14.046s 
+graphical.target reached after 4.972s in userspace.
+";
+
+        assert_eq!(parse_duration(output), Some("14.046s".to_string()));
+        assert_eq!(parse_duration("Bootup is not yet finished.\n"), None);
+    }
+}
@@ -0,0 +1,122 @@
+use crate::alerts::{Alert, Severity};
+use crate::config::{BudgetPeriod, Config};
+use crate::fl;
+use crate::network::NetworkTotals;
+use crate::state;
+use chrono::{Datelike, Local, NaiveDate};
+use human_bytes::human_bytes;
+use serde::{Deserialize, Serialize};
+
+const BUDGET_FILE: &str = "data-budget.bin";
+// Percentages of the budget at which a notification is sent
+const THRESHOLDS: [(u64, Severity); 2] = [(80, Severity::Warning), (100, Severity::Critical)];
+
+/// Running total of the budgeted interface, persisted so it survives restarts.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+struct BudgetState {
+    interface: String,
+    period_start: Option<NaiveDate>,
+    used: u64,
+    /// Kernel counter at the last update, to compute deltas
+    last_counter: Option<u64>,
+    /// Highest threshold already notified in this period
+    notified: u64,
+}
+
+pub struct DataBudget {
+    state: BudgetState,
+    dirty: bool,
+}
+
+impl DataBudget {
+    pub fn load() -> Self {
+        Self {
+            state: state::load(BUDGET_FILE).unwrap_or_default(),
+            dirty: false,
+        }
+    }
+
+    pub fn save(&mut self) {
+        if !self.dirty {
+            return;
+        }
+        match state::save(BUDGET_FILE, &self.state) {
+            Ok(()) => self.dirty = false,
+            Err(err) => tracing::warn!("failed to save data budget: {}", err),
+        }
+    }
+
+    /// Accounts the traffic since the last update and returns the thresholds crossed.
+    pub fn update(&mut self, config: &Config, network: &NetworkTotals) -> Vec<Alert> {
+        if config.data_budget_bytes == 0 || config.data_budget_interface.is_empty() {
+            return Vec::new();
+        }
+
+        let period_start = period_start(config.data_budget_period, Local::now().date_naive());
+        if self.state.interface != config.data_budget_interface
+            || self.state.period_start != Some(period_start)
+        {
+            self.state = BudgetState {
+                interface: config.data_budget_interface.clone(),
+                period_start: Some(period_start),
+                last_counter: self.state.last_counter,
+                ..Default::default()
+            };
+            self.dirty = true;
+        }
+
+        let Some(counter) = network.boot_total(&config.data_budget_interface) else {
+            return Vec::new();
+        };
+        // A counter lower than the last one means the machine rebooted and it restarted at zero
+        let delta = match self.state.last_counter {
+            Some(last) if counter >= last => counter - last,
+            Some(_) => counter,
+            None => 0,
+        };
+        self.state.last_counter = Some(counter);
+        if delta > 0 {
+            self.state.used += delta;
+            self.dirty = true;
+        }
+
+        let percent = self.state.used.saturating_mul(100) / config.data_budget_bytes;
+        let mut alerts = Vec::new();
+        for (threshold, severity) in THRESHOLDS {
+            if percent >= threshold && self.state.notified < threshold {
+                self.state.notified = threshold;
+                self.dirty = true;
+                alerts.push(Alert {
+                    key: format!("data-budget-{}-{}", period_start, threshold),
+                    summary: fl!(
+                        "data-budget-alert",
+                        percent = threshold,
+                        interface = config.data_budget_interface.as_str()
+                    ),
+                    body: self.usage(config),
+                    severity,
+                });
+            }
+        }
+        alerts
+    }
+
+    /// Human readable usage of the current period, e.g. "3.2 GB of 10 GB this month".
+    pub fn usage(&self, config: &Config) -> String {
+        let used = human_bytes(self.state.used as f64);
+        let budget = human_bytes(config.data_budget_bytes as f64);
+        match config.data_budget_period {
+            BudgetPeriod::Daily => fl!("data-budget-usage-daily", used = used, budget = budget),
+            BudgetPeriod::Monthly => {
+                fl!("data-budget-usage-monthly", used = used, budget = budget)
+            }
+        }
+    }
+}
+
+fn period_start(period: BudgetPeriod, today: NaiveDate) -> NaiveDate {
+    match period {
+        BudgetPeriod::Daily => today,
+        BudgetPeriod::Monthly => today.with_day(1).unwrap_or(today),
+    }
+}
@@ -0,0 +1,109 @@
+use crate::config::Config;
+use crate::fl;
+use crate::window::Message;
+use chrono::{DateTime, Local, Utc};
+use cosmic::iced::Length;
+use cosmic::widget;
+use cosmic::Element;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+const KEPT_PERIODS: usize = 10;
+
+/// A stretch of time during which the CPU stayed above `busy_threshold`.
+#[derive(Clone, Copy, Debug)]
+pub struct BusyPeriod {
+    pub started_at: Instant,
+    /// `None` while the period is still going on
+    pub ended_at: Option<Instant>,
+    /// Wall clock time of the start, for display
+    pub start: DateTime<Utc>,
+    pub end: Option<DateTime<Utc>>,
+    pub peak: f64,
+}
+
+impl BusyPeriod {
+    fn lasted(&self, config: &Config) -> bool {
+        let end = self.ended_at.unwrap_or_else(Instant::now);
+        end.saturating_duration_since(self.started_at)
+            >= Duration::from_secs(config.busy_min_seconds as u64)
+    }
+}
+
+/// Records the periods the CPU was pegged, so they can be found after coming back.
+#[derive(Default)]
+pub struct BusyDetector {
+    current: Option<BusyPeriod>,
+    /// Finished periods, newest first
+    periods: VecDeque<BusyPeriod>,
+}
+
+impl BusyDetector {
+    /// Feeds a CPU usage sample, returning whether the recorded periods changed.
+    pub fn update(&mut self, config: &Config, cpu: f64) -> bool {
+        if config.busy_threshold == 0 {
+            return false;
+        }
+
+        if cpu >= config.busy_threshold as f64 {
+            let period = self.current.get_or_insert_with(|| BusyPeriod {
+                started_at: Instant::now(),
+                ended_at: None,
+                start: Utc::now(),
+                end: None,
+                peak: cpu,
+            });
+            period.peak = period.peak.max(cpu);
+            return period.lasted(config);
+        }
+
+        let Some(mut period) = self.current.take() else {
+            return false;
+        };
+        // Short bursts are normal, only keep the sustained ones
+        if !period.lasted(config) {
+            return false;
+        }
+        period.ended_at = Some(Instant::now());
+        period.end = Some(Utc::now());
+        self.periods.push_front(period);
+        self.periods.truncate(KEPT_PERIODS);
+        true
+    }
+
+    /// Recorded periods including the ongoing one, once it lasted long enough.
+    pub fn periods(&self, config: &Config) -> Vec<BusyPeriod> {
+        self.current
+            .filter(|period| period.lasted(config))
+            .into_iter()
+            .chain(self.periods.iter().copied())
+            .collect()
+    }
+
+    pub fn view(&self, config: &Config) -> Element<Message> {
+        let mut column = widget::column()
+            .spacing(4)
+            .width(Length::Fill)
+            .push(widget::text(fl!("busy-periods")));
+
+        for period in self.periods(config) {
+            let start = period.start.with_timezone(&Local).format("%H:%M");
+            let end = match period.end {
+                Some(end) => end.with_timezone(&Local).format("%H:%M").to_string(),
+                None => fl!("busy-ongoing"),
+            };
+            column = column.push(widget::text(fl!(
+                "busy-period",
+                start = start.to_string(),
+                end = end,
+                peak = format!("{:.0}", period.peak)
+            )));
+        }
+
+        column.into()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.current.is_none() && self.periods.is_empty()
+    }
+}
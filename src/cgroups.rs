@@ -0,0 +1,275 @@
+use crate::fl;
+use crate::layout;
+use crate::window::Message;
+use cosmic::iced::Length;
+use cosmic::widget;
+use cosmic::Element;
+use human_bytes::human_bytes;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+const CGROUP_ROOT: &str = "/sys/fs/cgroup";
+const SHOWN_GROUPS: usize = 10;
+const USER_SLICE: &str = "user.slice";
+
+/// Resource usage of a systemd slice, scope or service, from its cgroup v2 files.
+#[derive(Clone, Debug)]
+pub struct GroupUsage {
+    pub name: String,
+    /// Share of the whole machine's CPU time
+    pub cpu_percent: f64,
+    /// Bytes, including page cache charged to the group
+    pub memory: u64,
+}
+
+#[derive(Default)]
+pub struct CgroupMonitor {
+    previous_usage: HashMap<PathBuf, u64>,
+    previous_time: Option<Instant>,
+    groups: Vec<GroupUsage>,
+}
+
+impl CgroupMonitor {
+    pub fn refresh(&mut self) {
+        let now = Instant::now();
+        let elapsed_usec = self
+            .previous_time
+            .map(|time| now.duration_since(time).as_micros() as f64)
+            .unwrap_or_default();
+        let cpus = std::thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(1) as f64;
+
+        let mut usage = HashMap::new();
+        self.groups = candidate_groups(Path::new(CGROUP_ROOT))
+            .into_iter()
+            .filter_map(|path| {
+                let memory = read_u64(&path.join("memory.current")).unwrap_or_default();
+                let cpu_usec = read_cpu_usage(&path)?;
+                let cpu_percent = match self.previous_usage.get(&path) {
+                    Some(previous) if elapsed_usec > 0.0 => {
+                        cpu_usec.saturating_sub(*previous) as f64 / (elapsed_usec * cpus) * 100.0
+                    }
+                    _ => 0.0,
+                };
+                let name = group_name(&path);
+                usage.insert(path, cpu_usec);
+
+                Some(GroupUsage {
+                    name,
+                    cpu_percent,
+                    memory,
+                })
+            })
+            .collect();
+        self.groups.sort_by(|a, b| b.memory.cmp(&a.memory));
+
+        self.previous_usage = usage;
+        self.previous_time = Some(now);
+    }
+
+    pub fn view(&self) -> Element<Message> {
+        let mut column = widget::column()
+            .spacing(4)
+            .width(Length::Fill)
+            .push(widget::text(fl!("resource-groups")));
+
+        for group in self.groups.iter().take(SHOWN_GROUPS) {
+            column = column.push(
+                layout::directional_row(vec![
+                    widget::text(group.name.as_str())
+                        .width(Length::Fill)
+                        .horizontal_alignment(layout::start_alignment())
+                        .into(),
+                    widget::text(format!("{:.1}%", group.cpu_percent)).into(),
+                    widget::text(human_bytes(group.memory as f64)).into(),
+                ])
+                .spacing(8),
+            );
+        }
+
+        column.into()
+    }
+}
+
+/// Resource usage per logged in user, from the `user-<uid>.slice` cgroups.
+#[derive(Default)]
+pub struct UserMonitor {
+    previous_usage: HashMap<u32, u64>,
+    previous_time: Option<Instant>,
+    users: Vec<GroupUsage>,
+    names: HashMap<u32, String>,
+}
+
+impl UserMonitor {
+    pub fn refresh(&mut self) {
+        let now = Instant::now();
+        let elapsed_usec = self
+            .previous_time
+            .map(|time| now.duration_since(time).as_micros() as f64)
+            .unwrap_or_default();
+        let cpus = std::thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(1) as f64;
+        if self.names.is_empty() {
+            self.names = user_names();
+        }
+
+        let mut usage = HashMap::new();
+        self.users = child_groups(&Path::new(CGROUP_ROOT).join(USER_SLICE))
+            .into_iter()
+            .filter_map(|path| {
+                let uid: u32 = file_name(&path)
+                    .strip_prefix("user-")?
+                    .strip_suffix(".slice")?
+                    .parse()
+                    .ok()?;
+                let memory = read_u64(&path.join("memory.current")).unwrap_or_default();
+                let cpu_usec = read_cpu_usage(&path)?;
+                let cpu_percent = match self.previous_usage.get(&uid) {
+                    Some(previous) if elapsed_usec > 0.0 => {
+                        cpu_usec.saturating_sub(*previous) as f64 / (elapsed_usec * cpus) * 100.0
+                    }
+                    _ => 0.0,
+                };
+                usage.insert(uid, cpu_usec);
+
+                Some(GroupUsage {
+                    name: self
+                        .names
+                        .get(&uid)
+                        .cloned()
+                        .unwrap_or_else(|| uid.to_string()),
+                    cpu_percent,
+                    memory,
+                })
+            })
+            .collect();
+        self.users
+            .sort_by(|a, b| b.cpu_percent.total_cmp(&a.cpu_percent));
+
+        self.previous_usage = usage;
+        self.previous_time = Some(now);
+    }
+
+    pub fn view(&self) -> Element<Message> {
+        let mut column = widget::column()
+            .spacing(4)
+            .width(Length::Fill)
+            .push(widget::text(fl!("user-sessions")));
+
+        for user in &self.users {
+            column = column.push(
+                layout::directional_row(vec![
+                    widget::text(user.name.as_str())
+                        .width(Length::Fill)
+                        .horizontal_alignment(layout::start_alignment())
+                        .into(),
+                    widget::text(format!("{:.1}%", user.cpu_percent)).into(),
+                    widget::text(human_bytes(user.memory as f64)).into(),
+                ])
+                .spacing(8),
+            );
+        }
+
+        column.into()
+    }
+}
+
+/// Login names by uid from `/etc/passwd`.
+fn user_names() -> HashMap<u32, String> {
+    let Ok(passwd) = fs::read_to_string("/etc/passwd") else {
+        return HashMap::new();
+    };
+    passwd
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split(':');
+            let name = fields.next()?;
+            let uid = fields.nth(1)?.parse().ok()?;
+            Some((uid, name.to_string()))
+        })
+        .collect()
+}
+
+/// The groups worth showing: top level slices, system services and the apps and
+/// sessions of every logged in user.
+fn candidate_groups(root: &Path) -> Vec<PathBuf> {
+    let mut groups = Vec::new();
+    for top in child_groups(root) {
+        let name = file_name(&top);
+        if name == "system.slice" {
+            groups.extend(child_groups(&top));
+        }
+        if name == USER_SLICE {
+            for user in child_groups(&top) {
+                for service in child_groups(&user) {
+                    if file_name(&service).starts_with("user@") {
+                        for slice in child_groups(&service) {
+                            groups.extend(child_groups(&slice));
+                        }
+                    } else {
+                        groups.push(service);
+                    }
+                }
+            }
+        }
+        groups.push(top);
+    }
+    groups
+}
+
+fn child_groups(path: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(path) else {
+        return Vec::new();
+    };
+    entries
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().map(|t| t.is_dir()).unwrap_or(false))
+        .map(|entry| entry.path())
+        .filter(|path| {
+            let name = file_name(path);
+            name.ends_with(".slice") || name.ends_with(".scope") || name.ends_with(".service")
+        })
+        .collect()
+}
+
+fn file_name(path: &Path) -> &str {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or_default()
+}
+
+fn group_name(path: &Path) -> String {
+    let name = file_name(path);
+    name.strip_suffix(".scope")
+        .or_else(|| name.strip_suffix(".slice"))
+        .or_else(|| name.strip_suffix(".service"))
+        .unwrap_or(name)
+        .to_string()
+}
+
+fn read_u64(path: &Path) -> Option<u64> {
+    fs::read_to_string(path).ok()?.trim().parse().ok()
+}
+
+fn read_cpu_usage(path: &Path) -> Option<u64> {
+    fs::read_to_string(path.join("cpu.stat"))
+        .ok()?
+        .lines()
+        .find_map(|line| line.strip_prefix("usage_usec "))
+        .and_then(|usec| usec.trim().parse().ok())
+}
+
+/// Flatpak application a process belongs to, from its `app-flatpak-<app id>-<n>.scope` cgroup.
+pub fn flatpak_app_id(pid: u32) -> Option<String> {
+    let cgroup = fs::read_to_string(format!("/proc/{}/cgroup", pid)).ok()?;
+    cgroup.split('/').find_map(|segment| {
+        let scope = segment.trim().strip_prefix("app-flatpak-")?;
+        let scope = scope.strip_suffix(".scope")?;
+        let (app_id, _) = scope.rsplit_once('-')?;
+        Some(app_id.to_string())
+    })
+}
@@ -1,207 +1,1039 @@
+use crate::config::{ChartDensity, ChartPalette, ChartRendering, Config};
+use crate::fl;
+use crate::history::{History, SeriesHistory};
+use crate::layout::{self, MetricView, PreferredSize, ViewOptions};
+use crate::metrics::{self, Sample, Sampler, Scale, SeriesId, SourceConfig};
+use crate::power;
 use crate::window::Message;
 use chrono::{DateTime, Utc};
 use cosmic::iced::alignment::{Horizontal, Vertical};
 use cosmic::iced::Size;
 use cosmic::iced_renderer::Geometry;
 use cosmic::iced_widget::canvas::Frame;
-use cosmic::iced_widget::Column;
+use cosmic::iced_widget::{Column, ProgressBar};
 use cosmic::widget::Text;
 use cosmic::Element;
 use cosmic::{
-    iced::{
-        widget::{Row, Scrollable},
-        Alignment, Length,
-    },
+    iced::{Alignment, Length},
     iced_widget::canvas::Cache,
 };
 use plotters::prelude::*;
 use plotters_iced::{Chart, ChartBuilder, ChartWidget};
 use std::{
-    collections::VecDeque,
+    cell::Cell,
+    collections::{BTreeMap, BTreeSet, HashMap, VecDeque},
+    ops::Range,
     time::{Duration, Instant},
 };
-use sysinfo::{CpuRefreshKind, RefreshKind, System};
 
 const PLOT_SECONDS: usize = 60;
 const SAMPLE_EVERY: Duration = Duration::from_millis(1000);
+/// Lower bound of the configured `sample_interval_ms`
+const MIN_SAMPLE_EVERY: Duration = Duration::from_millis(250);
+/// Upper bound of the configured `sample_interval_ms`, the plot shows a minute
+const MAX_SAMPLE_EVERY: Duration = Duration::from_secs(PLOT_SECONDS as u64);
+// Applied with `reduced_motion`, so the charts move less often
+const REDUCED_MOTION_SAMPLE_EVERY: Duration = Duration::from_millis(2000);
+/// Ticks arrive at the sample interval, allow them to be a little early
+const TICK_SLACK: Duration = Duration::from_millis(50);
+/// Wall clock and monotonic time drifting apart by more than this between two samples
+/// means the system was suspended or the clock was set
+const CLOCK_JUMP: Duration = Duration::from_secs(5);
+/// Drawing a chart taking longer than this points to software rendering
+const SLOW_FRAME: Duration = Duration::from_millis(50);
+/// Consecutive samples with a slow frame before `ChartRendering::Auto` falls back to bars
+const SLOW_FRAMES: usize = 3;
+/// Narrowest a chart stays readable at, two fit side by side in a wide window
+const MIN_CHART_WIDTH: f32 = 300.0;
+/// Between views sharing a row
+const VIEW_SPACING: f32 = 8.0;
+/// Height of every chart in the sparkline strip, in logical pixels
+const SPARKLINE_HEIGHT: f32 = 40.0;
+const SPARKLINE_TITLE_WIDTH: f32 = 80.0;
+/// Sources charted in the sparkline strip, in this order
+const SPARKLINE_SOURCES: [&str; 3] = ["CPU", "Memory", "Network"];
+
+/// Colors used to draw the charts, derived from the current theme.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ChartStyle {
+    /// Plotted data
+    pub accent: RGBColor,
+    /// Mesh lines
+    pub grid: RGBColor,
+    /// Axis and labels
+    pub label: RGBColor,
+    /// Colors of the series, `Accent` plots the secondary ones in the label color
+    pub palette: ChartPalette,
+}
+
+impl ChartStyle {
+    /// Color and opacity of the series at `index`.
+    pub fn series_color(&self, index: usize) -> (RGBColor, f64) {
+        let colors: &[RGBColor] = match self.palette {
+            ChartPalette::Accent if index == 0 => return (self.accent, 1.0),
+            ChartPalette::Accent => return (self.label, 0.6),
+            ChartPalette::OkabeIto => &OKABE_ITO,
+            ChartPalette::TolBright => &TOL_BRIGHT,
+            ChartPalette::Ibm => &IBM,
+        };
+        (colors[index % colors.len()], 1.0)
+    }
+}
+
+// Ordered so the first series get the most distinct hues
+const OKABE_ITO: [RGBColor; 7] = [
+    RGBColor(0x56, 0xb4, 0xe9),
+    RGBColor(0xe6, 0x9f, 0x00),
+    RGBColor(0x00, 0x9e, 0x73),
+    RGBColor(0xcc, 0x79, 0xa7),
+    RGBColor(0xf0, 0xe4, 0x42),
+    RGBColor(0x00, 0x72, 0xb2),
+    RGBColor(0xd5, 0x5e, 0x00),
+];
+const TOL_BRIGHT: [RGBColor; 7] = [
+    RGBColor(0x44, 0x77, 0xaa),
+    RGBColor(0xee, 0x66, 0x77),
+    RGBColor(0x22, 0x88, 0x33),
+    RGBColor(0xcc, 0xbb, 0x44),
+    RGBColor(0x66, 0xcc, 0xee),
+    RGBColor(0xaa, 0x33, 0x77),
+    RGBColor(0xbb, 0xbb, 0xbb),
+];
+const IBM: [RGBColor; 5] = [
+    RGBColor(0x64, 0x8f, 0xff),
+    RGBColor(0xfe, 0x61, 0x00),
+    RGBColor(0x78, 0x5e, 0xf0),
+    RGBColor(0xff, 0xb0, 0x00),
+    RGBColor(0xdc, 0x26, 0x7f),
+];
+
+/// Font of the axis labels, sized in logical pixels and scaled with the display.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LabelFont {
+    pub family: String,
+    pub size: u32,
+}
+
+impl Default for LabelFont {
+    fn default() -> Self {
+        Self {
+            family: "sans-serif".to_string(),
+            size: 8,
+        }
+    }
+}
+
+impl LabelFont {
+    fn from_config(config: &Config) -> Self {
+        let default = Self::default();
+        Self {
+            family: if config.chart_label_font.trim().is_empty() {
+                default.family
+            } else {
+                config.chart_label_font.trim().to_string()
+            },
+            // Beyond that the labels crowd out the plot
+            size: config.chart_label_size.clamp(6, 24),
+        }
+    }
+}
+
+struct ChartEntry {
+    source: String,
+    title: String,
+    chart: UsageChart,
+    /// Text mirroring the plotted data for screen readers, regenerated every sample
+    summary: String,
+}
 
 pub struct SystemChart {
-    sys: System,
+    sampler: Sampler,
+    /// What the sources were built from, `None` for a sampler built elsewhere
+    source_config: Option<SourceConfig>,
     last_sample_time: Instant,
-    cpu: Option<PercentualUsageChart>,
-    memory: Option<PercentualUsageChart>,
-    chart_height: f32,
-    color: RGBColor,
+    /// Wall clock time of the last sample, compared against `last_sample_time`
+    last_sample_wall: Option<DateTime<Utc>>,
+    charts: Vec<ChartEntry>,
+    /// Sources that returned no data on the last sample, with their placeholders
+    unavailable: BTreeMap<String, Unavailable>,
+    density: ChartDensity,
+    /// Show `ChartEntry::summary` under every chart
+    text_summary: bool,
+    /// Physical pixels per logical pixel of the surface the charts are shown on
+    scale_factor: f64,
+    label_font: LabelFont,
+    /// Value axis labels on the right, for right-to-left languages by default
+    labels_right: bool,
+    on_battery: bool,
+    reduced_motion: bool,
+    rendering: ChartRendering,
+    /// Samples in a row whose slowest chart took longer than `SLOW_FRAME` to draw
+    slow_frames: usize,
+    /// Drawing turned out to be too slow, show bars instead with `ChartRendering::Auto`
+    fallback: bool,
+    style: ChartStyle,
+    visible: bool,
+    sample_every: Duration,
+    /// From `sample_interval_ms`, before battery and reduced motion slow it down
+    base_sample_every: Duration,
+    latest: Vec<Sample>,
+    /// Time ranges highlighted on every chart
+    bands: Vec<Band>,
+    /// Points in time marked with a vertical line on every chart
+    markers: Vec<Instant>,
 }
 
+/// Placeholder of a source that returned no data on the last sample.
+struct Unavailable {
+    source: String,
+    title: String,
+}
+
+impl MetricView for Unavailable {
+    fn name(&self) -> &str {
+        &self.source
+    }
+
+    fn preferred_size(&self, options: &ViewOptions) -> PreferredSize {
+        PreferredSize {
+            width: MIN_CHART_WIDTH,
+            height: options.density.chart_height(),
+        }
+    }
+
+    fn view(&self, _options: &ViewOptions) -> Element<Message> {
+        unavailable_placeholder(&self.title)
+    }
+}
+
+impl MetricView for ChartEntry {
+    fn name(&self) -> &str {
+        &self.source
+    }
+
+    fn preferred_size(&self, options: &ViewOptions) -> PreferredSize {
+        PreferredSize {
+            width: MIN_CHART_WIDTH,
+            height: options.density.chart_height(),
+        }
+    }
+
+    fn view(&self, options: &ViewOptions) -> Element<Message> {
+        let chart = if options.simple {
+            self.chart.simple_view(&self.title)
+        } else {
+            let height = self.preferred_size(options).height;
+            self.chart.view(&self.title, height)
+        };
+        if options.text_summary && !self.summary.is_empty() {
+            Column::with_children(vec![chart, Text::new(self.summary.as_str()).into()])
+                .spacing(4)
+                .width(Length::Fill)
+                .into()
+        } else {
+            chart
+        }
+    }
+}
+
+/// Highlighted time range, `end` of `None` extends to the newest sample.
+pub type Band = (Instant, Option<Instant>);
+
 impl SystemChart {
-    pub fn new(color: RGBColor) -> Self {
+    pub fn new(style: ChartStyle, config: &Config) -> Self {
+        let mut chart = Self::with_sampler(style, Sampler::new(metrics::sources(config)));
+        chart.source_config = Some(SourceConfig::new(config));
+        chart.density = config.chart_density;
+        chart.label_font = LabelFont::from_config(config);
+        chart.labels_right = config.chart_axis_side.is_right();
+        chart.text_summary = config.text_summary;
+        chart.set_sample_interval(config.sample_interval_ms);
+        chart.set_reduced_motion(config.reduced_motion);
+        chart.set_rendering(config.chart_rendering);
+        chart
+    }
+
+    pub fn with_sampler(style: ChartStyle, mut sampler: Sampler) -> Self {
+        sampler.prime();
         Self {
-            sys: System::new_with_specifics(
-                RefreshKind::new()
-                    .with_cpu(CpuRefreshKind::new().with_cpu_usage())
-                    .without_processes(),
-            ),
-            color,
+            sampler,
+            source_config: None,
+            style,
             last_sample_time: Instant::now(),
-            chart_height: 180.0,
-            cpu: None,
-            memory: None,
+            last_sample_wall: None,
+            density: ChartDensity::default(),
+            text_summary: false,
+            scale_factor: 1.0,
+            label_font: LabelFont::default(),
+            labels_right: false,
+            on_battery: false,
+            reduced_motion: false,
+            rendering: ChartRendering::default(),
+            slow_frames: 0,
+            fallback: false,
+            charts: Vec::new(),
+            unavailable: BTreeMap::new(),
+            visible: false,
+            sample_every: SAMPLE_EVERY,
+            base_sample_every: SAMPLE_EVERY,
+            latest: Vec::new(),
+            bands: Vec::new(),
+            markers: Vec::new(),
         }
     }
 }
 
 impl SystemChart {
+    /// Applies a config change. The metric sources are only rebuilt when a setting they
+    /// are built from changed, dropping charts of removed ones.
+    pub fn set_config(&mut self, config: &Config) {
+        let source_config = SourceConfig::new(config);
+        if self.source_config.as_ref() != Some(&source_config) {
+            let sources = metrics::sources(config);
+            let names = sources
+                .iter()
+                .map(|source| source.name().to_string())
+                .collect::<BTreeSet<_>>();
+            self.charts.retain(|entry| names.contains(&entry.source));
+            self.unavailable.retain(|source, _| names.contains(source));
+            self.sampler.set_sources(sources);
+            self.source_config = Some(source_config);
+        }
+        self.text_summary = config.text_summary;
+        self.set_sample_interval(config.sample_interval_ms);
+        self.set_reduced_motion(config.reduced_motion);
+        self.set_rendering(config.chart_rendering);
+
+        if config.chart_density != self.density {
+            self.density = config.chart_density;
+            for entry in &mut self.charts {
+                entry.chart.margin = self.density.margin();
+                entry.chart.dirty = true;
+                if self.visible {
+                    entry.chart.invalidate();
+                }
+            }
+        }
+
+        let label_font = LabelFont::from_config(config);
+        let labels_right = config.chart_axis_side.is_right();
+        if label_font != self.label_font || labels_right != self.labels_right {
+            self.label_font = label_font;
+            self.labels_right = labels_right;
+            for entry in &mut self.charts {
+                entry.chart.label_font = self.label_font.clone();
+                entry.chart.labels_right = self.labels_right;
+                entry.chart.dirty = true;
+                if self.visible {
+                    entry.chart.invalidate();
+                }
+            }
+        }
+    }
+
+    /// Recolors every chart, e.g. after the accent color or theme mode changed.
+    pub fn set_style(&mut self, style: ChartStyle) {
+        if style == self.style {
+            return;
+        }
+
+        self.style = style;
+        for entry in &mut self.charts {
+            entry.chart.style = style;
+            entry.chart.dirty = true;
+            if self.visible {
+                entry.chart.invalidate();
+            }
+        }
+    }
+
+    /// Lays the charts out for the surface's scale factor, e.g. 1.25 at 125%.
+    pub fn set_scale_factor(&mut self, scale_factor: f64) {
+        if scale_factor == self.scale_factor || scale_factor <= 0.0 {
+            return;
+        }
+
+        self.scale_factor = scale_factor;
+        for entry in &mut self.charts {
+            entry.chart.scale_factor = scale_factor;
+            entry.chart.dirty = true;
+            if self.visible {
+                entry.chart.invalidate();
+            }
+        }
+    }
+
+    /// Highlights the given time ranges on every chart.
+    pub fn set_bands(&mut self, bands: Vec<Band>) {
+        if bands == self.bands {
+            return;
+        }
+
+        self.bands = bands;
+        for entry in &mut self.charts {
+            entry.chart.bands = self.bands.clone();
+            entry.chart.dirty = true;
+            if self.visible {
+                entry.chart.invalidate();
+            }
+        }
+    }
+
+    /// Draws a vertical marker at each of the given times on every chart.
+    pub fn set_markers(&mut self, markers: Vec<Instant>) {
+        if markers == self.markers {
+            return;
+        }
+
+        self.markers = markers;
+        for entry in &mut self.charts {
+            entry.chart.markers = self.markers.clone();
+            entry.chart.dirty = true;
+            if self.visible {
+                entry.chart.invalidate();
+            }
+        }
+    }
+
+    /// Breaks the plotted lines at the current time instead of connecting the samples
+    /// around it, e.g. across a suspend.
+    pub fn insert_gap(&mut self) {
+        let now = Instant::now();
+        for entry in &mut self.charts {
+            entry.chart.gaps.push(now);
+            entry.chart.dirty = true;
+            if self.visible {
+                entry.chart.invalidate();
+            }
+        }
+    }
+
     #[inline]
     fn is_initialized(&self) -> bool {
-        self.cpu.is_some()
+        !self.charts.is_empty() || !self.unavailable.is_empty()
+    }
+
+    /// Charts only redraw while shown; data pushed while hidden is drawn on the next show.
+    pub fn set_visible(&mut self, visible: bool) {
+        self.visible = visible;
+        if visible {
+            for entry in &mut self.charts {
+                entry.chart.invalidate();
+            }
+        } else {
+            metrics::reset_frames();
+        }
+    }
+
+    /// Samples less often and skips expensive collectors while on battery.
+    pub fn set_on_battery(&mut self, on_battery: bool) {
+        self.on_battery = on_battery;
+        self.sample_every = self.sample_interval();
+        self.sampler.set_low_power(on_battery);
+    }
+
+    fn set_sample_interval(&mut self, milliseconds: u64) {
+        self.base_sample_every =
+            Duration::from_millis(milliseconds).clamp(MIN_SAMPLE_EVERY, MAX_SAMPLE_EVERY);
+        self.sample_every = self.sample_interval();
+    }
+
+    /// Samples, and so redraws, less often and draws a plainer mesh.
+    fn set_reduced_motion(&mut self, reduced_motion: bool) {
+        if reduced_motion == self.reduced_motion {
+            return;
+        }
+
+        self.reduced_motion = reduced_motion;
+        self.sample_every = self.sample_interval();
+        for entry in &mut self.charts {
+            entry.chart.simple_mesh = reduced_motion;
+            entry.chart.dirty = true;
+            if self.visible {
+                entry.chart.invalidate();
+            }
+        }
+    }
+
+    /// Switching modes gives `ChartRendering::Auto` a fresh chance to draw the canvases.
+    fn set_rendering(&mut self, rendering: ChartRendering) {
+        if rendering == self.rendering {
+            return;
+        }
+
+        self.rendering = rendering;
+        self.slow_frames = 0;
+        self.fallback = false;
+    }
+
+    /// Whether the charts are shown as bars and values instead of canvases.
+    fn is_simple(&self) -> bool {
+        match self.rendering {
+            ChartRendering::Auto => self.fallback,
+            ChartRendering::Canvas => false,
+            ChartRendering::Simple => true,
+        }
+    }
+
+    /// Falls back to bars after `SLOW_FRAMES` samples in a row took too long to draw.
+    fn check_render_times(&mut self) {
+        if self.rendering != ChartRendering::Auto || self.fallback {
+            return;
+        }
+
+        // Charts are only drawn while visible, samples without a frame don't count
+        let Some(slowest) = self
+            .charts
+            .iter()
+            .filter_map(|entry| entry.chart.render_time.take())
+            .max()
+        else {
+            return;
+        };
+        if slowest <= SLOW_FRAME {
+            self.slow_frames = 0;
+            return;
+        }
+
+        self.slow_frames += 1;
+        if self.slow_frames >= SLOW_FRAMES {
+            tracing::warn!(
+                ?slowest,
+                "drawing the charts is too slow, showing bars instead"
+            );
+            self.fallback = true;
+        }
+    }
+
+    fn sample_interval(&self) -> Duration {
+        if self.on_battery {
+            power::BATTERY_SAMPLE_EVERY.max(self.base_sample_every)
+        } else if self.reduced_motion {
+            REDUCED_MOTION_SAMPLE_EVERY.max(self.base_sample_every)
+        } else {
+            self.base_sample_every
+        }
+    }
+
+    /// How often new samples are due, the UI only needs to wake up this often.
+    pub fn sample_every(&self) -> Duration {
+        self.sample_every
     }
 
     #[inline]
     fn should_update(&self) -> bool {
-        !self.is_initialized() || self.last_sample_time.elapsed() > SAMPLE_EVERY
+        !self.is_initialized() || self.last_sample_time.elapsed() + TICK_SLACK >= self.sample_every
     }
 
-    pub fn update(&mut self) {
-        if !self.should_update() {
-            return;
+    /// The samples collected by the most recent update.
+    pub fn latest(&self) -> &[Sample] {
+        &self.latest
+    }
+
+    /// Most recent value of a series, if it was sampled.
+    pub fn current(&self, series: &str) -> Option<f64> {
+        self.latest
+            .iter()
+            .flat_map(|sample| sample.series.iter())
+            .find(|(id, _)| id.as_str() == series)
+            .map(|(_, value)| *value)
+    }
+
+    /// Unit of a series, from the scale of the source that sampled it.
+    pub fn unit(&self, series: &str) -> Option<&str> {
+        self.latest
+            .iter()
+            .find(|sample| sample.series.iter().any(|(id, _)| id.as_str() == series))
+            .map(|sample| sample.scale.unit.as_str())
+    }
+
+    /// Samples every source when due, returning whether new data was collected.
+    pub fn update(&mut self) -> bool {
+        // Until then the charts keep showing "Loading…" instead of a bogus first point
+        if !self.sampler.is_warm() || !self.should_update() {
+            return false;
         }
 
-        self.sys.refresh_all();
-        self.last_sample_time = Instant::now();
+        let samples = self.sampler.sample();
         let now = Utc::now();
-        let cpu_data = self.sys.global_cpu_info().cpu_usage() as i32;
-        let total_memory = self.sys.total_memory() as f64;
-        let used_memory = self.sys.used_memory() as f64;
-        let memory_data = ((used_memory / total_memory) * 100.0) as i32;
+        // The monotonic clock stops while suspended, the wall clock keeps going
+        let monotonic = self.last_sample_time.elapsed();
+        if let Some(last) = self.last_sample_wall {
+            let wall = now - last;
+            let drift = chrono::Duration::from_std(monotonic)
+                .map(|monotonic| (wall - monotonic).abs())
+                .unwrap_or_default();
+            if drift.to_std().unwrap_or_default() > CLOCK_JUMP {
+                tracing::info!(%wall, ?monotonic, "clock jumped, breaking the charts");
+                self.insert_gap();
+            }
+        }
+        let sampled_at = Instant::now();
+        self.last_sample_time = sampled_at;
+        self.last_sample_wall = Some(now);
+        self.latest = samples.clone();
 
-        //check if initialized
-        if !self.is_initialized() {
-            self.cpu = Some(PercentualUsageChart::new(
-                vec![(now, cpu_data)].into_iter(),
-                self.color,
-            ));
-            self.memory = Some(PercentualUsageChart::new(
-                vec![(now, memory_data)].into_iter(),
-                self.color,
-            ));
-        } else {
-            self.cpu
-                .as_mut()
-                .expect("uninitialzed cpu error")
-                .push_data(now, cpu_data);
+        for sample in samples {
+            if sample.series.is_empty() {
+                self.unavailable.insert(
+                    sample.source.clone(),
+                    Unavailable {
+                        source: sample.source,
+                        title: sample.title,
+                    },
+                );
+                continue;
+            }
+            self.unavailable.remove(&sample.source);
+
+            let idx = self
+                .charts
+                .iter()
+                .position(|entry| entry.source == sample.source)
+                .unwrap_or_else(|| {
+                    let mut chart = UsageChart::new(self.style, sample.scale.clone());
+                    chart.margin = self.density.margin();
+                    chart.scale_factor = self.scale_factor;
+                    chart.label_font = self.label_font.clone();
+                    chart.labels_right = self.labels_right;
+                    chart.simple_mesh = self.reduced_motion;
+                    chart.bands = self.bands.clone();
+                    chart.markers = self.markers.clone();
+                    self.charts.push(ChartEntry {
+                        source: sample.source.clone(),
+                        title: sample.title.clone(),
+                        chart,
+                        summary: String::new(),
+                    });
+                    self.charts.len() - 1
+                });
+            let entry = &mut self.charts[idx];
+            // The scale may change at runtime, e.g. memory switching to absolute units
+            if entry.chart.scale != sample.scale {
+                entry.chart.scale = sample.scale;
+                entry.chart.series.clear();
+            }
+            for (series, value) in sample.series {
+                entry.chart.push_data(
+                    series,
+                    Point {
+                        at: sampled_at,
+                        time: now,
+                        value,
+                    },
+                );
+            }
+            entry.summary = entry.chart.summary(&entry.title);
+        }
+        self.check_render_times();
+
+        if self.visible {
+            for entry in &mut self.charts {
+                entry.chart.invalidate();
+            }
+        }
+
+        true
+    }
+
+    pub fn history(&self) -> History {
+        History {
+            series: self
+                .charts
+                .iter()
+                .flat_map(|entry| {
+                    entry.chart.series.iter().map(|series| SeriesHistory {
+                        source: entry.source.clone(),
+                        series: series.id.to_string(),
+                        title: entry.title.clone(),
+                        scale: entry.chart.scale.clone(),
+                        points: series
+                            .data_points
+                            .iter()
+                            .map(|point| (point.time.timestamp_millis(), point.value))
+                            .collect(),
+                    })
+                })
+                .collect(),
+        }
+    }
+
+    /// Pre-fills the charts with previously persisted samples, dropping the ones
+    /// already outside of the plotted window.
+    pub fn restore(&mut self, history: History) {
+        let now = Utc::now();
+        let now_at = Instant::now();
+        let limit = Duration::from_secs(PLOT_SECONDS as u64);
+        for series in history.series {
+            let points = series
+                .points
+                .into_iter()
+                .filter_map(|(ms, value)| {
+                    let time = DateTime::from_timestamp_millis(ms)?;
+                    // Samples from the future or older than the window are dropped
+                    let age = (now - time).to_std().ok().filter(|age| *age < limit)?;
+                    Some(Point {
+                        at: now_at.checked_sub(age)?,
+                        time,
+                        value,
+                    })
+                })
+                .collect::<Vec<_>>();
+            if points.is_empty() {
+                continue;
+            }
 
-            self.memory
-                .as_mut()
-                .expect("uninitialzed memory error")
-                .push_data(now, memory_data);
+            let idx = self
+                .charts
+                .iter()
+                .position(|entry| entry.source == series.source)
+                .unwrap_or_else(|| {
+                    let mut chart = UsageChart::new(self.style, series.scale);
+                    chart.margin = self.density.margin();
+                    chart.scale_factor = self.scale_factor;
+                    chart.label_font = self.label_font.clone();
+                    chart.labels_right = self.labels_right;
+                    chart.simple_mesh = self.reduced_motion;
+                    self.charts.push(ChartEntry {
+                        source: series.source,
+                        title: series.title,
+                        chart,
+                        summary: String::new(),
+                    });
+                    self.charts.len() - 1
+                });
+            self.charts[idx].chart.series.push(Series {
+                id: SeriesId::new(series.series),
+                data_points: points.into(),
+            });
         }
     }
 
-    pub fn view(&self) -> Element<Message> {
+    /// Flows the charts into rows fitting `width`, each followed by the element in
+    /// `details` under its source name. The chart of the `first` source leads.
+    pub fn view<'a>(
+        &'a self,
+        mut details: HashMap<&str, Element<'a, Message>>,
+        width: f32,
+        first: Option<&str>,
+    ) -> Element<'a, Message> {
         if !self.is_initialized() {
-            Text::new("Loading...")
+            return Text::new(fl!("loading"))
                 .horizontal_alignment(Horizontal::Center)
                 .vertical_alignment(Vertical::Center)
-                .into()
-        } else {
-            // let chart_height = self.chart_height;
-            // let mut idx = 0;
-            // for chunk in self.processors.chunks(self.items_per_row) {
-            //     let mut row = Row::new()
-            //         .spacing(8)
-            //         .padding(12)
-            //         .width(Length::Fill)
-            //         .height(Length::Shrink)
-            //         .align_items(Alignment::Center);
-            //     for item in chunk {
-            //         row = row.push(item.view(idx, chart_height));
-            //         idx += 1;
-            //     }
-            //     while idx % self.items_per_row != 0 {
-            //         row = row.push(Space::new(Length::Fill, Length::Fixed(50.0)));
-            //         idx += 1;
-            //     }
-            //     col = col.push(row);
-            // }
-
-            let cpu_chart = self.cpu.as_ref().unwrap().view("CPU", self.chart_height);
-            let cpu_row = Row::with_children(vec![cpu_chart])
-                .spacing(8)
-                .padding(12)
-                .width(Length::Fill)
-                .height(Length::Shrink)
-                .align_items(Alignment::Center);
-
-            let memory_chart = self
-                .memory
-                .as_ref()
-                .unwrap()
-                .view("Memory", self.chart_height);
-            let memory_row = Row::with_children(vec![memory_chart])
-                .spacing(8)
-                .padding(12)
-                .width(Length::Fill)
-                .height(Length::Shrink)
-                .align_items(Alignment::Center);
+                .into();
+        }
 
-            let col = Column::with_children(vec![cpu_row.into(), memory_row.into()])
-                .width(Length::Fill)
-                .height(Length::Shrink)
-                .align_items(Alignment::Center);
+        let options = ViewOptions {
+            density: self.density,
+            simple: self.is_simple(),
+            text_summary: self.text_summary,
+        };
+        let mut views = self.views().collect::<Vec<_>>();
+        // Stable, the others keep their order
+        views.sort_by_key(|view| Some(view.name()) != first);
+        let items = views
+            .into_iter()
+            .map(|view| {
+                let element = view.view(&options);
+                let element = match details.remove(view.name()) {
+                    Some(details) => Column::with_children(vec![element, details])
+                        .spacing(self.density.padding())
+                        .width(Length::Fill)
+                        .into(),
+                    None => element,
+                };
+                (view.preferred_size(&options), element)
+            })
+            .collect();
+
+        layout::flow(items, width, VIEW_SPACING, self.density.padding())
+    }
+
+    /// Every chart, or its placeholder while it has no data, in the order they appeared.
+    fn views(&self) -> impl Iterator<Item = &dyn MetricView> {
+        let charts = self
+            .charts
+            .iter()
+            .map(|entry| match self.unavailable.get(&entry.source) {
+                Some(placeholder) => placeholder as &dyn MetricView,
+                None => entry as &dyn MetricView,
+            });
+        let placeholders = self
+            .unavailable
+            .values()
+            .filter(|placeholder| {
+                !self
+                    .charts
+                    .iter()
+                    .any(|entry| entry.source == placeholder.source)
+            })
+            .map(|placeholder| placeholder as &dyn MetricView);
+        charts.chain(placeholders)
+    }
 
-            Scrollable::new(col).height(Length::Shrink).into()
+    /// A bare chart of `values`, newest first, in the current style. For series kept
+    /// outside the charts, like temperatures.
+    pub fn sparkline(&self, values: Vec<f64>, range: Range<f64>) -> Sparkline {
+        Sparkline {
+            series: vec![values],
+            range,
+            mirrored: false,
+            style: self.style,
+            scale_factor: self.scale_factor,
         }
     }
+
+    /// Title, newest value and sparkline of the CPU, memory and network charts that have
+    /// data.
+    pub fn sparklines(&self) -> Vec<(String, String, Sparkline)> {
+        SPARKLINE_SOURCES
+            .iter()
+            .filter_map(|source| {
+                let entry = self.charts.iter().find(|entry| entry.source == *source)?;
+                let value = entry
+                    .chart
+                    .legend_entries()
+                    .into_iter()
+                    .map(|entry| entry.value)
+                    .collect::<Vec<_>>()
+                    .join(" / ");
+                Some((
+                    entry.title.clone(),
+                    value,
+                    Sparkline::from_chart(&entry.chart, self.scale_factor),
+                ))
+            })
+            .collect()
+    }
+
+    /// Current values only, one row per source, for the collapsed popup.
+    pub fn glance_view<'a>(&self) -> Element<'a, Message> {
+        if !self.is_initialized() {
+            return Text::new(fl!("loading")).into();
+        }
+
+        let rows = self
+            .latest
+            .iter()
+            .filter(|sample| !sample.series.is_empty())
+            .map(|sample| {
+                let values = sample
+                    .series
+                    .iter()
+                    .map(|(_, value)| format_value(*value, &sample.scale.unit))
+                    .collect::<Vec<_>>()
+                    .join(" / ");
+                layout::directional_row(vec![
+                    Text::new(sample.title.clone())
+                        .width(Length::Fill)
+                        .horizontal_alignment(layout::start_alignment())
+                        .into(),
+                    Text::new(values).into(),
+                ])
+                .spacing(8)
+                .width(Length::Fill)
+                .into()
+            })
+            .collect::<Vec<Element<Message>>>();
+
+        Column::with_children(rows)
+            .spacing(4)
+            .width(Length::Fill)
+            .into()
+    }
+}
+
+/// A sample, positioned by monotonic time so clock changes cannot disturb the charts.
+pub struct Point {
+    pub at: Instant,
+    /// Wall clock time, only used for persisting and exporting
+    pub time: DateTime<Utc>,
+    pub value: f64,
+}
+
+struct Series {
+    id: SeriesId,
+    /// Newest first
+    data_points: VecDeque<Point>,
 }
 
-struct PercentualUsageChart {
+/// Plots every series of a source; the first one is filled, the others drawn as lines.
+pub struct UsageChart {
     cache: Cache,
-    data_points: VecDeque<(DateTime<Utc>, i32)>,
+    dirty: bool,
+    failed: Cell<bool>,
+    /// How long the last redraw took, taken by `SystemChart` to detect slow rendering
+    render_time: Cell<Option<Duration>>,
+    series: Vec<Series>,
     limit: Duration,
-    color: RGBColor,
+    style: ChartStyle,
+    scale: Scale,
+    margin: u32,
+    scale_factor: f64,
+    label_font: LabelFont,
+    labels_right: bool,
+    /// Only the major grid lines and fewer labels
+    simple_mesh: bool,
+    bands: Vec<Band>,
+    markers: Vec<Instant>,
+    /// Times the plotted lines are not connected across
+    gaps: Vec<Instant>,
 }
 
-impl PercentualUsageChart {
-    fn new(data: impl Iterator<Item = (DateTime<Utc>, i32)>, color: RGBColor) -> Self {
-        let data_points: VecDeque<_> = data.collect();
+impl UsageChart {
+    pub fn new(style: ChartStyle, scale: Scale) -> Self {
         Self {
             cache: Cache::new(),
-            data_points,
+            dirty: false,
+            failed: Cell::new(false),
+            render_time: Cell::new(None),
+            series: Vec::new(),
             limit: Duration::from_secs(PLOT_SECONDS as u64),
-            color,
+            style,
+            scale,
+            margin: ChartDensity::default().margin(),
+            scale_factor: 1.0,
+            label_font: LabelFont::default(),
+            labels_right: false,
+            simple_mesh: false,
+            bands: Vec::new(),
+            markers: Vec::new(),
+            gaps: Vec::new(),
         }
     }
 
-    fn push_data(&mut self, time: DateTime<Utc>, value: i32) {
-        let cur_ms = time.timestamp_millis();
-        self.data_points.push_front((time, value));
-        loop {
-            if let Some((time, _)) = self.data_points.back() {
-                let diff = Duration::from_millis((cur_ms - time.timestamp_millis()) as u64);
-                if diff > self.limit {
-                    self.data_points.pop_back();
-                    continue;
-                }
+    pub fn push_data(&mut self, id: SeriesId, point: Point) {
+        let idx = self
+            .series
+            .iter()
+            .position(|series| series.id == id)
+            .unwrap_or_else(|| {
+                self.series.push(Series {
+                    id,
+                    data_points: VecDeque::new(),
+                });
+                self.series.len() - 1
+            });
+        let series = &mut self.series[idx];
+
+        let now = point.at;
+        series.data_points.push_front(point);
+        while series
+            .data_points
+            .back()
+            .is_some_and(|oldest| now.saturating_duration_since(oldest.at) > self.limit)
+        {
+            series.data_points.pop_back();
+        }
+        let limit = self.limit;
+        self.gaps
+            .retain(|gap| now.saturating_duration_since(*gap) <= limit);
+        self.dirty = true;
+    }
+
+    /// Splits the newest first `points` into runs not crossing any gap.
+    fn runs<'a>(&self, points: &'a VecDeque<Point>) -> Vec<Vec<&'a Point>> {
+        let mut runs: Vec<Vec<&Point>> = vec![Vec::new()];
+        for point in points {
+            let crosses_gap = runs.last().and_then(|run| run.last()).is_some_and(|newer| {
+                self.gaps
+                    .iter()
+                    .any(|gap| point.at < *gap && *gap <= newer.at)
+            });
+            if crosses_gap {
+                runs.push(Vec::new());
+            }
+            if let Some(run) = runs.last_mut() {
+                run.push(point);
             }
-            break;
         }
-        self.cache.clear();
+        runs
+    }
+
+    fn points(&self) -> usize {
+        self.series
+            .iter()
+            .map(|series| series.data_points.len())
+            .sum()
+    }
+
+    fn invalidate(&mut self) {
+        if self.dirty {
+            self.cache.clear();
+            self.dirty = false;
+            self.failed.set(false);
+        }
     }
 
     fn view(&self, title: &str, chart_height: f32) -> Element<Message> {
-        Column::new()
+        if self.failed.get() {
+            return unavailable_placeholder(title);
+        }
+
+        let mut column = Column::new()
             .width(Length::Fill)
             .height(Length::Shrink)
             .spacing(5)
             .align_items(Alignment::Center)
-            .push(Text::new(title.to_string()))
+            .push(Text::new(title.to_string()));
+        // A single series is named by the title already
+        if self.series.len() > 1 {
+            column = column.push(legend(self.legend_entries()));
+        }
+        column
             .push(ChartWidget::new(self).height(Length::Fixed(chart_height)))
             .into()
     }
+
+    /// Every series in the colors it is plotted in, with its newest value.
+    fn legend_entries(&self) -> Vec<LegendEntry> {
+        self.series
+            .iter()
+            .enumerate()
+            .map(|(index, series)| {
+                let (color, alpha) = self.style.series_color(index);
+                LegendEntry {
+                    color: cosmic::iced::Color::from_rgba8(color.0, color.1, color.2, alpha),
+                    label: series_label(&series.id),
+                    value: series
+                        .data_points
+                        .front()
+                        .map(|point| format_value(point.value, &self.scale.unit))
+                        .unwrap_or_default(),
+                }
+            })
+            .collect()
+    }
+
+    /// The newest value of every series as a bar, or as text if the scale has no range.
+    fn simple_view(&self, title: &str) -> Element<Message> {
+        let rows = self.series.iter().filter_map(|series| {
+            let value = series.data_points.front()?.value;
+            let text = Text::new(format_value(value, &self.scale.unit));
+            let row: Element<Message> = match self.scale.range {
+                Some((min, max)) => layout::directional_row(vec![
+                    ProgressBar::new(min as f32..=max as f32, value as f32)
+                        .height(Length::Fixed(8.0))
+                        .into(),
+                    text.into(),
+                ])
+                .spacing(8)
+                .align_items(Alignment::Center)
+                .into(),
+                None => text.into(),
+            };
+            Some(row)
+        });
+
+        Column::with_children(std::iter::once(Text::new(title.to_string()).into()).chain(rows))
+            .width(Length::Fill)
+            .spacing(5)
+            .into()
+    }
 }
 
-impl Chart<Message> for PercentualUsageChart {
+impl Chart<Message> for UsageChart {
     type State = ();
 
     #[inline]
@@ -211,53 +1043,707 @@ impl Chart<Message> for PercentualUsageChart {
         bounds: Size,
         draw_fn: F,
     ) -> Geometry {
-        renderer.draw_cache(&self.cache, bounds, draw_fn)
+        // Plotters draws on whole pixels, which fall between physical pixels at fractional
+        // scales and come out blurry. Lay the chart out in physical pixels and scale the
+        // frame back down, so every line lands on a physical pixel
+        let scale = self.scale_factor as f32;
+        let physical = Size::new(bounds.width * scale, bounds.height * scale);
+        renderer.draw_cache(&self.cache, physical, |frame| {
+            // Only runs when the cache was cleared, so this times actual redraws
+            let started = Instant::now();
+            frame.with_save(|frame| {
+                frame.scale(1.0 / scale);
+                draw_fn(frame);
+            });
+            self.render_time.set(Some(started.elapsed()));
+        })
     }
 
-    fn build_chart<DB: DrawingBackend>(&self, _state: &Self::State, mut chart: ChartBuilder<DB>) {
-        // Acquire time range
-        let newest_time = self
-            .data_points
-            .front()
-            .unwrap_or(&(chrono::DateTime::from_timestamp(0, 0).unwrap(), 0))
-            .0;
-        let oldest_time = newest_time - chrono::Duration::seconds(PLOT_SECONDS as i64);
+    fn build_chart<DB: DrawingBackend>(&self, _state: &Self::State, chart: ChartBuilder<DB>) {
+        let _span = tracing::trace_span!("build_chart", points = self.points()).entered();
+        if let Err(err) = self.try_build_chart(chart) {
+            tracing::error!("failed to draw chart: {}", err);
+            self.failed.set(true);
+        }
+    }
+}
+
+impl UsageChart {
+    /// Current, lowest, highest and average value of the primary series in the window.
+    fn summary(&self, title: &str) -> String {
+        let Some(primary) = self.series.first() else {
+            return String::new();
+        };
+        let Some(current) = primary.data_points.front() else {
+            return String::new();
+        };
+        let (min, max, sum) = primary.data_points.iter().fold(
+            (f64::INFINITY, f64::NEG_INFINITY, 0.0),
+            |(min, max, sum), point| {
+                (
+                    min.min(point.value),
+                    max.max(point.value),
+                    sum + point.value,
+                )
+            },
+        );
+        let unit = self.scale.unit.as_str();
+        fl!(
+            "chart-summary",
+            title = title,
+            current = format_value(current.value, unit),
+            min = format_value(min, unit),
+            max = format_value(max, unit),
+            average = format_value(sum / primary.data_points.len() as f64, unit),
+            seconds = PLOT_SECONDS
+        )
+    }
+
+    /// The value as plotted, negated for the mirrored second series.
+    fn plotted(&self, index: usize, point: &Point) -> f64 {
+        if self.scale.mirrored && index == 1 {
+            -point.value
+        } else {
+            point.value
+        }
+    }
+
+    fn y_range(&self) -> Range<f64> {
+        if let Some((min, max)) = self.scale.range {
+            return min..max;
+        }
+
+        // Keep the x-axis centered so both directions are drawn to the same scale
+        if self.scale.mirrored {
+            let extent = self
+                .series
+                .iter()
+                .flat_map(|series| series.data_points.iter())
+                .fold(0.0_f64, |extent, point| extent.max(point.value.abs()));
+            let extent = if extent > 0.0 { extent * 1.1 } else { 1.0 };
+            return -extent..extent;
+        }
+
+        // Fit the data, always keeping zero in view and some headroom above the peak
+        let (min, max) = self
+            .series
+            .iter()
+            .flat_map(|series| series.data_points.iter())
+            .fold((0.0_f64, 0.0_f64), |(min, max), point| {
+                (min.min(point.value), max.max(point.value))
+            });
+        let max = if max > 0.0 { max * 1.1 } else { 1.0 };
+        min * 1.1..max
+    }
+
+    fn try_build_chart<DB: DrawingBackend>(
+        &self,
+        mut chart: ChartBuilder<DB>,
+    ) -> Result<(), String> {
+        // The x-axis is in seconds before the newest sample
+        let Some(newest) = self
+            .series
+            .iter()
+            .filter_map(|series| series.data_points.front())
+            .map(|point| point.at)
+            .max()
+        else {
+            return Ok(());
+        };
+        let x = |at: Instant| -newest.saturating_duration_since(at).as_secs_f64();
+        let x_range = -(PLOT_SECONDS as f64)..0.0;
+        let y_range = self.y_range();
+        // Sizes are in logical pixels, the chart is laid out in physical ones
+        let px = |logical: u32| (logical as f64 * self.scale_factor).round() as u32;
+        let line_width = px(1).max(1);
+        // The rotated labels are as wide as they are tall, 28 at the default size
+        let label_area = px(20 + self.label_font.size);
+        let (left_labels, right_labels) = if self.labels_right {
+            (0, label_area)
+        } else {
+            (label_area, 0)
+        };
         let mut chart = chart
             .x_label_area_size(0)
-            .y_label_area_size(28)
-            .margin(20)
-            .build_cartesian_2d(oldest_time..newest_time, 0..100)
-            .expect("failed to build chart");
+            .y_label_area_size(left_labels)
+            .right_y_label_area_size(right_labels)
+            .margin(px(self.margin))
+            .build_cartesian_2d(x_range.clone(), y_range.clone())
+            .map_err(|err| err.to_string())?;
 
+        let (light_lines, y_labels) = if self.simple_mesh {
+            (TRANSPARENT, 5)
+        } else {
+            (self.style.grid.mix(0.2), 10)
+        };
         chart
             .configure_mesh()
-            .bold_line_style(self.color.mix(0.1))
-            .light_line_style(self.color.mix(0.05))
-            .axis_style(ShapeStyle::from(self.color.mix(0.45)).stroke_width(1))
-            .y_labels(10)
+            .bold_line_style(self.style.grid.mix(0.5))
+            .light_line_style(light_lines)
+            .axis_style(ShapeStyle::from(self.style.label.mix(0.45)).stroke_width(line_width))
+            .y_labels(y_labels)
             .y_label_style(
-                ("sans-serif", 8)
+                (self.label_font.family.as_str(), px(self.label_font.size))
                     .into_font()
-                    .color(&self.color.mix(0.65))
+                    .color(&self.style.label.mix(0.75))
                     .transform(FontTransform::Rotate90),
             )
-            .y_label_formatter(&y_label_formatter)
+            .y_label_formatter(&|v| {
+                // Both halves of a mirrored chart count up from the x-axis
+                let v = if self.scale.mirrored { v.abs() } else { *v };
+                format_value(v, &self.scale.unit)
+            })
             .draw()
-            .expect("failed to draw chart mesh");
+            .map_err(|err| err.to_string())?;
 
         chart
             .draw_series(
-                AreaSeries::new(
-                    self.data_points.iter().map(|x| (x.0, x.1)),
-                    0,
-                    self.color.mix(0.175),
-                )
-                .border_style(ShapeStyle::from(self.color).stroke_width(1)),
+                self.bands
+                    .iter()
+                    .map(|(start, end)| (x(*start), end.map_or(0.0, x)))
+                    .filter(|(_, end)| *end >= x_range.start)
+                    .map(|(start, end)| {
+                        Rectangle::new(
+                            [
+                                (start.max(x_range.start), y_range.end),
+                                (end, y_range.start),
+                            ],
+                            self.style.label.mix(0.12).filled(),
+                        )
+                    }),
             )
-            .expect("failed to draw chart data");
+            .map_err(|err| err.to_string())?;
+
+        chart
+            .draw_series(
+                self.markers
+                    .iter()
+                    .map(|at| x(*at))
+                    .filter(|x| *x > x_range.start)
+                    .map(|x| {
+                        PathElement::new(
+                            vec![(x, y_range.start), (x, y_range.end)],
+                            ShapeStyle::from(self.style.label.mix(0.5)).stroke_width(line_width),
+                        )
+                    }),
+            )
+            .map_err(|err| err.to_string())?;
+
+        let mut series = self.series.iter();
+        if let Some(primary) = series.next() {
+            let (color, alpha) = self.style.series_color(0);
+            for run in self.runs(&primary.data_points) {
+                chart
+                    .draw_series(
+                        AreaSeries::new(
+                            run.into_iter().map(|point| (x(point.at), point.value)),
+                            0.0_f64.max(y_range.start),
+                            color.mix(0.175),
+                        )
+                        .border_style(ShapeStyle::from(color.mix(alpha)).stroke_width(line_width)),
+                    )
+                    .map_err(|err| err.to_string())?;
+            }
+        }
+        for (index, secondary) in series.enumerate().map(|(i, series)| (i + 1, series)) {
+            let (color, alpha) = self.style.series_color(index);
+            for run in self.runs(&secondary.data_points) {
+                let points = run
+                    .into_iter()
+                    .map(|point| (x(point.at), self.plotted(index, point)));
+                if self.scale.mirrored && index == 1 {
+                    chart
+                        .draw_series(AreaSeries::new(points, 0.0, color.mix(0.175)).border_style(
+                            ShapeStyle::from(color.mix(alpha)).stroke_width(line_width),
+                        ))
+                        .map_err(|err| err.to_string())?;
+                } else {
+                    chart
+                        .draw_series(LineSeries::new(
+                            points,
+                            ShapeStyle::from(color.mix(alpha)).stroke_width(line_width),
+                        ))
+                        .map_err(|err| err.to_string())?;
+                }
+            }
+        }
+
+        Ok(())
     }
 }
 
-fn y_label_formatter(v: &i32) -> String {
-    return format!("{}%", v);
+/// A chart without axes, grid or labels, a few dozen pixels tall. Drawn from a copy of
+/// the values so it doesn't share the cache of the full chart.
+pub struct Sparkline {
+    /// Plotted values of every series, newest first
+    series: Vec<Vec<f64>>,
+    range: Range<f64>,
+    /// The second series is drawn below the x-axis
+    mirrored: bool,
+    style: ChartStyle,
+    scale_factor: f64,
+}
+
+impl Sparkline {
+    fn from_chart(chart: &UsageChart, scale_factor: f64) -> Self {
+        Self {
+            series: chart
+                .series
+                .iter()
+                .enumerate()
+                .map(|(index, series)| {
+                    series
+                        .data_points
+                        .iter()
+                        .map(|point| chart.plotted(index, point))
+                        .collect()
+                })
+                .collect(),
+            range: chart.y_range(),
+            mirrored: chart.scale.mirrored,
+            style: chart.style,
+            scale_factor,
+        }
+    }
+}
+
+impl Chart<Message> for Sparkline {
+    type State = ();
+
+    fn draw<R: plotters_iced::Renderer, F: Fn(&mut Frame)>(
+        &self,
+        renderer: &R,
+        bounds: Size,
+        draw_fn: F,
+    ) -> Geometry {
+        // Laid out in physical pixels like `UsageChart`, for sharp lines
+        let scale = self.scale_factor as f32;
+        let physical = Size::new(bounds.width * scale, bounds.height * scale);
+        renderer.draw(physical, |frame| {
+            frame.with_save(|frame| {
+                frame.scale(1.0 / scale);
+                draw_fn(frame);
+            });
+        })
+    }
+
+    fn build_chart<DB: DrawingBackend>(&self, _state: &Self::State, chart: ChartBuilder<DB>) {
+        if let Err(err) = self.try_build_chart(chart) {
+            tracing::error!("failed to draw sparkline: {}", err);
+        }
+    }
+}
+
+impl Sparkline {
+    fn try_build_chart<DB: DrawingBackend>(
+        &self,
+        mut chart: ChartBuilder<DB>,
+    ) -> Result<(), String> {
+        let line_width = (self.scale_factor.round() as u32).max(1);
+        // One sample per step, the strip has no time axis to keep gaps on
+        let samples = self.series.iter().map(Vec::len).max().unwrap_or(0).max(2);
+        let mut chart = chart
+            .margin(line_width)
+            .build_cartesian_2d(-((samples - 1) as f64)..0.0, self.range.clone())
+            .map_err(|err| err.to_string())?;
+        for (index, values) in self.series.iter().enumerate() {
+            let (color, alpha) = self.style.series_color(index);
+            let line = ShapeStyle::from(color.mix(alpha)).stroke_width(line_width);
+            let points = values
+                .iter()
+                .enumerate()
+                .map(|(age, value)| (-(age as f64), *value));
+            // Filled like in the full chart: the primary series and the mirrored one
+            if index == 0 || (self.mirrored && index == 1) {
+                let baseline = if index == 0 {
+                    0.0_f64.max(self.range.start)
+                } else {
+                    0.0
+                };
+                chart
+                    .draw_series(
+                        AreaSeries::new(points, baseline, color.mix(0.175)).border_style(line),
+                    )
+                    .map_err(|err| err.to_string())?;
+            } else {
+                chart
+                    .draw_series(LineSeries::new(points, line))
+                    .map_err(|err| err.to_string())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Stacked rows of a title, a sparkline and the newest value, the "mission control"
+/// glance at the system.
+pub fn sparkline_strip<'a>(rows: Vec<(String, String, Sparkline)>) -> Element<'a, Message> {
+    let rows = rows
+        .into_iter()
+        .map(|(title, value, sparkline)| {
+            layout::directional_row(vec![
+                Text::new(title)
+                    .width(Length::Fixed(SPARKLINE_TITLE_WIDTH))
+                    .horizontal_alignment(layout::start_alignment())
+                    .into(),
+                ChartWidget::new(sparkline)
+                    .width(Length::Fill)
+                    .height(Length::Fixed(SPARKLINE_HEIGHT))
+                    .into(),
+                Text::new(value).into(),
+            ])
+            .spacing(8)
+            .width(Length::Fill)
+            .align_items(Alignment::Center)
+            .into()
+        })
+        .collect::<Vec<Element<Message>>>();
+
+    Column::with_children(rows)
+        .spacing(2)
+        .width(Length::Fill)
+        .into()
+}
+
+fn unavailable_placeholder<'a>(title: &str) -> Element<'a, Message> {
+    Text::new(fl!("metric-unavailable", metric = title))
+        .horizontal_alignment(Horizontal::Center)
+        .vertical_alignment(Vertical::Center)
+        .into()
+}
+
+/// A series shown in a chart legend.
+pub struct LegendEntry {
+    pub color: cosmic::iced::Color,
+    pub label: String,
+    /// Newest value, formatted with its unit
+    pub value: String,
+}
+
+/// Color swatch, name and current value of every entry, side by side in reading order.
+pub fn legend<'a>(entries: Vec<LegendEntry>) -> Element<'a, Message> {
+    let entries = entries
+        .into_iter()
+        .map(|entry| {
+            layout::directional_row(vec![
+                Text::new("■")
+                    .style(cosmic::theme::Text::Color(entry.color))
+                    .into(),
+                Text::new(entry.label).into(),
+                Text::new(entry.value).into(),
+            ])
+            .spacing(4)
+            .align_items(Alignment::Center)
+            .into()
+        })
+        .collect::<Vec<Element<Message>>>();
+
+    layout::directional_row(entries).spacing(16).into()
+}
+
+/// Localized name of a series, falling back to its id without the source prefix.
+fn series_label(id: &SeriesId) -> String {
+    match id.as_str() {
+        "network_rx" => fl!("series-download"),
+        "network_tx" => fl!("series-upload"),
+        "memory_used" => fl!("series-used"),
+        "memory_total" => fl!("series-total"),
+        "swap_in" => fl!("series-swap-in"),
+        "swap_out" => fl!("series-swap-out"),
+        "frame_time_avg" => fl!("series-average"),
+        "frame_time_max" => fl!("series-longest"),
+        // e.g. `gpu:card0` or `custom:Hashrate`
+        id => id.split_once(':').map_or(id, |(_, name)| name).to_string(),
+    }
+}
+
+pub(crate) fn format_value(v: f64, unit: &str) -> String {
+    if v != 0.0 && v.abs() < 10.0 {
+        format!("{:.1}{}", v, unit)
+    } else {
+        format!("{:.0}{}", v, unit)
+    }
+}
+
+#[cfg(test)]
+mod snapshot_tests;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::MemoryScale;
+    use crate::metrics::{CpuSource, FakeBackend, MemorySource, MetricSource};
+
+    const STYLE: ChartStyle = ChartStyle {
+        accent: RGBColor(0x63, 0xd0, 0xdf),
+        grid: RGBColor(0x5c, 0x5c, 0x5c),
+        label: RGBColor(0xc4, 0xc4, 0xc4),
+        palette: ChartPalette::Accent,
+    };
+
+    fn chart_with(backend: FakeBackend) -> SystemChart {
+        let sources: Vec<Box<dyn MetricSource>> = vec![
+            Box::new(CpuSource::new(false)),
+            Box::new(MemorySource::new(MemoryScale::Percent)),
+        ];
+        SystemChart::with_sampler(STYLE, Sampler::with_backend(Box::new(backend), sources))
+    }
+
+    fn point(at: Instant, value: f64) -> Point {
+        Point {
+            at,
+            time: Utc::now(),
+            value,
+        }
+    }
+
+    #[test]
+    fn update_plots_every_source() {
+        let mut chart = chart_with(FakeBackend {
+            cpu_usage: 42.0,
+            used_memory: 2,
+            total_memory: 8,
+            ..Default::default()
+        });
+
+        assert!(chart.update());
+        assert_eq!(chart.current("cpu"), Some(42.0));
+        assert_eq!(chart.current("memory"), Some(25.0));
+        assert_eq!(chart.unit("cpu"), Some("%"));
+        assert_eq!(chart.unit("swap"), None);
+        assert_eq!(chart.charts.len(), 2);
+        assert!(chart.unavailable.is_empty());
+    }
+
+    #[test]
+    fn sparklines_follow_the_charted_sources() {
+        let mut chart = chart_with(FakeBackend {
+            cpu_usage: 42.0,
+            used_memory: 2,
+            total_memory: 8,
+            ..Default::default()
+        });
+
+        assert!(chart.update());
+        let sparklines = chart.sparklines();
+        assert_eq!(
+            sparklines
+                .iter()
+                .map(|(title, _, _)| title.as_str())
+                .collect::<Vec<_>>(),
+            vec!["CPU", "Memory"]
+        );
+        assert_eq!(sparklines[0].2.series, vec![vec![42.0]]);
+    }
+
+    #[test]
+    fn update_waits_for_the_sample_interval() {
+        let mut chart = chart_with(FakeBackend {
+            total_memory: 8,
+            ..Default::default()
+        });
+
+        assert!(chart.update());
+        assert!(!chart.update());
+    }
+
+    #[test]
+    fn update_waits_for_the_backend_to_warm_up() {
+        let mut chart = chart_with(FakeBackend {
+            total_memory: 8,
+            warm_up: Duration::from_secs(3600),
+            ..Default::default()
+        });
+
+        assert!(!chart.update());
+        assert!(!chart.is_initialized());
+    }
+
+    #[test]
+    fn reduced_motion_samples_less_often() {
+        let mut chart = chart_with(FakeBackend::default());
+
+        chart.set_reduced_motion(true);
+        assert_eq!(chart.sample_every(), REDUCED_MOTION_SAMPLE_EVERY);
+        chart.set_on_battery(true);
+        assert_eq!(chart.sample_every(), power::BATTERY_SAMPLE_EVERY);
+        chart.set_on_battery(false);
+        chart.set_reduced_motion(false);
+        assert_eq!(chart.sample_every(), SAMPLE_EVERY);
+    }
+
+    #[test]
+    fn configured_interval_is_only_slowed_down() {
+        let mut chart = chart_with(FakeBackend::default());
+
+        chart.set_sample_interval(10_000);
+        chart.set_reduced_motion(true);
+        assert_eq!(chart.sample_every(), Duration::from_secs(10));
+        chart.set_sample_interval(0);
+        chart.set_reduced_motion(false);
+        assert_eq!(chart.sample_every(), MIN_SAMPLE_EVERY);
+        chart.set_sample_interval(u64::MAX);
+        assert_eq!(chart.sample_every(), MAX_SAMPLE_EVERY);
+    }
+
+    #[test]
+    fn slow_frames_fall_back_to_bars() {
+        let mut chart = chart_with(FakeBackend::default());
+        assert!(chart.update());
+        let slow = || Some(SLOW_FRAME * 2);
+
+        for _ in 1..SLOW_FRAMES {
+            chart.charts[0].chart.render_time.set(slow());
+            chart.check_render_times();
+        }
+        assert!(!chart.is_simple());
+        // A fast frame in between starts the count over
+        chart.charts[0].chart.render_time.set(Some(Duration::ZERO));
+        chart.check_render_times();
+        for _ in 0..SLOW_FRAMES {
+            chart.charts[0].chart.render_time.set(slow());
+            chart.check_render_times();
+        }
+        assert!(chart.is_simple());
+
+        chart.set_rendering(ChartRendering::Canvas);
+        assert!(!chart.is_simple());
+    }
+
+    #[test]
+    fn legend_names_every_series_with_its_newest_value() {
+        let mut chart = UsageChart::new(STYLE, Scale::mirrored(" KiB/s"));
+        let now = Instant::now();
+        chart.push_data(SeriesId::new("network_rx"), point(now, 50.0));
+        chart.push_data(SeriesId::new("network_tx"), point(now, 100.0));
+
+        let entries = chart.legend_entries();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].label, fl!("series-download"));
+        assert_eq!(entries[0].value, "50 KiB/s");
+        assert_eq!(entries[1].value, "100 KiB/s");
+        assert_eq!(series_label(&SeriesId::new("gpu:card0")), "card0");
+    }
+
+    #[test]
+    fn source_without_data_is_unavailable() {
+        // Total memory of 0 means it could not be read
+        let mut chart = chart_with(FakeBackend::default());
+
+        assert!(chart.update());
+        assert_eq!(chart.charts.len(), 1);
+        assert!(chart.unavailable.contains_key("Memory"));
+        let views = chart.views().map(|view| view.name()).collect::<Vec<_>>();
+        assert_eq!(views, ["CPU", "Memory"]);
+    }
+
+    #[test]
+    fn retention_drops_points_outside_the_window() {
+        let mut chart = UsageChart::new(STYLE, Scale::percent());
+        let start = Instant::now();
+        for i in 0..=120 {
+            chart.push_data(
+                SeriesId::new("cpu"),
+                point(start + Duration::from_secs(i), i as f64),
+            );
+        }
+
+        let points = &chart.series[0].data_points;
+        assert_eq!(points.len(), PLOT_SECONDS + 1);
+        assert_eq!(points.front().map(|point| point.value), Some(120.0));
+        assert_eq!(points.back().map(|point| point.value), Some(60.0));
+    }
+
+    #[test]
+    fn retention_tolerates_samples_out_of_order() {
+        let mut chart = UsageChart::new(STYLE, Scale::percent());
+        let start = Instant::now();
+        chart.push_data(
+            SeriesId::new("cpu"),
+            point(start + Duration::from_secs(10), 1.0),
+        );
+        chart.push_data(SeriesId::new("cpu"), point(start, 2.0));
+
+        assert_eq!(chart.series[0].data_points.len(), 2);
+    }
+
+    #[test]
+    fn gaps_split_the_plotted_runs() {
+        let mut chart = UsageChart::new(STYLE, Scale::percent());
+        let start = Instant::now();
+        for i in 0..4 {
+            chart.push_data(
+                SeriesId::new("cpu"),
+                point(start + Duration::from_secs(i), i as f64),
+            );
+        }
+        chart.gaps.push(start + Duration::from_millis(1500));
+
+        let runs = chart
+            .runs(&chart.series[0].data_points)
+            .into_iter()
+            .map(|run| run.iter().map(|point| point.value).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        assert_eq!(runs, vec![vec![3.0, 2.0], vec![1.0, 0.0]]);
+    }
+
+    #[test]
+    fn summary_describes_the_primary_series() {
+        let mut chart = UsageChart::new(STYLE, Scale::percent());
+        let start = Instant::now();
+        for (i, value) in [20.0, 40.0, 30.0].into_iter().enumerate() {
+            chart.push_data(
+                SeriesId::new("cpu"),
+                point(start + Duration::from_secs(i as u64), value),
+            );
+        }
+
+        let summary = chart.summary("CPU");
+        assert!(summary.contains("30%"), "{}", summary);
+        assert!(summary.contains("20%"), "{}", summary);
+        assert!(summary.contains("40%"), "{}", summary);
+        assert!(summary.contains("CPU"), "{}", summary);
+    }
+
+    #[test]
+    fn mirrored_scale_plots_the_second_series_below_the_axis() {
+        let mut chart = UsageChart::new(STYLE, Scale::mirrored(" KiB/s"));
+        let now = Instant::now();
+        chart.push_data(SeriesId::new("network_rx"), point(now, 50.0));
+        chart.push_data(SeriesId::new("network_tx"), point(now, 100.0));
+
+        assert_eq!(chart.y_range(), -110.0..110.0);
+        assert_eq!(chart.plotted(0, &chart.series[0].data_points[0]), 50.0);
+        assert_eq!(chart.plotted(1, &chart.series[1].data_points[0]), -100.0);
+    }
+
+    #[test]
+    fn label_font_falls_back_to_the_default() {
+        let config = Config {
+            chart_label_size: 40,
+            chart_label_font: " ".to_string(),
+            ..Config::default()
+        };
+        assert_eq!(
+            LabelFont::from_config(&config),
+            LabelFont {
+                family: "sans-serif".to_string(),
+                size: 24,
+            }
+        );
+    }
+
+    #[test]
+    fn palettes_give_every_series_its_own_hue() {
+        assert_eq!(STYLE.series_color(0), (STYLE.accent, 1.0));
+        assert_eq!(STYLE.series_color(2), (STYLE.label, 0.6));
+
+        let style = ChartStyle {
+            palette: ChartPalette::OkabeIto,
+            ..STYLE
+        };
+        assert_ne!(style.series_color(0).0, style.series_color(1).0);
+        // Cycles once every color was used
+        assert_eq!(style.series_color(7), style.series_color(0));
+    }
 }
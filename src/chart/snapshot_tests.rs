@@ -0,0 +1,135 @@
+//! Renders charts of known datasets and compares them against the golden images in
+//! `tests/golden`. A missing golden image fails the test, run with `UPDATE_GOLDEN=1` to
+//! record missing images or re-record all of them after an intended change.
+
+use super::*;
+use image::RgbImage;
+use std::path::PathBuf;
+
+const WIDTH: u32 = 440;
+const HEIGHT: u32 = 180;
+/// Channel difference below which pixels count as equal, absorbs antialiasing noise
+const PIXEL_TOLERANCE: u8 = 16;
+/// Share of differing pixels tolerated, absorbs font rendering differences between systems
+const MAX_DIFFERING: f64 = 0.005;
+
+const STYLE: ChartStyle = ChartStyle {
+    accent: RGBColor(0x63, 0xd0, 0xdf),
+    grid: RGBColor(0x5c, 0x5c, 0x5c),
+    label: RGBColor(0xc4, 0xc4, 0xc4),
+    palette: ChartPalette::Accent,
+};
+
+fn chart_with(scale: Scale, series: &[(&str, &[f64])]) -> (UsageChart, Instant) {
+    let mut chart = UsageChart::new(STYLE, scale);
+    let start = Instant::now();
+    for (id, values) in series {
+        for (i, value) in values.iter().enumerate() {
+            chart.push_data(
+                SeriesId::new(*id),
+                Point {
+                    at: start + Duration::from_secs(i as u64),
+                    time: Utc::now(),
+                    value: *value,
+                },
+            );
+        }
+    }
+    (chart, start)
+}
+
+fn ramp(len: usize, step: f64) -> Vec<f64> {
+    (0..len).map(|i| i as f64 * step).collect()
+}
+
+fn render(chart: &UsageChart) -> RgbImage {
+    let mut buffer = vec![0u8; (WIDTH * HEIGHT * 3) as usize];
+    {
+        let root = BitMapBackend::with_buffer(&mut buffer, (WIDTH, HEIGHT)).into_drawing_area();
+        root.fill(&BLACK).expect("failed to clear the bitmap");
+        chart.build_chart(&(), ChartBuilder::on(&root));
+        root.present().expect("failed to render the chart");
+    }
+    assert!(!chart.failed.get(), "drawing the chart failed");
+    RgbImage::from_raw(WIDTH, HEIGHT, buffer).expect("bitmap size mismatch")
+}
+
+fn assert_matches_golden(name: &str, image: &RgbImage) {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/golden")
+        .join(format!("{}.png", name));
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        image.save(&path).expect("failed to write golden image");
+        eprintln!("recorded golden image {}", path.display());
+        return;
+    }
+    if !path.exists() {
+        panic!(
+            "{} has no golden image, record it with UPDATE_GOLDEN=1 and commit {}",
+            name,
+            path.display()
+        );
+    }
+
+    let golden = image::open(&path)
+        .expect("failed to read golden image")
+        .to_rgb8();
+    assert_eq!(
+        golden.dimensions(),
+        image.dimensions(),
+        "{} size changed",
+        name
+    );
+    let differing = golden
+        .pixels()
+        .zip(image.pixels())
+        .filter(|(a, b)| {
+            a.0.iter()
+                .zip(b.0.iter())
+                .any(|(a, b)| a.abs_diff(*b) > PIXEL_TOLERANCE)
+        })
+        .count();
+    let share = differing as f64 / (WIDTH * HEIGHT) as f64;
+    if share > MAX_DIFFERING {
+        let actual = path.with_extension("actual.png");
+        let _ = image.save(&actual);
+        panic!(
+            "{} differs from its golden image in {:.2}% of the pixels, see {}",
+            name,
+            share * 100.0,
+            actual.display()
+        );
+    }
+}
+
+#[test]
+fn percent_ramp() {
+    let (chart, _) = chart_with(Scale::percent(), &[("cpu", &ramp(61, 100.0 / 60.0))]);
+    assert_matches_golden("percent_ramp", &render(&chart));
+}
+
+#[test]
+fn auto_scale_with_secondary_series() {
+    let used = ramp(61, 0.1);
+    let total = vec![16.0; 61];
+    let (chart, _) = chart_with(
+        Scale::auto(" GiB"),
+        &[("memory_used", &used), ("memory_total", &total)],
+    );
+    assert_matches_golden("auto_scale_with_secondary_series", &render(&chart));
+}
+
+#[test]
+fn bands_markers_and_gaps() {
+    let values = (0..61)
+        .map(|i| if (20..35).contains(&i) { 95.0 } else { 20.0 })
+        .collect::<Vec<_>>();
+    let (mut chart, start) = chart_with(Scale::percent(), &[("cpu", &values)]);
+    chart.bands = vec![(
+        start + Duration::from_secs(20),
+        Some(start + Duration::from_secs(35)),
+    )];
+    chart.markers = vec![start + Duration::from_secs(45)];
+    chart.gaps = vec![start + Duration::from_millis(50_500)];
+    assert_matches_golden("bands_markers_and_gaps", &render(&chart));
+}
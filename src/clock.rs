@@ -0,0 +1,224 @@
+use crate::alerts::{Alert, Severity};
+use crate::fl;
+use crate::layout;
+use crate::window::Message;
+use cosmic::iced::Length;
+use cosmic::widget;
+use cosmic::Element;
+use std::process::Command;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const REFRESH_EVERY: Duration = Duration::from_secs(60);
+const ALERT_KEY: &str = "clock-unsynchronized";
+
+#[zbus::proxy(
+    interface = "org.freedesktop.timedate1",
+    default_service = "org.freedesktop.timedate1",
+    default_path = "/org/freedesktop/timedate1"
+)]
+trait Timedate1 {
+    #[zbus(property, name = "NTP")]
+    fn ntp(&self) -> zbus::Result<bool>;
+
+    #[zbus(property, name = "NTPSynchronized")]
+    fn ntp_synchronized(&self) -> zbus::Result<bool>;
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ClockStatus {
+    /// Whether network time synchronization is turned on
+    pub ntp: bool,
+    pub synchronized: bool,
+    /// Seconds the system clock is ahead of the time source, when the daemon tells
+    pub offset: Option<f64>,
+}
+
+#[derive(Clone, Debug)]
+enum ClockState {
+    Pending,
+    Unavailable(String),
+    Status(ClockStatus),
+}
+
+/// Asks timedated whether the clock is synchronized, and chrony or timesyncd for the
+/// offset, on a background thread.
+pub struct ClockMonitor {
+    state: Arc<Mutex<ClockState>>,
+    last_run: Option<Instant>,
+}
+
+impl ClockMonitor {
+    pub fn new() -> Self {
+        Self {
+            state: Arc::new(Mutex::new(ClockState::Pending)),
+            last_run: None,
+        }
+    }
+
+    /// Starts a new query when due and returns an alert while synchronization is on but
+    /// lost, for `Alerts::sync`.
+    pub fn poll(&mut self) -> Vec<Alert> {
+        if self
+            .last_run
+            .map_or(true, |time| time.elapsed() > REFRESH_EVERY)
+        {
+            self.last_run = Some(Instant::now());
+            let state = self.state.clone();
+            thread::spawn(move || {
+                let result = match query_status() {
+                    Ok(status) => ClockState::Status(status),
+                    Err(err) => {
+                        tracing::warn!("clock synchronization status unavailable: {}", err);
+                        ClockState::Unavailable(err.to_string())
+                    }
+                };
+                if let Ok(mut state) = state.lock() {
+                    *state = result;
+                }
+            });
+        }
+
+        match self.state.lock().as_deref() {
+            Ok(ClockState::Status(status)) if status.ntp && !status.synchronized => {
+                vec![Alert {
+                    key: ALERT_KEY.to_string(),
+                    summary: fl!("clock-unsynchronized-alert"),
+                    body: fl!("clock-unsynchronized-body"),
+                    severity: Severity::Warning,
+                }]
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    pub fn view(&self) -> Element<Message> {
+        let state = self
+            .state
+            .lock()
+            .map(|state| state.clone())
+            .unwrap_or(ClockState::Pending);
+        let status = match state {
+            ClockState::Pending => fl!("loading"),
+            ClockState::Unavailable(err) => fl!("clock-unavailable", error = err),
+            ClockState::Status(status) => {
+                let mut text = if !status.ntp {
+                    fl!("clock-ntp-off")
+                } else if status.synchronized {
+                    fl!("clock-synchronized")
+                } else {
+                    fl!("clock-unsynchronized")
+                };
+                if let Some(offset) = status.offset {
+                    text = format!("{} · {}", text, format_offset(offset));
+                }
+                text
+            }
+        };
+
+        layout::directional_row(vec![
+            widget::text(fl!("clock"))
+                .width(Length::Fill)
+                .horizontal_alignment(layout::start_alignment())
+                .into(),
+            widget::text(status).into(),
+        ])
+        .spacing(8)
+        .width(Length::Fill)
+        .into()
+    }
+}
+
+fn query_status() -> zbus::Result<ClockStatus> {
+    let connection = zbus::blocking::Connection::system()?;
+    let timedate = Timedate1ProxyBlocking::new(&connection)?;
+    Ok(ClockStatus {
+        ntp: timedate.ntp()?,
+        synchronized: timedate.ntp_synchronized()?,
+        offset: chrony_offset().or_else(timesyncd_offset),
+    })
+}
+
+fn chrony_offset() -> Option<f64> {
+    let output = Command::new("chronyc").arg("tracking").output().ok()?;
+    output
+        .status
+        .success()
+        .then(|| parse_chrony_offset(&String::from_utf8_lossy(&output.stdout)))?
+}
+
+fn timesyncd_offset() -> Option<f64> {
+    let output = Command::new("timedatectl")
+        .arg("timesync-status")
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| parse_timesyncd_offset(&String::from_utf8_lossy(&output.stdout)))?
+}
+
+/// From `System time     : 0.000012345 seconds fast of NTP time`, slow is negative.
+fn parse_chrony_offset(tracking: &str) -> Option<f64> {
+    let line = tracking
+        .lines()
+        .find(|line| line.starts_with("System time"))?;
+    let mut fields = line.split_once(':')?.1.split_whitespace();
+    let seconds = fields.next()?.parse::<f64>().ok()?;
+    match fields.nth(1)? {
+        "fast" => Some(seconds),
+        "slow" => Some(-seconds),
+        _ => None,
+    }
+}
+
+/// From `Offset: -1.234ms`, timesyncd picks the unit to fit the value.
+fn parse_timesyncd_offset(status: &str) -> Option<f64> {
+    let value = status
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("Offset:"))?
+        .trim();
+    let split = value.find(|c: char| c.is_alphabetic())?;
+    let (number, unit) = value.split_at(split);
+    let number = number.parse::<f64>().ok()?;
+    match unit {
+        "ns" => Some(number / 1e9),
+        "us" => Some(number / 1e6),
+        "ms" => Some(number / 1e3),
+        "s" => Some(number),
+        "min" => Some(number * 60.0),
+        _ => None,
+    }
+}
+
+fn format_offset(seconds: f64) -> String {
+    if seconds.abs() < 1.0 {
+        format!("{:+.1} ms", seconds * 1000.0)
+    } else {
+        format!("{:+.2} s", seconds)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_chrony_offset() {
+        let tracking = "\
+Reference ID    : C0A80001 (router.lan)
+Stratum         : 3
+System time     : 0.000250000 seconds slow of NTP time
+Last offset     : -0.000012345 seconds
+";
+        assert_eq!(parse_chrony_offset(tracking), Some(-0.00025));
+    }
+
+    #[test]
+    fn parses_timesyncd_offset() {
+        let status = "       Server: 192.168.1.1 (router.lan)\n       Offset: +1.5ms\n        Delay: 3.2ms\n";
+        assert_eq!(parse_timesyncd_offset(status), Some(0.0015));
+        assert_eq!(parse_timesyncd_offset("Offset: -2s"), Some(-2.0));
+    }
+}
@@ -1,24 +1,601 @@
 use cosmic::cosmic_config::{self, cosmic_config_derive::CosmicConfigEntry, CosmicConfigEntry};
 
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
 pub const CONFIG_VERSION: u64 = 1;
 
+/// Unit of the memory chart's y-axis.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub enum MemoryScale {
+    #[default]
+    Percent,
+    /// Used and total memory in GiB
+    Absolute,
+}
+
+/// Size of the charts in the popup.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub enum ChartDensity {
+    /// Fits short panels and small screens
+    Compact,
+    #[default]
+    Regular,
+    Large,
+}
+
+impl ChartDensity {
+    pub fn chart_height(self) -> f32 {
+        match self {
+            ChartDensity::Compact => 100.0,
+            ChartDensity::Regular => 180.0,
+            ChartDensity::Large => 260.0,
+        }
+    }
+
+    /// Space around each chart row
+    pub fn padding(self) -> u16 {
+        match self {
+            ChartDensity::Compact => 4,
+            ChartDensity::Regular => 12,
+            ChartDensity::Large => 16,
+        }
+    }
+
+    /// Space between the plotting area and the edge of the chart
+    pub fn margin(self) -> u32 {
+        match self {
+            ChartDensity::Compact => 8,
+            ChartDensity::Regular => 20,
+            ChartDensity::Large => 24,
+        }
+    }
+}
+
+/// Values shown next to the panel icon with `panel_values`.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub enum PanelMetric {
+    #[default]
+    All,
+    Cpu,
+    Memory,
+}
+
+impl PanelMetric {
+    /// Ids of the series shown
+    pub fn series(self) -> &'static [&'static str] {
+        match self {
+            PanelMetric::All => &["cpu", "memory"],
+            PanelMetric::Cpu => &["cpu"],
+            PanelMetric::Memory => &["memory"],
+        }
+    }
+
+    /// Name of the chart the popup opens on while a single metric is shown
+    pub fn source(self) -> Option<&'static str> {
+        match self {
+            PanelMetric::All => None,
+            PanelMetric::Cpu => Some("CPU"),
+            PanelMetric::Memory => Some("Memory"),
+        }
+    }
+}
+
+/// Colors of the plotted series.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub enum ChartPalette {
+    /// The first series in the accent color, the others in the text color
+    #[default]
+    Accent,
+    /// Okabe and Ito's palette, told apart with every common color vision deficiency
+    OkabeIto,
+    /// Paul Tol's bright scheme, also colorblind safe
+    TolBright,
+    /// The IBM Design Library's colorblind safe palette
+    Ibm,
+}
+
+/// Side of the charts the value axis labels are drawn on.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub enum AxisSide {
+    /// Left, or right when the language is written right to left
+    #[default]
+    Auto,
+    Left,
+    Right,
+}
+
+impl AxisSide {
+    pub fn is_right(self) -> bool {
+        match self {
+            AxisSide::Auto => crate::localize::is_rtl(),
+            AxisSide::Left => false,
+            AxisSide::Right => true,
+        }
+    }
+}
+
+/// How the charts are drawn.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub enum ChartRendering {
+    /// Canvas charts, falling back to bars when drawing them turns out to be too slow
+    #[default]
+    Auto,
+    Canvas,
+    /// Bars and values only, for software rendering
+    Simple,
+}
+
+/// Bundles of sections, sampling and alert limits for a kind of machine.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub enum Profile {
+    /// Whatever was set by hand, no preset applies
+    #[default]
+    Custom,
+    Desktop,
+    /// Samples less often and watches temperatures and frequencies
+    Laptop,
+    /// Services, users, drives and connections, with lower alert limits
+    Server,
+}
+
+impl Profile {
+    /// The presets offered in the popup
+    pub const PRESETS: [Profile; 3] = [Profile::Desktop, Profile::Laptop, Profile::Server];
+
+    /// Overwrites the settings the profile bundles, leaving the rest alone.
+    pub fn apply(self, config: &mut Config) {
+        config.profile = self;
+        match self {
+            Profile::Custom => {}
+            Profile::Desktop => {
+                config.sample_interval_ms = 1000;
+                config.show_processes = true;
+                config.show_disks = true;
+                config.show_cpu_frequency = false;
+                config.show_cgroups = false;
+                config.show_users = false;
+                config.show_connections = false;
+                config.temperature_alert = 90;
+                config.busy_threshold = 90;
+            }
+            Profile::Laptop => {
+                config.sample_interval_ms = 2000;
+                config.show_processes = true;
+                config.show_cpu_frequency = true;
+                config.show_disks = false;
+                config.show_cgroups = false;
+                config.show_users = false;
+                config.show_connections = false;
+                config.temperature_alert = 85;
+                config.busy_threshold = 80;
+            }
+            Profile::Server => {
+                config.sample_interval_ms = 5000;
+                config.show_processes = true;
+                config.show_disks = true;
+                config.show_filesystems = true;
+                config.show_cgroups = true;
+                config.show_users = true;
+                config.show_connections = true;
+                config.smart_enabled = true;
+                config.temperature_alert = 80;
+                config.busy_threshold = 75;
+            }
+        }
+    }
+}
+
+/// What happens to alerts raised during quiet hours.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub enum QuietMode {
+    /// Listed in the popup's events, without a notification
+    #[default]
+    PopupOnly,
+    /// Dropped, conditions still present when quiet hours end notify then
+    Suppress,
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub enum BudgetPeriod {
+    Daily,
+    #[default]
+    Monthly,
+}
+
+/// A user command whose printed number is plotted as its own chart.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct CustomMetric {
+    pub label: String,
+    pub command: String,
+    #[serde(default)]
+    pub unit: String,
+}
+
+/// A button in the popup running a shell command.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct QuickAction {
+    pub label: String,
+    pub command: String,
+}
+
+/// A URL requested periodically, its status and latency shown in the popup.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct HealthCheck {
+    #[serde(default)]
+    pub label: String,
+    pub url: String,
+}
+
+/// Friendly name and group of a hwmon sensor, keyed by its hardware label.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct SensorLabel {
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub group: String,
+}
+
 #[derive(Clone, CosmicConfigEntry, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub struct Config {
-    // #[serde(default)]
-    // pub show_tooltip: bool,
-    // #[serde(default)]
-    // pub last_used_limit: usize,
-    // #[serde(default)]
-    // pub last_used: Vec<String>,
-    // #[serde(default)]
-    // pub font_family: String,
-    // #[serde(default)]
-    // pub show_unicode: bool,
+    #[serde(default)]
+    pub panel_values: bool,
+    #[serde(default)]
+    pub panel_metric: PanelMetric,
+    /// Show a dot with the number of ongoing alert conditions over the panel icon
+    #[serde(default = "default_panel_alert_badge")]
+    pub panel_alert_badge: bool,
+    /// Pulse the panel button while a reading is critical, also with notifications off
+    #[serde(default)]
+    pub panel_flash: bool,
+    /// Memory usage in percent that flashes the panel, 0 to only use alerts
+    #[serde(default = "default_critical_memory")]
+    pub critical_memory: u32,
+    /// Temperature in °C of any sensor that flashes the panel, 0 to only use alerts
+    #[serde(default = "default_critical_temperature")]
+    pub critical_temperature: u32,
+    /// The preset last applied, `Custom` once nothing was
+    #[serde(default)]
+    pub profile: Profile,
+    /// How often the charts are sampled, on battery and with `reduced_motion` it can only
+    /// get slower
+    #[serde(default = "default_sample_interval_ms")]
+    pub sample_interval_ms: u64,
+    #[serde(default)]
+    pub memory_scale: MemoryScale,
+    #[serde(default)]
+    pub chart_density: ChartDensity,
+    #[serde(default)]
+    pub chart_rendering: ChartRendering,
+    #[serde(default)]
+    pub chart_palette: ChartPalette,
+    /// Size of the axis labels in logical pixels, scaled with the display
+    #[serde(default = "default_chart_label_size")]
+    pub chart_label_size: u32,
+    /// Font family of the axis labels, empty for the default sans-serif
+    #[serde(default)]
+    pub chart_label_font: String,
+    #[serde(default)]
+    pub chart_axis_side: AxisSide,
+    /// Redraw less often and draw a plainer grid, for motion sensitivity or saving power
+    #[serde(default)]
+    pub reduced_motion: bool,
+    /// Describe every chart in text, for screen readers
+    #[serde(default)]
+    pub text_summary: bool,
+    /// Open the popup with the full charts instead of the quick glance, remembers the last
+    /// stage used
+    #[serde(default)]
+    pub popup_expanded: bool,
+    /// Show the quick glance as a strip of small CPU, memory, network and temperature
+    /// charts instead of values
+    #[serde(default)]
+    pub glance_sparklines: bool,
+    /// Set once the optional sections were matched to the hardware on the first run, or
+    /// on the first run since detection exists, where they are left as configured
+    #[serde(default)]
+    pub hardware_detected: bool,
+    /// Show the cpufreq governor and per-core frequencies
+    #[serde(default)]
+    pub show_cpu_frequency: bool,
+    /// Chart major page faults and huge page usage
+    #[serde(default)]
+    pub advanced_memory_metrics: bool,
+    /// Chart pages swapped in and out per second
+    #[serde(default)]
+    pub swap_activity_metric: bool,
+    /// Chart context switches and interrupts per second, and the run queue
+    #[serde(default)]
+    pub scheduler_metrics: bool,
+    /// List interrupts and softirqs per CPU with the busiest sources
+    #[serde(default)]
+    pub show_interrupts: bool,
+    /// Chart PipeWire XRuns and show its quantum, for pro-audio work
+    #[serde(default)]
+    pub audio_metrics: bool,
+    /// Experimental: chart how regularly the compositor lets the open popup draw
+    #[serde(default)]
+    pub frame_time_metric: bool,
+    /// Show zram and zswap compression under the memory chart
+    #[serde(default)]
+    pub show_compressed_swap: bool,
+    /// Show CPU and memory grouped by systemd slice and scope
+    #[serde(default)]
+    pub show_cgroups: bool,
+    /// Show CPU and memory per logged in user
+    #[serde(default)]
+    pub show_users: bool,
+    #[serde(default)]
+    pub show_processes: bool,
+    /// List the remote hosts with the most TCP traffic, read with `ss`
+    #[serde(default)]
+    pub show_connections: bool,
+    /// List the processes holding GPU memory under the GPU chart
+    #[serde(default)]
+    pub show_gpu_processes: bool,
+    /// Show utilization, throughput and temperature per drive
+    #[serde(default)]
+    pub show_disks: bool,
+    /// Show the usage of every mounted filesystem with an estimate of when it fills up
+    #[serde(default)]
+    pub show_filesystems: bool,
+    /// Notify when a filesystem is estimated to fill up within this many days, 0 to
+    /// never notify
+    #[serde(default = "default_days_until_full_alert")]
+    pub days_until_full_alert: u32,
+    /// Query drive health with `smartctl`
+    #[serde(default)]
+    pub smart_enabled: bool,
+    /// List libvirt domains, only used when built with the `libvirt` feature
+    #[serde(default)]
+    pub show_vms: bool,
+    #[serde(default = "default_libvirt_uri")]
+    pub libvirt_uri: String,
+    /// Inside a virtual machine, hide emulated sensors and label the CPU chart as vCPU
+    #[serde(default = "default_adapt_to_vm")]
+    pub adapt_to_vm: bool,
+    /// Show how much memory the host reclaimed through the virtio balloon, needs debugfs access
+    #[serde(default)]
+    pub show_balloon: bool,
+    /// List pod usage from the kubelet summary API of the current `kubectl` context
+    #[serde(default)]
+    pub show_pods: bool,
+    /// `kubectl` context to list pods of, empty for the current context as long as its
+    /// API server is on this machine
+    #[serde(default)]
+    pub pods_context: String,
+    /// List CPU, memory, network and block I/O of Docker and Podman containers
+    #[serde(default)]
+    pub show_containers: bool,
+    /// Show whether the clock is synchronized over NTP and notify when it stops being so
+    #[serde(default)]
+    pub show_clock_sync: bool,
+    /// Show a badge in the popup header while a reboot or security updates are pending
+    #[serde(default)]
+    pub show_updates: bool,
+    /// Also send a notification for them
+    #[serde(default)]
+    pub notify_updates: bool,
+    /// Shell command failing when a reboot is needed, empty to check the distribution's
+    /// marker
+    #[serde(default)]
+    pub reboot_required_command: String,
+    /// Shell command printing one line per pending security update, empty to use the
+    /// distribution's package manager
+    #[serde(default)]
+    pub security_updates_command: String,
+    /// CPU usage in percent above which busy periods are recorded, 0 disables it
+    #[serde(default = "default_busy_threshold")]
+    pub busy_threshold: u32,
+    /// How long the CPU has to stay busy for a period to be recorded
+    #[serde(default = "default_busy_min_seconds")]
+    pub busy_min_seconds: u32,
+    /// CPU usage in percent counted towards the daily summary, 0 hides it
+    #[serde(default)]
+    pub daily_load_threshold: u32,
+    /// Keep a week of daily averages and show them as a report
+    #[serde(default)]
+    pub weekly_report: bool,
+    #[serde(default)]
+    pub sensor_labels: BTreeMap<String, SensorLabel>,
+    /// Temperature in °C that triggers a notification for sensors without hardware limits,
+    /// 0 disables it
+    #[serde(default)]
+    pub temperature_alert: u32,
+    /// Start of the daily quiet hours as `HH:MM`, empty disables them
+    #[serde(default)]
+    pub quiet_hours_start: String,
+    /// End of the quiet hours, before the start when they span midnight
+    #[serde(default)]
+    pub quiet_hours_end: String,
+    #[serde(default)]
+    pub quiet_mode: QuietMode,
+    #[serde(default)]
+    pub prometheus_enabled: bool,
+    #[serde(default = "default_prometheus_port")]
+    pub prometheus_port: u16,
+    #[serde(default)]
+    pub mqtt_enabled: bool,
+    #[serde(default)]
+    pub mqtt_broker_url: String,
+    #[serde(default = "default_mqtt_topic")]
+    pub mqtt_topic: String,
+    #[serde(default)]
+    pub mqtt_username: String,
+    #[serde(default)]
+    pub mqtt_password: String,
+    #[serde(default)]
+    pub custom_metrics: Vec<CustomMetric>,
+    /// Directory of a node_exporter textfile collector, read for `textfile_metrics`
+    #[serde(default)]
+    pub textfile_directory: String,
+    /// Metric names from the `*.prom` files to chart, one chart each
+    #[serde(default)]
+    pub textfile_metrics: Vec<String>,
+    #[serde(default)]
+    pub quick_actions: Vec<QuickAction>,
+    /// systemd units whose state and usage are shown, with a restart button
+    #[serde(default)]
+    pub watched_units: Vec<String>,
+    /// Services to probe over HTTP, a failing one also shows a red dot on the panel
+    #[serde(default)]
+    pub health_checks: Vec<HealthCheck>,
+    /// Process names whose resident memory is charted
+    #[serde(default)]
+    pub watched_processes: Vec<String>,
+    /// Interface whose traffic counts against the data budget
+    #[serde(default)]
+    pub data_budget_interface: String,
+    /// Data budget per period in bytes, 0 disables it
+    #[serde(default)]
+    pub data_budget_bytes: u64,
+    #[serde(default)]
+    pub data_budget_period: BudgetPeriod,
+}
+
+fn default_sample_interval_ms() -> u64 {
+    1000
+}
+
+fn default_prometheus_port() -> u16 {
+    9184
+}
+
+fn default_busy_threshold() -> u32 {
+    90
+}
+
+fn default_busy_min_seconds() -> u32 {
+    30
+}
+
+fn default_chart_label_size() -> u32 {
+    8
+}
+
+fn default_days_until_full_alert() -> u32 {
+    7
+}
+
+fn default_panel_alert_badge() -> bool {
+    true
+}
+
+fn default_critical_memory() -> u32 {
+    98
+}
+
+fn default_critical_temperature() -> u32 {
+    95
+}
+
+fn default_libvirt_uri() -> String {
+    "qemu:///system".to_string()
+}
+
+fn default_adapt_to_vm() -> bool {
+    true
+}
+
+fn default_mqtt_topic() -> String {
+    "cosmic/sys-status".to_string()
 }
 
 impl Default for Config {
     fn default() -> Self {
-        Self {}
+        Self {
+            panel_values: false,
+            panel_metric: PanelMetric::default(),
+            panel_alert_badge: default_panel_alert_badge(),
+            panel_flash: false,
+            critical_memory: default_critical_memory(),
+            critical_temperature: default_critical_temperature(),
+            profile: Profile::default(),
+            sample_interval_ms: default_sample_interval_ms(),
+            memory_scale: MemoryScale::default(),
+            chart_density: ChartDensity::default(),
+            chart_rendering: ChartRendering::default(),
+            chart_palette: ChartPalette::default(),
+            chart_label_size: default_chart_label_size(),
+            chart_label_font: String::new(),
+            chart_axis_side: AxisSide::default(),
+            reduced_motion: false,
+            text_summary: false,
+            popup_expanded: false,
+            glance_sparklines: false,
+            hardware_detected: false,
+            show_cpu_frequency: false,
+            advanced_memory_metrics: false,
+            swap_activity_metric: false,
+            scheduler_metrics: false,
+            show_interrupts: false,
+            audio_metrics: false,
+            frame_time_metric: false,
+            show_compressed_swap: false,
+            show_cgroups: false,
+            show_users: false,
+            show_processes: false,
+            show_connections: false,
+            show_gpu_processes: false,
+            show_disks: false,
+            show_filesystems: false,
+            days_until_full_alert: default_days_until_full_alert(),
+            smart_enabled: false,
+            show_vms: false,
+            libvirt_uri: default_libvirt_uri(),
+            adapt_to_vm: default_adapt_to_vm(),
+            show_balloon: false,
+            show_pods: false,
+            pods_context: String::new(),
+            show_containers: false,
+            show_clock_sync: false,
+            show_updates: false,
+            notify_updates: false,
+            reboot_required_command: String::new(),
+            security_updates_command: String::new(),
+            busy_threshold: default_busy_threshold(),
+            busy_min_seconds: default_busy_min_seconds(),
+            daily_load_threshold: 0,
+            weekly_report: false,
+            sensor_labels: BTreeMap::new(),
+            temperature_alert: 0,
+            quiet_hours_start: String::new(),
+            quiet_hours_end: String::new(),
+            quiet_mode: QuietMode::default(),
+            prometheus_enabled: false,
+            prometheus_port: default_prometheus_port(),
+            mqtt_enabled: false,
+            mqtt_broker_url: String::new(),
+            mqtt_topic: default_mqtt_topic(),
+            mqtt_username: String::new(),
+            mqtt_password: String::new(),
+            custom_metrics: Vec::new(),
+            textfile_directory: String::new(),
+            textfile_metrics: Vec::new(),
+            quick_actions: Vec::new(),
+            watched_units: Vec::new(),
+            health_checks: Vec::new(),
+            watched_processes: Vec::new(),
+            data_budget_interface: String::new(),
+            data_budget_bytes: 0,
+            data_budget_period: BudgetPeriod::default(),
+        }
+    }
+}
+
+/// Whether no setting was ever saved, i.e. the applet runs for the first time. Only then
+/// may defaults be picked for the user, later runs keep what they configured.
+pub fn is_unsaved() -> bool {
+    let Some(dir) = dirs::config_dir() else {
+        return false;
+    };
+    let dir = dir
+        .join("cosmic")
+        .join(crate::window::ID)
+        .join(format!("v{}", CONFIG_VERSION));
+    match fs::read_dir(dir) {
+        Ok(mut entries) => entries.next().is_none(),
+        Err(err) => err.kind() == io::ErrorKind::NotFound,
     }
 }
@@ -0,0 +1,217 @@
+use crate::fl;
+use crate::layout;
+use crate::window::Message;
+use cosmic::iced::Length;
+use cosmic::widget;
+use cosmic::Element;
+use human_bytes::human_bytes;
+use std::collections::HashMap;
+use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Instant;
+
+const SHOWN_HOSTS: usize = 8;
+
+/// Bytes sent and received on one TCP connection, keyed by local and peer address.
+type Counters = HashMap<(String, String), (u64, u64)>;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct HostTraffic {
+    /// Remote address without the port
+    pub host: String,
+    /// Bytes per second sent to the host
+    pub sent_rate: f64,
+    pub received_rate: f64,
+}
+
+/// Remote hosts with the most TCP traffic, from the socket statistics `ss -ti` prints.
+/// `ss` runs on a background thread, it can take a while with many sockets open.
+#[derive(Default)]
+pub struct ConnectionMonitor {
+    shared: Arc<Mutex<Shared>>,
+    /// Set while `ss` runs, so slow runs don't pile up threads
+    querying: Arc<AtomicBool>,
+}
+
+#[derive(Default)]
+struct Shared {
+    counters: Counters,
+    last_refresh: Option<Instant>,
+    hosts: Vec<HostTraffic>,
+}
+
+impl Shared {
+    /// Turns the counters into rates since the previous update.
+    fn update(&mut self, counters: Counters) {
+        let now = Instant::now();
+        let elapsed = self
+            .last_refresh
+            .map(|time| now.duration_since(time).as_secs_f64())
+            .unwrap_or_default();
+        self.last_refresh = Some(now);
+
+        let mut hosts: HashMap<&str, (f64, f64)> = HashMap::new();
+        if elapsed > 0.0 {
+            for ((local, peer), (sent, received)) in &counters {
+                // New connections have no previous counters to compare against
+                let Some((last_sent, last_received)) =
+                    self.counters.get(&(local.clone(), peer.clone()))
+                else {
+                    continue;
+                };
+                let entry = hosts.entry(host(peer)).or_default();
+                entry.0 += sent.saturating_sub(*last_sent) as f64 / elapsed;
+                entry.1 += received.saturating_sub(*last_received) as f64 / elapsed;
+            }
+        }
+
+        let mut hosts = hosts
+            .into_iter()
+            .filter(|(_, (sent, received))| sent + received > 0.0)
+            .map(|(host, (sent_rate, received_rate))| HostTraffic {
+                host: host.to_string(),
+                sent_rate,
+                received_rate,
+            })
+            .collect::<Vec<_>>();
+        hosts.sort_by(|a, b| {
+            (b.sent_rate + b.received_rate).total_cmp(&(a.sent_rate + a.received_rate))
+        });
+        hosts.truncate(SHOWN_HOSTS);
+        self.hosts = hosts;
+        self.counters = counters;
+    }
+}
+
+impl ConnectionMonitor {
+    /// Starts reading the connections unless the previous read is still running.
+    pub fn refresh(&mut self) {
+        if self.querying.swap(true, Ordering::AcqRel) {
+            return;
+        }
+        let shared = self.shared.clone();
+        let querying = self.querying.clone();
+        thread::spawn(move || {
+            let output = Command::new("ss")
+                .args(["-t", "-i", "-n", "-H", "state", "established"])
+                .output()
+                .ok()
+                .filter(|output| output.status.success());
+            if let Ok(mut shared) = shared.lock() {
+                match output {
+                    Some(output) => shared.update(parse(&String::from_utf8_lossy(&output.stdout))),
+                    None => shared.hosts.clear(),
+                }
+            }
+            querying.store(false, Ordering::Release);
+        });
+    }
+
+    pub fn view(&self) -> Element<Message> {
+        let mut column = widget::column()
+            .spacing(4)
+            .width(Length::Fill)
+            .push(widget::text(fl!("top-talkers")));
+
+        let hosts = self
+            .shared
+            .lock()
+            .map(|shared| shared.hosts.clone())
+            .unwrap_or_default();
+        for host in &hosts {
+            column = column.push(
+                layout::directional_row(vec![
+                    widget::text(host.host.as_str())
+                        .width(Length::Fill)
+                        .horizontal_alignment(layout::start_alignment())
+                        .into(),
+                    widget::text(fl!(
+                        "connection-rates",
+                        sent = human_bytes(host.sent_rate),
+                        received = human_bytes(host.received_rate)
+                    ))
+                    .into(),
+                ])
+                .spacing(8),
+            );
+        }
+
+        column.into()
+    }
+}
+
+/// Parses `ss -tinH state established`, where every connection's addresses are followed
+/// by an indented line of TCP info.
+fn parse(output: &str) -> Counters {
+    let mut counters = Counters::new();
+    let mut connection = None;
+    for line in output.lines() {
+        if !line.starts_with(char::is_whitespace) {
+            // Recv-Q, Send-Q, local and peer address
+            let fields = line.split_whitespace().collect::<Vec<_>>();
+            connection = match fields[..] {
+                [_, _, local, peer, ..] => Some((local.to_string(), peer.to_string())),
+                _ => None,
+            };
+            continue;
+        }
+
+        let Some(key) = connection.take() else {
+            continue;
+        };
+        let counter = |name: &str| {
+            line.split_whitespace()
+                .find_map(|field| field.strip_prefix(name)?.strip_prefix(':'))
+                .and_then(|value| value.parse::<u64>().ok())
+                .unwrap_or_default()
+        };
+        counters.insert(key, (counter("bytes_sent"), counter("bytes_received")));
+    }
+    counters
+}
+
+/// Strips the port, and the brackets around IPv6 addresses.
+fn host(address: &str) -> &str {
+    let host = address.rsplit_once(':').map_or(address, |(host, _)| host);
+    host.trim_start_matches('[').trim_end_matches(']')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SS: &str = "\
+0      0      192.168.1.10:22      192.168.1.20:51234
+\t cubic wscale:7,7 rto:204 rtt:0.5/0.25 mss:1448 bytes_sent:12345 bytes_acked:12346 bytes_received:6789 segs_out:40
+0      0      [2001:db8::1]:40000  [2001:db8::2]:443
+\t cubic rto:204 bytes_sent:100 bytes_received:2000
+";
+
+    #[test]
+    fn parses_counters_per_connection() {
+        let counters = parse(SS);
+
+        assert_eq!(
+            counters.get(&(
+                "192.168.1.10:22".to_string(),
+                "192.168.1.20:51234".to_string()
+            )),
+            Some(&(12345, 6789))
+        );
+        assert_eq!(
+            counters.get(&(
+                "[2001:db8::1]:40000".to_string(),
+                "[2001:db8::2]:443".to_string()
+            )),
+            Some(&(100, 2000))
+        );
+    }
+
+    #[test]
+    fn host_strips_the_port() {
+        assert_eq!(host("192.168.1.20:51234"), "192.168.1.20");
+        assert_eq!(host("[2001:db8::2]:443"), "2001:db8::2");
+    }
+}
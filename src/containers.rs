@@ -0,0 +1,484 @@
+use crate::fl;
+use crate::layout;
+use crate::window::Message;
+use cosmic::iced::Length;
+use cosmic::widget;
+use cosmic::Element;
+use human_bytes::human_bytes;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::env;
+use std::io::{Read, Write};
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const REFRESH_EVERY: Duration = Duration::from_secs(5);
+const SHOWN_CONTAINERS: usize = 10;
+/// Bounds every read and write on the socket, a hung daemon would block the query forever
+const SOCKET_TIMEOUT: Duration = Duration::from_secs(5);
+/// Sockets serving the Docker Engine API, which Podman's service speaks too. containerd
+/// only has a gRPC API and is left out.
+const API_SOCKETS: [&str; 2] = ["/run/docker.sock", "/run/podman/podman.sock"];
+
+/// Usage of one running container, from the runtime's stats API.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ContainerUsage {
+    pub name: String,
+    /// Share of the whole machine's CPU time
+    pub cpu_percent: f64,
+    /// Bytes without the inactive page cache, as `docker stats` shows it
+    pub memory: u64,
+    /// Bytes per second received on every network interface of the container
+    pub rx_rate: f64,
+    pub tx_rate: f64,
+    /// Bytes per second read from block devices
+    pub read_rate: f64,
+    pub write_rate: f64,
+}
+
+#[derive(Clone, Debug)]
+enum ContainerState {
+    Pending,
+    Unavailable(String),
+    Containers(Vec<ContainerUsage>),
+}
+
+/// An entry of `/containers/json`, which only lists running containers.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ContainerSummary {
+    id: String,
+    #[serde(default)]
+    names: Vec<String>,
+}
+
+/// The parts of `/containers/{id}/stats` shown in the popup.
+#[derive(Debug, Default, Deserialize)]
+struct Stats {
+    #[serde(default)]
+    cpu_stats: CpuStats,
+    #[serde(default)]
+    memory_stats: MemoryStats,
+    /// Missing for containers sharing the host's network
+    networks: Option<HashMap<String, NetworkStats>>,
+    #[serde(default)]
+    blkio_stats: BlkioStats,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct CpuStats {
+    #[serde(default)]
+    cpu_usage: CpuUsage,
+    /// Nanoseconds of CPU time of the whole machine, summed over all cores
+    system_cpu_usage: Option<u64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct CpuUsage {
+    #[serde(default)]
+    total_usage: u64,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct MemoryStats {
+    usage: Option<u64>,
+    #[serde(default)]
+    stats: HashMap<String, u64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct NetworkStats {
+    rx_bytes: u64,
+    tx_bytes: u64,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct BlkioStats {
+    /// `null` without I/O accounting, e.g. in rootless Podman
+    io_service_bytes_recursive: Option<Vec<BlkioEntry>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BlkioEntry {
+    /// `read` or `write`, capitalized with cgroup v1
+    op: String,
+    value: u64,
+}
+
+/// Cumulative counters of a container, compared between refreshes to get rates.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct Counters {
+    cpu_ns: u64,
+    system_ns: u64,
+    rx_bytes: u64,
+    tx_bytes: u64,
+    read_bytes: u64,
+    written_bytes: u64,
+}
+
+impl Counters {
+    fn new(stats: &Stats) -> Self {
+        let networks = stats.networks.iter().flat_map(|networks| networks.values());
+        let blkio = stats
+            .blkio_stats
+            .io_service_bytes_recursive
+            .iter()
+            .flatten();
+        let bytes = |op: &str| -> u64 {
+            blkio
+                .clone()
+                .filter(|entry| entry.op.eq_ignore_ascii_case(op))
+                .map(|entry| entry.value)
+                .sum()
+        };
+        Self {
+            cpu_ns: stats.cpu_stats.cpu_usage.total_usage,
+            system_ns: stats.cpu_stats.system_cpu_usage.unwrap_or_default(),
+            rx_bytes: networks.clone().map(|network| network.rx_bytes).sum(),
+            tx_bytes: networks.map(|network| network.tx_bytes).sum(),
+            read_bytes: bytes("read"),
+            written_bytes: bytes("write"),
+        }
+    }
+
+    /// CPU share of the whole machine, then network and block I/O rates since `previous`.
+    fn rates(&self, previous: Option<&Counters>, elapsed: Duration) -> (f64, [f64; 4]) {
+        let Some(previous) = previous.filter(|_| !elapsed.is_zero()) else {
+            return (0.0, [0.0; 4]);
+        };
+        let per_second =
+            |now: u64, then: u64| now.saturating_sub(then) as f64 / elapsed.as_secs_f64();
+        let system_ns = self.system_ns.saturating_sub(previous.system_ns);
+        let cpu_percent = if system_ns > 0 {
+            self.cpu_ns.saturating_sub(previous.cpu_ns) as f64 / system_ns as f64 * 100.0
+        } else {
+            0.0
+        };
+        (
+            cpu_percent,
+            [
+                per_second(self.rx_bytes, previous.rx_bytes),
+                per_second(self.tx_bytes, previous.tx_bytes),
+                per_second(self.read_bytes, previous.read_bytes),
+                per_second(self.written_bytes, previous.written_bytes),
+            ],
+        )
+    }
+}
+
+/// Reads the stats of every running Docker and Podman container on a background thread.
+pub struct ContainerMonitor {
+    state: Arc<Mutex<ContainerState>>,
+    /// Counters of the previous refresh by container id, with when they were read
+    previous: Arc<Mutex<HashMap<String, (Instant, Counters)>>>,
+    last_run: Option<Instant>,
+    /// Set while a query runs, so a slow daemon doesn't pile up threads
+    querying: Arc<AtomicBool>,
+}
+
+impl ContainerMonitor {
+    pub fn new() -> Self {
+        Self {
+            state: Arc::new(Mutex::new(ContainerState::Pending)),
+            previous: Arc::new(Mutex::new(HashMap::new())),
+            last_run: None,
+            querying: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Starts a new query when due and the previous one completed.
+    pub fn poll(&mut self) {
+        if self
+            .last_run
+            .is_some_and(|time| time.elapsed() < REFRESH_EVERY)
+        {
+            return;
+        }
+        if self.querying.swap(true, Ordering::AcqRel) {
+            return;
+        }
+        self.last_run = Some(Instant::now());
+        let state = self.state.clone();
+        let previous = self.previous.clone();
+        let querying = self.querying.clone();
+        thread::spawn(move || {
+            let result = match query_containers(&previous) {
+                Ok(containers) => ContainerState::Containers(containers),
+                Err(err) => {
+                    tracing::warn!("container stats unavailable: {}", err);
+                    ContainerState::Unavailable(err)
+                }
+            };
+            if let Ok(mut state) = state.lock() {
+                *state = result;
+            }
+            querying.store(false, Ordering::Release);
+        });
+    }
+
+    pub fn view(&self) -> Element<Message> {
+        let mut column = widget::column()
+            .spacing(4)
+            .width(Length::Fill)
+            .push(widget::text(fl!("containers")));
+
+        let state = self
+            .state
+            .lock()
+            .map(|state| state.clone())
+            .unwrap_or(ContainerState::Pending);
+        match state {
+            ContainerState::Pending => column = column.push(widget::text(fl!("loading"))),
+            ContainerState::Unavailable(err) => {
+                column = column.push(widget::text(fl!("containers-unavailable", error = err)))
+            }
+            ContainerState::Containers(containers) if containers.is_empty() => {
+                column = column.push(widget::text(fl!("containers-none")))
+            }
+            ContainerState::Containers(containers) => {
+                for container in containers.iter().take(SHOWN_CONTAINERS) {
+                    column = column.push(
+                        layout::directional_row(vec![
+                            widget::text(container.name.as_str())
+                                .width(Length::Fill)
+                                .horizontal_alignment(layout::start_alignment())
+                                .into(),
+                            widget::text(format!("{:.1}%", container.cpu_percent)).into(),
+                            widget::text(human_bytes(container.memory as f64)).into(),
+                            widget::text(fl!(
+                                "process-network-rates",
+                                rx = human_bytes(container.rx_rate),
+                                tx = human_bytes(container.tx_rate)
+                            ))
+                            .into(),
+                            widget::text(fl!(
+                                "disk-rates",
+                                read = human_bytes(container.read_rate),
+                                write = human_bytes(container.write_rate)
+                            ))
+                            .into(),
+                        ])
+                        .spacing(8),
+                    );
+                }
+            }
+        }
+
+        column.into()
+    }
+}
+
+/// The API sockets of the system daemons and of the user's rootless Podman.
+fn api_sockets() -> Vec<PathBuf> {
+    API_SOCKETS
+        .iter()
+        .map(PathBuf::from)
+        .chain(
+            env::var_os("XDG_RUNTIME_DIR").map(|dir| PathBuf::from(dir).join("podman/podman.sock")),
+        )
+        .filter(|socket| socket.exists())
+        .collect()
+}
+
+/// Running containers of every daemon found, the busiest first.
+fn query_containers(
+    previous: &Mutex<HashMap<String, (Instant, Counters)>>,
+) -> Result<Vec<ContainerUsage>, String> {
+    let sockets = api_sockets();
+    if sockets.is_empty() {
+        return Err("no Docker or Podman API socket found".to_string());
+    }
+    let last = previous
+        .lock()
+        .map(|previous| previous.clone())
+        .unwrap_or_default();
+    let mut counters = HashMap::new();
+    let mut containers = Vec::new();
+    for socket in &sockets {
+        let list: Vec<ContainerSummary> = serde_json::from_slice(&get(socket, "/containers/json")?)
+            .map_err(|err| format!("container list: {}", err))?;
+        for container in list {
+            // `one-shot` skips the second read Docker otherwise waits a second for, the
+            // rates come from the previous refresh instead
+            let path = format!(
+                "/containers/{}/stats?stream=false&one-shot=true",
+                container.id
+            );
+            let stats: Stats = match get(socket, &path).and_then(|body| {
+                serde_json::from_slice(&body).map_err(|err| format!("container stats: {}", err))
+            }) {
+                Ok(stats) => stats,
+                // Stopped between listing and asking for its stats
+                Err(err) => {
+                    tracing::debug!("no stats for container {}: {}", container.id, err);
+                    continue;
+                }
+            };
+            let now = Instant::now();
+            let current = Counters::new(&stats);
+            let (cpu_percent, [rx_rate, tx_rate, read_rate, write_rate]) =
+                match last.get(&container.id) {
+                    Some((at, counters)) => current.rates(Some(counters), now - *at),
+                    None => current.rates(None, Duration::ZERO),
+                };
+            containers.push(ContainerUsage {
+                name: container_name(&container),
+                cpu_percent,
+                memory: memory_usage(&stats.memory_stats),
+                rx_rate,
+                tx_rate,
+                read_rate,
+                write_rate,
+            });
+            counters.insert(container.id, (now, current));
+        }
+    }
+    if let Ok(mut previous) = previous.lock() {
+        *previous = counters;
+    }
+    containers.sort_by(|a, b| b.cpu_percent.total_cmp(&a.cpu_percent));
+    Ok(containers)
+}
+
+fn container_name(container: &ContainerSummary) -> String {
+    container
+        .names
+        .first()
+        .map(|name| name.trim_start_matches('/').to_string())
+        .unwrap_or_else(|| container.id.chars().take(12).collect())
+}
+
+/// Usage minus the inactive page cache, named `inactive_file` with cgroup v2 and
+/// `total_inactive_file` with v1.
+fn memory_usage(memory: &MemoryStats) -> u64 {
+    let inactive = memory
+        .stats
+        .get("inactive_file")
+        .or_else(|| memory.stats.get("total_inactive_file"))
+        .copied()
+        .unwrap_or_default();
+    memory.usage.unwrap_or_default().saturating_sub(inactive)
+}
+
+/// A GET request on the API socket. HTTP/1.0 makes the daemon close the connection after
+/// the response instead of sending it chunked.
+fn get(socket: &Path, path: &str) -> Result<Vec<u8>, String> {
+    let connect = || -> std::io::Result<Vec<u8>> {
+        let mut stream = UnixStream::connect(socket)?;
+        stream.set_read_timeout(Some(SOCKET_TIMEOUT))?;
+        stream.set_write_timeout(Some(SOCKET_TIMEOUT))?;
+        write!(stream, "GET {} HTTP/1.0\r\nHost: localhost\r\n\r\n", path)?;
+        let mut response = Vec::new();
+        stream.read_to_end(&mut response)?;
+        Ok(response)
+    };
+    let response = connect().map_err(|err| format!("{}: {}", socket.display(), err))?;
+    parse_response(&response).map(<[u8]>::to_vec)
+}
+
+/// The body of a successful response.
+fn parse_response(response: &[u8]) -> Result<&[u8], String> {
+    let end = response
+        .windows(4)
+        .position(|window| window == b"\r\n\r\n")
+        .ok_or_else(|| "incomplete response".to_string())?;
+    let head = String::from_utf8_lossy(&response[..end]);
+    let status = head.lines().next().unwrap_or_default();
+    if status.split_whitespace().nth(1) != Some("200") {
+        return Err(status.to_string());
+    }
+    Ok(&response[end + 4..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_the_body_of_successful_responses() {
+        let response = b"HTTP/1.0 200 OK\r\nContent-Type: application/json\r\n\r\n[]";
+        assert_eq!(parse_response(response), Ok(&b"[]"[..]));
+
+        let response = b"HTTP/1.0 404 Not Found\r\n\r\n{\"message\":\"no such container\"}";
+        assert_eq!(
+            parse_response(response),
+            Err("HTTP/1.0 404 Not Found".to_string())
+        );
+    }
+
+    #[test]
+    fn sums_network_and_block_io_counters() {
+        let stats: Stats = serde_json::from_str(
+            r#"{
+                "cpu_stats": {"cpu_usage": {"total_usage": 2000000000}, "system_cpu_usage": 80000000000, "online_cpus": 4},
+                "memory_stats": {"usage": 52428800, "stats": {"inactive_file": 10485760}},
+                "networks": {
+                    "eth0": {"rx_bytes": 1000, "tx_bytes": 200},
+                    "eth1": {"rx_bytes": 24, "tx_bytes": 56}
+                },
+                "blkio_stats": {"io_service_bytes_recursive": [
+                    {"major": 259, "minor": 0, "op": "read", "value": 4096},
+                    {"major": 259, "minor": 0, "op": "write", "value": 8192},
+                    {"major": 8, "minor": 0, "op": "Read", "value": 512}
+                ]}
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            Counters::new(&stats),
+            Counters {
+                cpu_ns: 2_000_000_000,
+                system_ns: 80_000_000_000,
+                rx_bytes: 1024,
+                tx_bytes: 256,
+                read_bytes: 4608,
+                written_bytes: 8192,
+            }
+        );
+        assert_eq!(memory_usage(&stats.memory_stats), 41_943_040);
+    }
+
+    #[test]
+    fn tolerates_missing_networks_and_io_accounting() {
+        let stats: Stats = serde_json::from_str(
+            r#"{"cpu_stats": {"cpu_usage": {"total_usage": 5}}, "blkio_stats": {"io_service_bytes_recursive": null}}"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            Counters::new(&stats),
+            Counters {
+                cpu_ns: 5,
+                ..Counters::default()
+            }
+        );
+    }
+
+    #[test]
+    fn rates_need_a_previous_refresh() {
+        let previous = Counters {
+            cpu_ns: 1_000_000_000,
+            system_ns: 40_000_000_000,
+            ..Counters::default()
+        };
+        let current = Counters {
+            cpu_ns: 3_000_000_000,
+            system_ns: 48_000_000_000,
+            rx_bytes: 2048,
+            tx_bytes: 1024,
+            read_bytes: 512,
+            written_bytes: 256,
+        };
+
+        assert_eq!(current.rates(None, Duration::ZERO), (0.0, [0.0; 4]));
+        assert_eq!(
+            current.rates(Some(&previous), Duration::from_secs(2)),
+            (25.0, [1024.0, 512.0, 256.0, 128.0])
+        );
+    }
+}
@@ -0,0 +1,218 @@
+use crate::fl;
+use crate::layout;
+use crate::window::Message;
+use cosmic::iced::{Alignment, Length};
+use cosmic::widget;
+use cosmic::Element;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::thread;
+
+const CPU_ROOT: &str = "/sys/devices/system/cpu";
+/// Governors offered as quick actions, when the driver supports them
+const QUICK_GOVERNORS: [&str; 2] = ["powersave", "performance"];
+
+#[derive(Clone, Debug)]
+pub struct CoreFrequency {
+    pub core: usize,
+    /// `None` while the core is offline
+    pub mhz: Option<u64>,
+}
+
+/// Current cpufreq governor and per-core frequencies.
+#[derive(Default)]
+pub struct CpuFrequency {
+    governor: Option<String>,
+    available_governors: Vec<String>,
+    /// `on`, `off`, `forceoff` or `notsupported` from the kernel's SMT control
+    smt: Option<String>,
+    cores: Vec<CoreFrequency>,
+}
+
+impl CpuFrequency {
+    pub fn refresh(&mut self) {
+        let policies = cpufreq_dirs();
+        // Governors are set per policy but in practice they all match, show the first one
+        self.governor = policies
+            .first()
+            .and_then(|dir| read_string(&dir.join("scaling_governor")));
+        self.available_governors = policies
+            .first()
+            .and_then(|dir| read_string(&dir.join("scaling_available_governors")))
+            .map(|governors| governors.split_whitespace().map(str::to_string).collect())
+            .unwrap_or_default();
+
+        self.smt = read_string(&Path::new(CPU_ROOT).join("smt/control"))
+            .filter(|control| control != "notimplemented");
+
+        let offline = offline_cores();
+        self.cores = cpu_dirs()
+            .into_iter()
+            .filter_map(|(core, dir)| {
+                // Parked cores keep their directory but lose cpufreq
+                if offline.contains(&core) {
+                    return Some(CoreFrequency { core, mhz: None });
+                }
+                let khz: u64 = read_string(&dir.join("cpufreq/scaling_cur_freq"))?
+                    .parse()
+                    .ok()?;
+                Some(CoreFrequency {
+                    core,
+                    mhz: Some(khz / 1000),
+                })
+            })
+            .collect();
+    }
+
+    pub fn view(&self) -> Element<Message> {
+        let mut governor_row: Vec<Element<Message>> = vec![widget::text(fl!(
+            "cpu-governor",
+            governor = self.governor.as_deref().unwrap_or("-")
+        ))
+        .width(Length::Fill)
+        .horizontal_alignment(layout::start_alignment())
+        .into()];
+        for governor in QUICK_GOVERNORS {
+            if self.available_governors.iter().any(|g| g == governor)
+                && self.governor.as_deref() != Some(governor)
+            {
+                governor_row.push(
+                    widget::button::standard(governor)
+                        .on_press(Message::SetGovernor(governor.to_string()))
+                        .into(),
+                );
+            }
+        }
+
+        let mut column = widget::column()
+            .spacing(4)
+            .width(Length::Fill)
+            .push(widget::text(fl!("cpu-frequency")))
+            .push(
+                layout::directional_row(governor_row)
+                    .spacing(8)
+                    .align_items(Alignment::Center),
+            );
+        if let Some(smt) = &self.smt {
+            column = column.push(widget::text(fl!("cpu-smt", state = smt.as_str())));
+        }
+        // Four cores per line keeps many-core machines readable
+        for cores in self.cores.chunks(4) {
+            let mut cells: Vec<Element<Message>> = Vec::new();
+            for core in cores {
+                let frequency = match core.mhz {
+                    Some(mhz) => format!("{}: {} MHz", core.core, mhz),
+                    None => fl!("cpu-offline", core = core.core),
+                };
+                cells.push(widget::text(frequency).width(Length::FillPortion(1)).into());
+            }
+            column = column.push(layout::directional_row(cells).spacing(8));
+        }
+
+        column.into()
+    }
+}
+
+/// Switches every policy to `governor` through `pkexec`, which asks for authentication.
+pub fn set_governor(governor: String) {
+    let files = cpufreq_dirs()
+        .into_iter()
+        .map(|dir| dir.join("scaling_governor"))
+        .collect::<Vec<_>>();
+    if files.is_empty() {
+        return;
+    }
+
+    thread::spawn(move || {
+        let result = Command::new("pkexec")
+            .arg("tee")
+            .args(&files)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .spawn()
+            .and_then(|mut child| {
+                if let Some(mut stdin) = child.stdin.take() {
+                    stdin.write_all(governor.as_bytes())?;
+                }
+                child.wait()
+            });
+        match result {
+            Ok(status) if status.success() => tracing::info!("cpu governor set to {}", governor),
+            Ok(status) => tracing::warn!("failed to set cpu governor: pkexec {}", status),
+            Err(err) => tracing::warn!("failed to set cpu governor: {}", err),
+        }
+    });
+}
+
+fn cpufreq_dirs() -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(Path::new(CPU_ROOT).join("cpufreq")) else {
+        return Vec::new();
+    };
+    let mut dirs = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with("policy"))
+        })
+        .collect::<Vec<_>>();
+    dirs.sort();
+    dirs
+}
+
+fn cpu_dirs() -> Vec<(usize, PathBuf)> {
+    let Ok(entries) = fs::read_dir(CPU_ROOT) else {
+        return Vec::new();
+    };
+    let mut cpus = entries
+        .flatten()
+        .filter_map(|entry| {
+            let core = entry
+                .file_name()
+                .to_str()?
+                .strip_prefix("cpu")?
+                .parse()
+                .ok()?;
+            Some((core, entry.path()))
+        })
+        .collect::<Vec<_>>();
+    cpus.sort_by_key(|(core, _)| *core);
+    cpus
+}
+
+/// Cores taken offline, through hotplug or by the kernel parking SMT siblings.
+pub fn offline_cores() -> Vec<usize> {
+    read_string(&Path::new(CPU_ROOT).join("offline"))
+        .map(|list| parse_cpu_list(&list))
+        .unwrap_or_default()
+}
+
+/// Parses the kernel's CPU list format, e.g. `0-3,8,10-11`.
+fn parse_cpu_list(list: &str) -> Vec<usize> {
+    list.split(',')
+        .filter_map(|range| {
+            let range = range.trim();
+            let (start, end) = range.split_once('-').unwrap_or((range, range));
+            Some(start.parse::<usize>().ok()?..=end.parse::<usize>().ok()?)
+        })
+        .flatten()
+        .collect()
+}
+
+fn read_string(path: &Path) -> Option<String> {
+    Some(fs::read_to_string(path).ok()?.trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_cpu_lists() {
+        assert_eq!(parse_cpu_list("1,4-6\n"), vec![1, 4, 5, 6]);
+        assert_eq!(parse_cpu_list(""), Vec::<usize>::new());
+    }
+}
@@ -0,0 +1,194 @@
+use crate::config::Config;
+use crate::fl;
+use crate::layout;
+use crate::state;
+use crate::window::Message;
+use chrono::{Days, Local, NaiveDate};
+use cosmic::iced::Length;
+use cosmic::widget;
+use cosmic::Element;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+const DAILY_FILE: &str = "daily-usage.bin";
+/// Sample intervals a pause between samples may span before it's left out. `Instant`
+/// doesn't advance while suspended, longer pauses are the applet not sampling, so it
+/// doesn't know the load.
+const MAX_MISSED_SAMPLES: u32 = 3;
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+struct DayUsage {
+    /// Seconds the applet sampled the system
+    up: u64,
+    /// Seconds of those with the CPU at or above `daily_load_threshold`
+    busy: u64,
+}
+
+/// Hours per day spent above the configured CPU load, persisted for today and yesterday.
+pub struct DailyUsage {
+    days: BTreeMap<NaiveDate, DayUsage>,
+    last_update: Option<Instant>,
+    /// Fractions of a second not yet added to today, so short intervals add up
+    pending: DayPending,
+    dirty: bool,
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+struct DayPending {
+    up: f64,
+    busy: f64,
+}
+
+impl DailyUsage {
+    pub fn load() -> Self {
+        Self {
+            days: state::load(DAILY_FILE).unwrap_or_default(),
+            last_update: None,
+            pending: DayPending::default(),
+            dirty: false,
+        }
+    }
+
+    pub fn save(&mut self) {
+        if !self.dirty {
+            return;
+        }
+        match state::save(DAILY_FILE, &self.days) {
+            Ok(()) => self.dirty = false,
+            Err(err) => tracing::warn!("failed to save daily usage: {}", err),
+        }
+    }
+
+    /// Accounts the time since the last sample to today, `sample_every` being how often
+    /// samples are due.
+    pub fn update(&mut self, config: &Config, cpu: f64, sample_every: Duration) {
+        if config.daily_load_threshold == 0 {
+            return;
+        }
+
+        let now = Instant::now();
+        let elapsed = self
+            .last_update
+            .map(|time| now.duration_since(time))
+            .filter(|elapsed| *elapsed <= sample_every * MAX_MISSED_SAMPLES);
+        self.last_update = Some(now);
+        if let Some(elapsed) = elapsed {
+            let busy = cpu >= config.daily_load_threshold as f64;
+            self.record(Local::now().date_naive(), elapsed.as_secs_f64(), busy);
+        }
+    }
+
+    fn record(&mut self, today: NaiveDate, seconds: f64, busy: bool) {
+        self.pending.up += seconds;
+        if busy {
+            self.pending.busy += seconds;
+        }
+        // Whole seconds are plenty for hours, the rest carries over to the next sample
+        let up = self.pending.up.trunc();
+        let busy = self.pending.busy.trunc();
+        if up == 0.0 {
+            return;
+        }
+        self.pending.up -= up;
+        self.pending.busy -= busy;
+
+        let day = self.days.entry(today).or_default();
+        day.up += up as u64;
+        day.busy += busy as u64;
+        let yesterday = today - Days::new(1);
+        self.days.retain(|date, _| *date >= yesterday);
+        self.dirty = true;
+    }
+
+    pub fn view(&self, config: &Config) -> Element<Message> {
+        let today = Local::now().date_naive();
+        let mut column = widget::column()
+            .spacing(4)
+            .width(Length::Fill)
+            .push(widget::text(fl!(
+                "daily-usage",
+                threshold = config.daily_load_threshold
+            )));
+
+        for (label, date) in [
+            (fl!("today"), today),
+            (fl!("yesterday"), today - Days::new(1)),
+        ] {
+            let usage = self.days.get(&date).copied().unwrap_or_default();
+            column = column.push(
+                layout::directional_row(vec![
+                    widget::text(label)
+                        .width(Length::Fill)
+                        .horizontal_alignment(layout::start_alignment())
+                        .into(),
+                    widget::text(fl!(
+                        "daily-busy-hours",
+                        busy = hours(usage.busy),
+                        up = hours(usage.up)
+                    ))
+                    .into(),
+                ])
+                .spacing(8),
+            );
+        }
+
+        column.into()
+    }
+}
+
+fn hours(seconds: u64) -> String {
+    format!("{:.1}", seconds as f64 / 3600.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn daily() -> DailyUsage {
+        DailyUsage {
+            days: BTreeMap::new(),
+            last_update: None,
+            pending: DayPending::default(),
+            dirty: false,
+        }
+    }
+
+    #[test]
+    fn record_adds_up_short_intervals() {
+        let mut daily = daily();
+        let today = NaiveDate::from_ymd_opt(2024, 5, 2).unwrap();
+
+        for _ in 0..8 {
+            daily.record(today, 0.25, true);
+        }
+
+        assert_eq!(daily.days[&today], DayUsage { up: 2, busy: 2 });
+    }
+
+    #[test]
+    fn record_counts_busy_time_per_day() {
+        let mut daily = daily();
+        let today = NaiveDate::from_ymd_opt(2024, 5, 2).unwrap();
+
+        daily.record(today, 1.0, true);
+        daily.record(today, 1.0, false);
+
+        assert_eq!(daily.days[&today], DayUsage { up: 2, busy: 1 });
+    }
+
+    #[test]
+    fn record_only_keeps_today_and_yesterday() {
+        let mut daily = daily();
+        let first = NaiveDate::from_ymd_opt(2024, 5, 1).unwrap();
+
+        for offset in 0..3 {
+            daily.record(first + Days::new(offset), 1.0, true);
+        }
+
+        assert_eq!(
+            daily.days.keys().copied().collect::<Vec<_>>(),
+            vec![first + Days::new(1), first + Days::new(2)]
+        );
+    }
+}
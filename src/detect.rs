@@ -0,0 +1,155 @@
+//! Probes which data sources the machine has, to enable only the sections that can show
+//! something.
+
+use crate::config::Config;
+use crate::fl;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const POWER_SUPPLY: &str = "/sys/class/power_supply";
+const HWMON: &str = "/sys/class/hwmon";
+const DRM: &str = "/sys/class/drm";
+const CPUFREQ: &str = "/sys/devices/system/cpu/cpu0/cpufreq";
+const SYS_BLOCK: &str = "/sys/block";
+const BALLOON_DRIVER: &str = "/sys/bus/virtio/drivers/virtio_balloon";
+const LIBVIRT_SOCKET: &str = "/run/libvirt/libvirt-sock";
+const CONTAINER_SOCKETS: [&str; 3] = [
+    "/run/docker.sock",
+    "/run/podman/podman.sock",
+    "/run/containerd/containerd.sock",
+];
+
+/// What the machine has. Batteries and sensors show up on their own, they are only
+/// probed to tell the user what was found.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Hardware {
+    pub battery: bool,
+    pub sensors: bool,
+    pub gpu: bool,
+    pub cpufreq: bool,
+    pub compressed_swap: bool,
+    pub smartctl: bool,
+    /// A Docker, Podman or containerd socket
+    pub containers: bool,
+    /// `kubectl` with a kubeconfig
+    pub kubernetes: bool,
+    pub libvirt: bool,
+    pub balloon: bool,
+}
+
+impl Hardware {
+    pub fn probe() -> Self {
+        let runtime_dir = env::var_os("XDG_RUNTIME_DIR").map(PathBuf::from);
+        Self {
+            battery: any_entry(POWER_SUPPLY, |path| {
+                fs::read_to_string(path.join("type")).is_ok_and(|kind| kind.trim() == "Battery")
+            }),
+            sensors: any_entry(HWMON, |path| {
+                fs::read_dir(path).is_ok_and(|files| {
+                    files.flatten().any(|file| {
+                        let name = file.file_name();
+                        let name = name.to_string_lossy();
+                        name.starts_with("temp") && name.ends_with("_input")
+                    })
+                })
+            }),
+            gpu: any_entry(DRM, |path| path.join("device/gpu_busy_percent").exists())
+                || has_command("nvidia-smi"),
+            cpufreq: Path::new(CPUFREQ).exists(),
+            compressed_swap: any_entry(SYS_BLOCK, |path| {
+                path.file_name()
+                    .is_some_and(|name| name.to_string_lossy().starts_with("zram"))
+            }) || fs::read_to_string("/sys/module/zswap/parameters/enabled")
+                .is_ok_and(|enabled| enabled.trim() == "Y"),
+            smartctl: has_command("smartctl"),
+            containers: CONTAINER_SOCKETS
+                .iter()
+                .any(|socket| Path::new(socket).exists())
+                || runtime_dir
+                    .as_ref()
+                    .is_some_and(|dir| dir.join("podman/podman.sock").exists()),
+            kubernetes: has_command("kubectl")
+                && (env::var_os("KUBECONFIG").is_some()
+                    || dirs::home_dir().is_some_and(|home| home.join(".kube/config").exists())),
+            libvirt: Path::new(LIBVIRT_SOCKET).exists(),
+            balloon: Path::new(BALLOON_DRIVER).exists(),
+        }
+    }
+
+    /// Turns the optional sections on or off to match. Sections that are useful on any
+    /// machine, such as processes, are left as configured, and so are pods: a kubeconfig
+    /// may well point at a remote cluster.
+    pub fn apply(&self, config: &mut Config) {
+        config.show_gpu_processes = self.gpu;
+        config.show_cpu_frequency = self.cpufreq;
+        config.show_compressed_swap = self.compressed_swap;
+        config.smart_enabled = self.smartctl;
+        config.show_cgroups = self.containers;
+        config.show_containers = self.containers;
+        config.show_vms = self.libvirt;
+        config.show_balloon = self.balloon;
+    }
+
+    /// Names of what was found, for telling the user.
+    pub fn summary(&self) -> String {
+        let found = [
+            (self.battery, fl!("hardware-battery")),
+            (self.sensors, fl!("hardware-sensors")),
+            (self.gpu, fl!("gpu")),
+            (self.cpufreq, fl!("cpu-frequency")),
+            (self.compressed_swap, fl!("compressed-swap")),
+            (self.smartctl, fl!("smart-health")),
+            (self.containers, fl!("hardware-containers")),
+            (self.kubernetes, fl!("pods")),
+            (self.libvirt, fl!("hardware-libvirt")),
+            (self.balloon, fl!("hardware-balloon")),
+        ]
+        .into_iter()
+        .filter_map(|(present, name)| present.then_some(name))
+        .collect::<Vec<_>>();
+
+        if found.is_empty() {
+            fl!("hardware-none")
+        } else {
+            fl!("hardware-found", found = found.join(", "))
+        }
+    }
+}
+
+fn any_entry(dir: &str, predicate: impl Fn(&Path) -> bool) -> bool {
+    fs::read_dir(dir).is_ok_and(|entries| entries.flatten().any(|entry| predicate(&entry.path())))
+}
+
+fn has_command(name: &str) -> bool {
+    env::var_os("PATH")
+        .is_some_and(|path| env::split_paths(&path).any(|dir| dir.join(name).is_file()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn enables_only_what_was_found() {
+        let mut config = Config {
+            show_vms: true,
+            show_processes: true,
+            ..Config::default()
+        };
+        Hardware {
+            gpu: true,
+            smartctl: true,
+            ..Hardware::default()
+        }
+        .apply(&mut config);
+
+        assert!(config.show_gpu_processes);
+        assert!(config.smart_enabled);
+        assert!(!config.show_vms);
+        assert!(!config.show_containers);
+        assert!(!config.show_pods);
+        // Not tied to any hardware
+        assert!(config.show_processes);
+    }
+}
@@ -0,0 +1,191 @@
+use crate::fl;
+use crate::layout;
+use crate::window::Message;
+use cosmic::iced::{Alignment, Length};
+use cosmic::widget;
+use cosmic::Element;
+use human_bytes::human_bytes;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::time::Instant;
+
+const DISKSTATS: &str = "/proc/diskstats";
+const SYS_BLOCK: &str = "/sys/block";
+/// diskstats counts sectors of 512 bytes regardless of the drive's sector size
+const SECTOR_SIZE: f64 = 512.0;
+
+/// Cumulative counters of one block device from `/proc/diskstats`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct Counters {
+    sectors_read: u64,
+    sectors_written: u64,
+    /// Milliseconds spent doing I/O
+    io_ticks: u64,
+}
+
+#[derive(Clone, Debug)]
+pub struct DiskStatus {
+    pub name: String,
+    /// Share of the time the drive was busy
+    pub utilization: f64,
+    /// Bytes per second
+    pub read_rate: f64,
+    pub write_rate: f64,
+    pub celsius: Option<f32>,
+}
+
+/// Utilization, throughput and temperature of every physical drive.
+#[derive(Default)]
+pub struct DiskMonitor {
+    counters: HashMap<String, Counters>,
+    last_refresh: Option<Instant>,
+    disks: Vec<DiskStatus>,
+}
+
+impl DiskMonitor {
+    pub fn refresh(&mut self) {
+        let counters = fs::read_to_string(DISKSTATS)
+            .map(|diskstats| parse(&diskstats))
+            .unwrap_or_default();
+
+        let now = Instant::now();
+        // The first refresh has no previous counters, so it reports an idle drive
+        let elapsed = self
+            .last_refresh
+            .map(|time| now.duration_since(time).as_secs_f64())
+            .unwrap_or_default();
+        self.last_refresh = Some(now);
+
+        let mut disks = counters
+            .iter()
+            .filter(|(name, _)| is_physical(name))
+            .map(|(name, current)| {
+                let last = self.counters.get(name).copied().unwrap_or(*current);
+                let rate = |current: u64, last: u64| {
+                    if elapsed > 0.0 {
+                        current.saturating_sub(last) as f64 / elapsed
+                    } else {
+                        0.0
+                    }
+                };
+                DiskStatus {
+                    name: name.clone(),
+                    utilization: (rate(current.io_ticks, last.io_ticks) / 10.0).min(100.0),
+                    read_rate: rate(current.sectors_read, last.sectors_read) * SECTOR_SIZE,
+                    write_rate: rate(current.sectors_written, last.sectors_written) * SECTOR_SIZE,
+                    celsius: temperature(name),
+                }
+            })
+            .collect::<Vec<_>>();
+        disks.sort_by(|a, b| a.name.cmp(&b.name));
+        self.disks = disks;
+        self.counters = counters;
+    }
+
+    pub fn view(&self) -> Element<Message> {
+        let mut column = widget::column()
+            .spacing(4)
+            .width(Length::Fill)
+            .push(widget::text(fl!("disks")));
+
+        for disk in &self.disks {
+            let mut cells: Vec<Element<Message>> = vec![
+                widget::text(disk.name.as_str())
+                    .width(Length::Fill)
+                    .horizontal_alignment(layout::start_alignment())
+                    .into(),
+                widget::text(fl!(
+                    "disk-busy",
+                    percent = format!("{:.0}", disk.utilization)
+                ))
+                .into(),
+                widget::text(fl!(
+                    "disk-rates",
+                    read = human_bytes(disk.read_rate),
+                    write = human_bytes(disk.write_rate)
+                ))
+                .into(),
+            ];
+            if let Some(celsius) = disk.celsius {
+                cells.push(widget::text(format!("{:.0}°C", celsius)).into());
+            }
+            column = column.push(
+                layout::directional_row(cells)
+                    .spacing(8)
+                    .align_items(Alignment::Center),
+            );
+        }
+
+        column.into()
+    }
+}
+
+fn parse(diskstats: &str) -> HashMap<String, Counters> {
+    diskstats
+        .lines()
+        .filter_map(|line| {
+            // major, minor, name, then the counters; sectors read is the 3rd counter,
+            // sectors written the 7th and time spent doing I/O the 10th
+            let fields = line.split_whitespace().collect::<Vec<_>>();
+            let counter = |index: usize| fields.get(index)?.parse().ok();
+            Some((
+                fields.get(2)?.to_string(),
+                Counters {
+                    sectors_read: counter(5)?,
+                    sectors_written: counter(9)?,
+                    io_ticks: counter(12)?,
+                },
+            ))
+        })
+        .collect()
+}
+
+/// Whole drives backed by hardware, leaving out partitions, loop, zram and dm devices.
+fn is_physical(name: &str) -> bool {
+    Path::new(SYS_BLOCK).join(name).join("device").exists()
+}
+
+/// From the drive's hwmon device: nvme registers one, SATA drives need `drivetemp`.
+fn temperature(name: &str) -> Option<f32> {
+    let device = Path::new(SYS_BLOCK).join(name).join("device");
+    let hwmon_dirs = fs::read_dir(device.join("hwmon"))
+        .into_iter()
+        .chain(fs::read_dir(&device))
+        .flatten()
+        .flatten()
+        .filter(|entry| entry.file_name().to_string_lossy().starts_with("hwmon"));
+    for hwmon in hwmon_dirs {
+        let Ok(millidegrees) = fs::read_to_string(hwmon.path().join("temp1_input")) else {
+            continue;
+        };
+        if let Ok(millidegrees) = millidegrees.trim().parse::<i64>() {
+            return Some(millidegrees as f32 / 1000.0);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_diskstats_counters() {
+        let diskstats = "\
+ 259       0 nvme0n1 103245 3413 9452074 20651 281845 159385 15034256 218263 0 123456 245009 0 0 0 0 3126 6094
+   8       1 sda1 20 0 1234 5 0 0 0 0 0 12 5 0 0 0 0 0 0
+";
+        let counters = parse(diskstats);
+
+        assert_eq!(
+            counters.get("nvme0n1"),
+            Some(&Counters {
+                sectors_read: 9452074,
+                sectors_written: 15034256,
+                io_ticks: 123456,
+            })
+        );
+        assert_eq!(counters.get("sda1").map(|c| c.io_ticks), Some(12));
+    }
+}
@@ -0,0 +1,66 @@
+use crate::config::Config;
+use crate::metrics::{self, Sample, Sampler};
+use chrono::{SecondsFormat, Utc};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+use std::thread;
+use std::time::Duration;
+
+const DUMP_EVERY: Duration = Duration::from_secs(1);
+
+#[derive(Serialize)]
+struct Snapshot<'a> {
+    timestamp: String,
+    metrics: Vec<Metric<'a>>,
+}
+
+#[derive(Serialize)]
+struct Metric<'a> {
+    source: &'a str,
+    title: &'a str,
+    unit: &'a str,
+    series: BTreeMap<&'a str, f64>,
+}
+
+/// Prints the configured metrics as JSON, one snapshot per line, without starting the UI.
+/// With `follow` a snapshot is printed every second until the output is closed.
+pub fn run(config: &Config, follow: bool) -> io::Result<()> {
+    let mut sampler = Sampler::new(metrics::sources(config));
+    sampler.prime();
+    while !sampler.is_warm() {
+        thread::sleep(sysinfo::MINIMUM_CPU_UPDATE_INTERVAL);
+    }
+
+    let mut stdout = io::stdout().lock();
+    loop {
+        let samples = sampler.sample();
+        let line = serde_json::to_string(&snapshot(&samples)).map_err(io::Error::other)?;
+        writeln!(stdout, "{}", line)?;
+        stdout.flush()?;
+
+        if !follow {
+            return Ok(());
+        }
+        thread::sleep(DUMP_EVERY);
+    }
+}
+
+fn snapshot(samples: &[Sample]) -> Snapshot<'_> {
+    Snapshot {
+        timestamp: Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true),
+        metrics: samples
+            .iter()
+            .map(|sample| Metric {
+                source: &sample.source,
+                title: &sample.title,
+                unit: &sample.scale.unit,
+                series: sample
+                    .series
+                    .iter()
+                    .map(|(id, value)| (id.as_str(), *value))
+                    .collect(),
+            })
+            .collect(),
+    }
+}
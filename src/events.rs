@@ -0,0 +1,103 @@
+use crate::fl;
+use crate::layout;
+use crate::window::Message;
+use chrono::{DateTime, Local, Utc};
+use cosmic::iced::{Alignment, Length};
+use cosmic::widget;
+use cosmic::Element;
+use std::collections::VecDeque;
+use std::time::Instant;
+
+const KEPT_EVENTS: usize = 50;
+const SHOWN_EVENTS: usize = 5;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum EventKind {
+    Alert,
+    Suspend,
+    Resume,
+    Note,
+}
+
+/// Something that happened at a point in time, drawn as a marker on the charts.
+#[derive(Clone, Debug)]
+pub struct Event {
+    pub at: Instant,
+    /// Wall clock time, for display
+    pub time: DateTime<Utc>,
+    pub kind: EventKind,
+    pub text: String,
+}
+
+impl Event {
+    /// Time, kind and text, with the time formatted by `format` in local time.
+    pub fn describe(&self, format: &str) -> String {
+        let kind = match self.kind {
+            EventKind::Alert => fl!("event-alert"),
+            EventKind::Suspend => fl!("event-suspend"),
+            EventKind::Resume => fl!("event-resume"),
+            EventKind::Note => fl!("event-note"),
+        };
+        let time = self.time.with_timezone(&Local).format(format);
+        if self.text.is_empty() {
+            format!("{} {}", time, kind)
+        } else {
+            format!("{} {}: {}", time, kind, self.text)
+        }
+    }
+}
+
+/// Recent events, newest first.
+#[derive(Default)]
+pub struct EventLog {
+    events: VecDeque<Event>,
+}
+
+impl EventLog {
+    pub fn record(&mut self, kind: EventKind, text: String) {
+        tracing::debug!(?kind, text, "event recorded");
+        self.events.push_front(Event {
+            at: Instant::now(),
+            time: Utc::now(),
+            kind,
+            text,
+        });
+        self.events.truncate(KEPT_EVENTS);
+    }
+
+    /// Newest first.
+    pub fn iter(&self) -> impl Iterator<Item = &Event> {
+        self.events.iter()
+    }
+
+    pub fn markers(&self) -> Vec<Instant> {
+        self.events.iter().map(|event| event.at).collect()
+    }
+
+    pub fn view<'a>(&'a self, note: &'a str) -> Element<'a, Message> {
+        let mut column = widget::column()
+            .spacing(4)
+            .width(Length::Fill)
+            .push(widget::text(fl!("events")))
+            .push(
+                layout::directional_row(vec![
+                    widget::text_input(fl!("note-placeholder"), note)
+                        .on_input(Message::NoteInput)
+                        .on_submit(Message::AddNote)
+                        .width(Length::Fill)
+                        .into(),
+                    widget::button::standard(fl!("add-note"))
+                        .on_press(Message::AddNote)
+                        .into(),
+                ])
+                .spacing(8)
+                .align_items(Alignment::Center),
+            );
+
+        for event in self.events.iter().take(SHOWN_EVENTS) {
+            column = column.push(widget::text(event.describe("%H:%M")));
+        }
+
+        column.into()
+    }
+}
@@ -0,0 +1,96 @@
+use crate::history::History;
+use chrono::{DateTime, Local, SecondsFormat, Utc};
+use serde::Serialize;
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+impl ExportFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Json => "json",
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct Record<'a> {
+    timestamp: String,
+    source: &'a str,
+    series: &'a str,
+    value: f64,
+}
+
+fn records(history: &History) -> Vec<Record<'_>> {
+    let mut records = history
+        .series
+        .iter()
+        .flat_map(|series| {
+            series.points.iter().filter_map(move |(ms, value)| {
+                let time: DateTime<Utc> = DateTime::from_timestamp_millis(*ms)?;
+                Some(Record {
+                    timestamp: time.to_rfc3339_opts(SecondsFormat::Millis, true),
+                    source: &series.source,
+                    series: &series.series,
+                    value: *value,
+                })
+            })
+        })
+        .collect::<Vec<_>>();
+    // RFC 3339 timestamps in UTC sort chronologically as strings
+    records.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+    records
+}
+
+fn to_csv(records: &[Record]) -> String {
+    let mut csv = String::from("timestamp,source,series,value\n");
+    for record in records {
+        let _ = writeln!(
+            csv,
+            "{},{},{},{}",
+            record.timestamp,
+            csv_field(record.source),
+            csv_field(record.series),
+            record.value
+        );
+    }
+    csv
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Writes every retained sample to a timestamped file in the download directory
+/// and returns its path.
+pub fn export(history: &History, format: ExportFormat) -> io::Result<PathBuf> {
+    let records = records(history);
+    let contents = match format {
+        ExportFormat::Csv => to_csv(&records),
+        ExportFormat::Json => serde_json::to_string_pretty(&records)?,
+    };
+
+    let dir = dirs::download_dir()
+        .or_else(dirs::home_dir)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no download directory"))?;
+    let path = dir.join(format!(
+        "sys-status-history-{}.{}",
+        Local::now().format("%Y%m%d-%H%M%S"),
+        format.extension()
+    ));
+
+    fs::write(&path, contents)?;
+    Ok(path)
+}
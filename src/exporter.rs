@@ -0,0 +1,266 @@
+use crate::metrics::Sample;
+use std::fmt::Write as _;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{Ipv4Addr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const ACCEPT_POLL: Duration = Duration::from_millis(250);
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(2);
+/// How long binding retries while a dropped exporter's thread still holds the port: one
+/// `ACCEPT_POLL` plus the read and write timeouts of a client being served
+const BIND_RETRY: Duration = Duration::from_millis(4250);
+
+/// Serves the latest samples in the Prometheus text format on localhost.
+pub struct Exporter {
+    port: u16,
+    latest: Arc<Mutex<String>>,
+    /// Tells the thread owning the listening socket to close it and exit
+    stop: Arc<AtomicBool>,
+}
+
+impl Exporter {
+    /// Binds and serves on a thread of its own, errors binding the port are logged there.
+    pub fn spawn(port: u16) -> io::Result<Self> {
+        let latest = Arc::new(Mutex::new(String::new()));
+        let stop = Arc::new(AtomicBool::new(false));
+        let body = latest.clone();
+        let stopped = stop.clone();
+        thread::Builder::new()
+            .name("prometheus-exporter".into())
+            .spawn(move || {
+                let listener = match bind(port, &stopped) {
+                    Ok(listener) => listener,
+                    Err(err) => {
+                        tracing::error!(
+                            "failed to start prometheus exporter on port {}: {}",
+                            port,
+                            err
+                        );
+                        stopped.store(true, Ordering::Relaxed);
+                        return;
+                    }
+                };
+                tracing::info!(
+                    "serving prometheus metrics on http://127.0.0.1:{}/metrics",
+                    port
+                );
+                while !stopped.load(Ordering::Relaxed) {
+                    match listener.accept() {
+                        Ok((stream, _)) => {
+                            let body = body.lock().map(|body| body.clone()).unwrap_or_default();
+                            if let Err(err) = respond(stream, &body) {
+                                tracing::debug!("prometheus client error: {}", err);
+                            }
+                        }
+                        Err(err) if err.kind() == io::ErrorKind::WouldBlock => {
+                            thread::sleep(ACCEPT_POLL)
+                        }
+                        Err(err) => tracing::warn!("prometheus accept failed: {}", err),
+                    }
+                }
+            })?;
+
+        Ok(Self { port, latest, stop })
+    }
+
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    /// `false` once binding the port failed.
+    pub fn is_running(&self) -> bool {
+        !self.stop.load(Ordering::Relaxed)
+    }
+
+    pub fn publish(&self, samples: &[Sample]) {
+        if let Ok(mut latest) = self.latest.lock() {
+            *latest = render(samples);
+        }
+    }
+}
+
+impl Drop for Exporter {
+    /// Doesn't wait for the thread, which can be serving a client for up to its timeouts.
+    /// The port is free once it noticed, a new exporter on the same port retries until then.
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Binds the port, retrying for `BIND_RETRY` while it is in use, as by the thread of an
+/// exporter dropped right before.
+fn bind(port: u16, stopped: &AtomicBool) -> io::Result<TcpListener> {
+    let started = Instant::now();
+    loop {
+        match TcpListener::bind((Ipv4Addr::LOCALHOST, port)) {
+            Ok(listener) => {
+                listener.set_nonblocking(true)?;
+                return Ok(listener);
+            }
+            Err(err)
+                if err.kind() == io::ErrorKind::AddrInUse
+                    && started.elapsed() < BIND_RETRY
+                    && !stopped.load(Ordering::Relaxed) =>
+            {
+                thread::sleep(ACCEPT_POLL)
+            }
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+fn respond(stream: TcpStream, body: &str) -> io::Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(CLIENT_TIMEOUT))?;
+    stream.set_write_timeout(Some(CLIENT_TIMEOUT))?;
+
+    let mut reader = BufReader::new(stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    // Drain the headers, the body of a GET is irrelevant
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 2 {
+        header.clear();
+    }
+
+    let mut stream = reader.into_inner();
+    let mut parts = request_line.split_whitespace();
+    match (parts.next(), parts.next()) {
+        (Some("GET"), Some("/metrics")) => write!(
+            stream,
+            "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            body.len(),
+            body
+        ),
+        _ => write!(
+            stream,
+            "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+        ),
+    }
+}
+
+/// One `# TYPE` line per metric name followed by its series from every source, the
+/// text format rejects a name whose samples are split up or typed twice.
+fn render(samples: &[Sample]) -> String {
+    let mut metrics: Vec<(String, Vec<(&str, f64)>)> = Vec::new();
+    for sample in samples {
+        for (series, value) in &sample.series {
+            let name = format!("sys_status_{}", metric_name(series.as_str()));
+            let point = (sample.source.as_str(), *value);
+            match metrics.iter_mut().find(|(existing, _)| *existing == name) {
+                Some((_, points)) => points.push(point),
+                None => metrics.push((name, vec![point])),
+            }
+        }
+    }
+
+    let mut body = String::new();
+    for (name, points) in metrics {
+        let _ = writeln!(body, "# TYPE {} gauge", name);
+        for (source, value) in points {
+            let _ = writeln!(
+                body,
+                "{}{{source=\"{}\"}} {}",
+                name,
+                escape_label(source),
+                value
+            );
+        }
+    }
+    body
+}
+
+fn metric_name(series: &str) -> String {
+    series
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::{Scale, SeriesId};
+
+    fn sample(source: &str, series: &[(&str, f64)]) -> Sample {
+        Sample {
+            source: source.to_string(),
+            title: source.to_string(),
+            scale: Scale::auto(""),
+            series: series
+                .iter()
+                .map(|(id, value)| (SeriesId::new(*id), *value))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn types_every_metric_name_once() {
+        let body = render(&[
+            sample("GPU card0", &[("gpu", 40.0)]),
+            sample("GPU card1", &[("gpu", 60.0)]),
+        ]);
+        assert_eq!(
+            body,
+            "# TYPE sys_status_gpu gauge\n\
+             sys_status_gpu{source=\"GPU card0\"} 40\n\
+             sys_status_gpu{source=\"GPU card1\"} 60\n"
+        );
+    }
+
+    fn scrape(port: u16) -> io::Result<String> {
+        let mut stream = TcpStream::connect((Ipv4Addr::LOCALHOST, port))?;
+        write!(stream, "GET /metrics HTTP/1.1\r\n\r\n")?;
+        let mut response = String::new();
+        io::Read::read_to_string(&mut stream, &mut response)?;
+        Ok(response)
+    }
+
+    fn scrape_within(port: u16, timeout: Duration) -> String {
+        let started = Instant::now();
+        loop {
+            match scrape(port) {
+                Ok(response) => return response,
+                Err(err) if started.elapsed() > timeout => panic!("no response: {}", err),
+                Err(_) => thread::sleep(Duration::from_millis(50)),
+            }
+        }
+    }
+
+    #[test]
+    fn restarts_on_the_port_of_a_dropped_exporter() {
+        let port = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))
+            .and_then(|listener| listener.local_addr())
+            .unwrap()
+            .port();
+        let exporter = Exporter::spawn(port).unwrap();
+        exporter.publish(&[sample("CPU", &[("cpu", 10.0)])]);
+        assert!(scrape_within(port, Duration::from_secs(2)).contains("sys_status_cpu"));
+
+        drop(exporter);
+        let exporter = Exporter::spawn(port).unwrap();
+        exporter.publish(&[sample("CPU", &[("cpu", 20.0)])]);
+        let started = Instant::now();
+        while !scrape_within(port, BIND_RETRY).contains(" 20\n") {
+            assert!(started.elapsed() < BIND_RETRY, "old exporter still serving");
+            thread::sleep(Duration::from_millis(50));
+        }
+        assert!(exporter.is_running());
+    }
+}
@@ -0,0 +1,239 @@
+use crate::alerts::{Alert, Severity};
+use crate::config::Config;
+use crate::fl;
+use crate::layout;
+use crate::state;
+use crate::window::Message;
+use chrono::Utc;
+use cosmic::iced::{Alignment, Length};
+use cosmic::iced_widget::ProgressBar;
+use cosmic::widget;
+use cosmic::Element;
+use human_bytes::human_bytes;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+use std::time::{Duration, Instant};
+use sysinfo::Disks;
+
+const USAGE_FILE: &str = "filesystem-usage.bin";
+pub const ALERT_PREFIX: &str = "filesystem-full-";
+const REFRESH_EVERY: Duration = Duration::from_secs(60);
+/// Seconds between two persisted samples
+const RECORD_EVERY: i64 = 10 * 60;
+/// Seconds of history the estimate is fitted to
+const TREND_WINDOW: i64 = 14 * 24 * 60 * 60;
+/// An estimate needs at least this much history to not just follow the last download
+const MIN_TREND_SPAN: i64 = 60 * 60;
+const DAY: f64 = 24.0 * 60.0 * 60.0;
+const BAR_WIDTH: f32 = 120.0;
+
+#[derive(Clone, Debug)]
+struct Filesystem {
+    mount_point: String,
+    total: u64,
+    used: u64,
+}
+
+/// Usage of every mounted filesystem with an estimate of when it fills up, from a
+/// persisted history of its used bytes.
+pub struct FilesystemMonitor {
+    filesystems: Vec<Filesystem>,
+    /// `(unix timestamp in seconds, used bytes)` by mount point, oldest first
+    history: BTreeMap<String, VecDeque<(i64, u64)>>,
+    last_refresh: Option<Instant>,
+    dirty: bool,
+}
+
+impl FilesystemMonitor {
+    pub fn load() -> Self {
+        Self {
+            filesystems: Vec::new(),
+            history: state::load(USAGE_FILE).unwrap_or_default(),
+            last_refresh: None,
+            dirty: false,
+        }
+    }
+
+    pub fn save(&mut self) {
+        if !self.dirty {
+            return;
+        }
+        match state::save(USAGE_FILE, &self.history) {
+            Ok(()) => self.dirty = false,
+            Err(err) => tracing::warn!("failed to save filesystem usage: {}", err),
+        }
+    }
+
+    /// Reads the usage once a minute and returns the filesystems expected to fill up
+    /// within `days_until_full_alert` days, for `Alerts::sync`.
+    pub fn refresh(&mut self, config: &Config) -> Option<Vec<Alert>> {
+        if self
+            .last_refresh
+            .is_some_and(|time| time.elapsed() < REFRESH_EVERY)
+        {
+            return None;
+        }
+        self.last_refresh = Some(Instant::now());
+
+        let disks = Disks::new_with_refreshed_list();
+        let mut filesystems = disks
+            .list()
+            .iter()
+            .filter(|disk| !disk.is_removable() && disk.total_space() > 0)
+            .map(|disk| Filesystem {
+                mount_point: disk.mount_point().to_string_lossy().into_owned(),
+                total: disk.total_space(),
+                used: disk.total_space().saturating_sub(disk.available_space()),
+            })
+            .collect::<Vec<_>>();
+        filesystems.sort_by(|a, b| a.mount_point.cmp(&b.mount_point));
+        filesystems.dedup_by(|a, b| a.mount_point == b.mount_point);
+
+        let now = Utc::now().timestamp();
+        for filesystem in &filesystems {
+            self.record(&filesystem.mount_point, now, filesystem.used);
+        }
+        self.history.retain(|mount_point, _| {
+            filesystems
+                .iter()
+                .any(|filesystem| &filesystem.mount_point == mount_point)
+        });
+        self.filesystems = filesystems;
+
+        if config.days_until_full_alert == 0 {
+            return Some(Vec::new());
+        }
+        Some(
+            self.filesystems
+                .iter()
+                .filter_map(|filesystem| {
+                    let days = self.days_until_full(filesystem)?;
+                    (days < config.days_until_full_alert as f64).then(|| Alert {
+                        key: format!("{}{}", ALERT_PREFIX, filesystem.mount_point),
+                        summary: fl!(
+                            "filesystem-filling-up",
+                            mount_point = filesystem.mount_point.as_str()
+                        ),
+                        body: fl!("days-until-full", days = format!("{:.0}", days)),
+                        severity: Severity::Warning,
+                    })
+                })
+                .collect(),
+        )
+    }
+
+    fn record(&mut self, mount_point: &str, now: i64, used: u64) {
+        let samples = self.history.entry(mount_point.to_string()).or_default();
+        if samples
+            .back()
+            .is_some_and(|(time, _)| now - time < RECORD_EVERY)
+        {
+            return;
+        }
+        samples.push_back((now, used));
+        while samples
+            .front()
+            .is_some_and(|(time, _)| now - time > TREND_WINDOW)
+        {
+            samples.pop_front();
+        }
+        self.dirty = true;
+    }
+
+    fn days_until_full(&self, filesystem: &Filesystem) -> Option<f64> {
+        let growth = growth_per_second(self.history.get(&filesystem.mount_point)?)?;
+        Some(filesystem.total.saturating_sub(filesystem.used) as f64 / growth / DAY)
+    }
+
+    pub fn view(&self) -> Element<Message> {
+        let mut column = widget::column()
+            .spacing(4)
+            .width(Length::Fill)
+            .push(widget::text(fl!("filesystems")));
+
+        for filesystem in &self.filesystems {
+            let mut cells: Vec<Element<Message>> = vec![
+                widget::text(filesystem.mount_point.as_str())
+                    .width(Length::Fill)
+                    .horizontal_alignment(layout::start_alignment())
+                    .into(),
+                ProgressBar::new(0.0..=1.0, filesystem.used as f32 / filesystem.total as f32)
+                    .width(Length::Fixed(BAR_WIDTH))
+                    .height(Length::Fixed(8.0))
+                    .into(),
+                widget::text(fl!(
+                    "filesystem-usage",
+                    used = human_bytes(filesystem.used as f64),
+                    total = human_bytes(filesystem.total as f64)
+                ))
+                .into(),
+            ];
+            if let Some(days) = self.days_until_full(filesystem) {
+                cells.push(
+                    widget::text(fl!("days-until-full", days = format!("{:.0}", days))).into(),
+                );
+            }
+            column = column.push(
+                layout::directional_row(cells)
+                    .spacing(8)
+                    .align_items(Alignment::Center),
+            );
+        }
+
+        column.into()
+    }
+}
+
+/// Bytes per second the usage grows by, the slope of a least squares fit. `None` while
+/// the usage is stable or shrinking, or the history is too short.
+fn growth_per_second(samples: &VecDeque<(i64, u64)>) -> Option<f64> {
+    let (first, _) = *samples.front()?;
+    let (last, _) = *samples.back()?;
+    if last - first < MIN_TREND_SPAN {
+        return None;
+    }
+    let count = samples.len() as f64;
+    // Relative to the first sample, timestamps and byte counts squared don't fit an f64
+    let points = samples
+        .iter()
+        .map(|&(time, used)| ((time - first) as f64, used as f64))
+        .collect::<Vec<_>>();
+    let mean_time = points.iter().map(|(time, _)| time).sum::<f64>() / count;
+    let mean_used = points.iter().map(|(_, used)| used).sum::<f64>() / count;
+    let covariance = points
+        .iter()
+        .map(|(time, used)| (time - mean_time) * (used - mean_used))
+        .sum::<f64>();
+    let variance = points
+        .iter()
+        .map(|(time, _)| (time - mean_time).powi(2))
+        .sum::<f64>();
+    let slope = covariance / variance;
+    (slope > 0.0).then_some(slope)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fits_the_growth_of_the_usage() {
+        let hour = 60 * 60;
+        let samples = (0..5)
+            .map(|hours| (hours * hour, 1000 + hours as u64 * 3600))
+            .collect::<VecDeque<_>>();
+        assert_eq!(growth_per_second(&samples), Some(1.0));
+
+        let shrinking = samples
+            .iter()
+            .map(|&(time, used)| (time, 100_000 - used))
+            .collect::<VecDeque<_>>();
+        assert_eq!(growth_per_second(&shrinking), None);
+    }
+
+    #[test]
+    fn needs_an_hour_of_history() {
+        let samples = VecDeque::from([(0, 1000), (RECORD_EVERY, 2000)]);
+        assert_eq!(growth_per_second(&samples), None);
+    }
+}
@@ -0,0 +1,140 @@
+//! Pulses the panel button while a reading is past its critical threshold, to be noticed
+//! with notifications turned off.
+
+use crate::alerts::Severity;
+use crate::config::Config;
+use crate::window::Message;
+use cosmic::iced::{Background, Color};
+use cosmic::iced_futures::Subscription;
+use cosmic::iced_style::container;
+use cosmic::{widget, Element, Theme};
+use cosmic_time::{anim, chain, id, style_container, Duration, Instant, Timeline};
+use once_cell::sync::Lazy;
+
+static PANEL: Lazy<id::StyleContainer> = Lazy::new(id::StyleContainer::unique);
+/// From transparent to the full flash color, and the same back
+const HALF_PULSE: Duration = Duration::from_millis(600);
+
+/// Whether a reading is bad enough to flash the panel: a critical alert, memory usage
+/// at `critical_memory` percent or a sensor at `critical_temperature` °C. Zero turns a
+/// threshold off.
+pub fn is_critical(
+    config: &Config,
+    ongoing: Option<(usize, Severity)>,
+    memory_percent: Option<f64>,
+    hottest: Option<f32>,
+) -> bool {
+    ongoing.is_some_and(|(_, severity)| severity == Severity::Critical)
+        || (config.critical_memory > 0
+            && memory_percent.is_some_and(|percent| percent >= config.critical_memory as f64))
+        || (config.critical_temperature > 0
+            && hottest.is_some_and(|celsius| celsius >= config.critical_temperature as f32))
+}
+
+/// The pulsing background of the panel button, a steady one with reduced motion.
+pub struct PanelFlash {
+    timeline: Timeline,
+    active: bool,
+    reduced_motion: bool,
+}
+
+impl PanelFlash {
+    pub fn new() -> Self {
+        Self {
+            timeline: Timeline::new(),
+            active: false,
+            reduced_motion: false,
+        }
+    }
+
+    pub fn set_active(&mut self, active: bool, reduced_motion: bool) {
+        if active == self.active && reduced_motion == self.reduced_motion {
+            return;
+        }
+        self.active = active;
+        self.reduced_motion = reduced_motion;
+        // A fresh timeline drops the running chain
+        self.timeline = Timeline::new();
+        if active && !reduced_motion {
+            let pulse = chain![
+                PANEL,
+                style_container(Duration::ZERO).style(cosmic::theme::Container::Transparent),
+                style_container(HALF_PULSE).style(flash_style()),
+                style_container(HALF_PULSE).style(cosmic::theme::Container::Transparent),
+            ]
+            .loop_forever();
+            self.timeline.set_chain(pulse).start();
+        }
+    }
+
+    /// Advances the animation, from `subscription`.
+    pub fn now(&mut self, now: Instant) {
+        self.timeline.now(now);
+    }
+
+    /// Frames while the pulse runs, nothing otherwise.
+    pub fn subscription(&self) -> Subscription<Instant> {
+        self.timeline.as_subscription().map(|(_, now)| now)
+    }
+
+    pub fn view<'a>(&self, content: Element<'a, Message>) -> Element<'a, Message> {
+        if !self.active {
+            content
+        } else if self.reduced_motion {
+            widget::container(content).style(flash_style()).into()
+        } else {
+            anim!(PANEL, &self.timeline, content).into()
+        }
+    }
+}
+
+fn flash_style() -> cosmic::theme::Container {
+    cosmic::theme::Container::custom(|theme: &Theme| {
+        let cosmic = theme.cosmic();
+        let mut color = Color::from(cosmic.destructive_color());
+        color.a = 0.6;
+        container::Appearance {
+            background: Some(Background::Color(color)),
+            border: cosmic::iced::Border {
+                radius: cosmic.corner_radii.radius_s.into(),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flashes_past_any_critical_threshold() {
+        let config = Config {
+            critical_memory: 98,
+            critical_temperature: 95,
+            ..Config::default()
+        };
+        assert!(!is_critical(&config, None, Some(90.0), Some(70.0)));
+        assert!(is_critical(&config, None, Some(98.5), None));
+        assert!(is_critical(&config, None, None, Some(96.0)));
+        assert!(is_critical(
+            &config,
+            Some((1, Severity::Critical)),
+            None,
+            None
+        ));
+        assert!(!is_critical(
+            &config,
+            Some((2, Severity::Warning)),
+            None,
+            None
+        ));
+        let off = Config {
+            critical_memory: 0,
+            critical_temperature: 0,
+            ..config
+        };
+        assert!(!is_critical(&off, None, Some(100.0), Some(110.0)));
+    }
+}
@@ -0,0 +1,306 @@
+use crate::fl;
+use crate::layout;
+use crate::window::Message;
+use cosmic::iced::Length;
+use cosmic::widget;
+use cosmic::Element;
+use human_bytes::human_bytes;
+#[cfg(feature = "nvidia")]
+use nvml_wrapper::{enums::device::UsedGpuMemory, Nvml};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const SHOWN_PROCESSES: usize = 10;
+const DRM_ROOT: &str = "/sys/class/drm";
+
+#[derive(Clone, Debug)]
+pub struct GpuProcess {
+    pub pid: u32,
+    pub name: String,
+    /// Bytes of video memory held by the process
+    pub vram: u64,
+}
+
+/// Processes using the GPU, from DRM fdinfo (amdgpu and other drivers reporting
+/// `drm-memory-vram`) and, with the `nvidia` feature, NVML.
+pub struct GpuProcesses {
+    processes: Vec<GpuProcess>,
+    #[cfg(feature = "nvidia")]
+    nvml: Option<Nvml>,
+}
+
+impl GpuProcesses {
+    pub fn new() -> Self {
+        Self {
+            processes: Vec::new(),
+            #[cfg(feature = "nvidia")]
+            nvml: Nvml::init().ok(),
+        }
+    }
+
+    pub fn refresh(&mut self) {
+        let mut vram = drm_fdinfo_vram();
+
+        #[cfg(feature = "nvidia")]
+        if let Some(nvml) = &self.nvml {
+            for index in 0..nvml.device_count().unwrap_or_default() {
+                let Ok(device) = nvml.device_by_index(index) else {
+                    continue;
+                };
+                let graphics = device.running_graphics_processes().unwrap_or_default();
+                let compute = device.running_compute_processes().unwrap_or_default();
+                for process in graphics.into_iter().chain(compute) {
+                    let used = match process.used_gpu_memory {
+                        UsedGpuMemory::Used(bytes) => bytes,
+                        UsedGpuMemory::Unavailable => 0,
+                    };
+                    *vram.entry(process.pid).or_default() += used;
+                }
+            }
+        }
+
+        let mut processes = vram
+            .into_iter()
+            .map(|(pid, vram)| GpuProcess {
+                pid,
+                name: fs::read_to_string(format!("/proc/{}/comm", pid))
+                    .map(|name| name.trim().to_string())
+                    .unwrap_or_else(|_| pid.to_string()),
+                vram,
+            })
+            .collect::<Vec<_>>();
+        processes.sort_by(|a, b| b.vram.cmp(&a.vram));
+        processes.truncate(SHOWN_PROCESSES);
+        self.processes = processes;
+    }
+
+    pub fn view(&self) -> Element<Message> {
+        let mut column = widget::column()
+            .spacing(4)
+            .width(Length::Fill)
+            .push(widget::text(fl!("gpu-processes")));
+
+        for process in &self.processes {
+            column = column.push(
+                layout::directional_row(vec![
+                    widget::text(format!("{} ({})", process.name, process.pid))
+                        .width(Length::Fill)
+                        .horizontal_alignment(layout::start_alignment())
+                        .into(),
+                    widget::text(human_bytes(process.vram as f64)).into(),
+                ])
+                .spacing(8),
+            );
+        }
+
+        column.into()
+    }
+}
+
+/// Fan speed and power draw of a GPU.
+#[derive(Clone, Debug)]
+pub struct GpuCooling {
+    /// Card name, e.g. `card0` or `nvidia0`
+    pub name: String,
+    pub fan_percent: Option<u32>,
+    /// Current power draw in watts
+    pub power: Option<f64>,
+    /// Power limit in watts the card is currently held to
+    pub power_limit: Option<f64>,
+    /// Highest power limit the card can be set to
+    pub max_power_limit: Option<f64>,
+}
+
+/// Fans and power limits from the amdgpu hwmon files and, with the `nvidia` feature, NVML.
+pub struct GpuCoolingMonitor {
+    /// hwmon directories by card name
+    amdgpu: Vec<(String, PathBuf)>,
+    devices: Vec<GpuCooling>,
+    #[cfg(feature = "nvidia")]
+    nvml: Option<Nvml>,
+}
+
+impl GpuCoolingMonitor {
+    pub fn new() -> Self {
+        Self {
+            amdgpu: amdgpu_hwmon_dirs(),
+            devices: Vec::new(),
+            #[cfg(feature = "nvidia")]
+            nvml: Nvml::init().ok(),
+        }
+    }
+
+    pub fn refresh(&mut self) {
+        let mut devices = self
+            .amdgpu
+            .iter()
+            .map(|(card, hwmon)| {
+                // Fans are driven by PWM, 255 is full speed unless the driver says otherwise
+                let pwm_max = read_number(&hwmon.join("pwm1_max")).unwrap_or(255.0);
+                // Power is reported in microwatts, newer kernels only have power1_input
+                let power = read_number(&hwmon.join("power1_average"))
+                    .or_else(|| read_number(&hwmon.join("power1_input")));
+                GpuCooling {
+                    name: card.clone(),
+                    fan_percent: read_number(&hwmon.join("pwm1"))
+                        .filter(|_| pwm_max > 0.0)
+                        .map(|pwm| (pwm / pwm_max * 100.0).round() as u32),
+                    power: power.map(|microwatts| microwatts / 1_000_000.0),
+                    power_limit: read_number(&hwmon.join("power1_cap"))
+                        .map(|microwatts| microwatts / 1_000_000.0),
+                    max_power_limit: read_number(&hwmon.join("power1_cap_max"))
+                        .map(|microwatts| microwatts / 1_000_000.0),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        #[cfg(feature = "nvidia")]
+        if let Some(nvml) = &self.nvml {
+            for index in 0..nvml.device_count().unwrap_or_default() {
+                let Ok(device) = nvml.device_by_index(index) else {
+                    continue;
+                };
+                // NVML reports power in milliwatts
+                devices.push(GpuCooling {
+                    name: format!("nvidia{}", index),
+                    fan_percent: device.fan_speed(0).ok(),
+                    power: device.power_usage().ok().map(|mw| mw as f64 / 1000.0),
+                    power_limit: device
+                        .enforced_power_limit()
+                        .ok()
+                        .map(|mw| mw as f64 / 1000.0),
+                    max_power_limit: device
+                        .power_management_limit_constraints()
+                        .ok()
+                        .map(|constraints| constraints.max_limit as f64 / 1000.0),
+                });
+            }
+        }
+
+        devices.retain(|device| device.fan_percent.is_some() || device.power.is_some());
+        self.devices = devices;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.devices.is_empty()
+    }
+
+    pub fn view(&self) -> Element<Message> {
+        let mut column = widget::column().spacing(4).width(Length::Fill);
+
+        for device in &self.devices {
+            let mut cells: Vec<Element<Message>> = vec![widget::text(device.name.as_str())
+                .width(Length::Fill)
+                .horizontal_alignment(layout::start_alignment())
+                .into()];
+            if let Some(percent) = device.fan_percent {
+                cells.push(widget::text(fl!("gpu-fan", percent = percent)).into());
+            }
+            match (device.power, device.power_limit) {
+                (Some(power), Some(limit)) => cells.push(
+                    widget::text(fl!(
+                        "gpu-power-limit",
+                        power = format!("{:.0}", power),
+                        limit = format!("{:.0}", limit)
+                    ))
+                    .into(),
+                ),
+                (Some(power), None) => cells
+                    .push(widget::text(fl!("gpu-power", power = format!("{:.0}", power))).into()),
+                _ => {}
+            }
+            if let Some(max) = device.max_power_limit {
+                cells.push(
+                    widget::text(fl!("gpu-max-power-limit", limit = format!("{:.0}", max))).into(),
+                );
+            }
+            column = column.push(layout::directional_row(cells).spacing(8));
+        }
+
+        column.into()
+    }
+}
+
+fn amdgpu_hwmon_dirs() -> Vec<(String, PathBuf)> {
+    let Ok(entries) = fs::read_dir(DRM_ROOT) else {
+        return Vec::new();
+    };
+    let mut dirs = entries
+        .flatten()
+        .filter_map(|entry| {
+            let card = entry.file_name().to_str()?.to_string();
+            // Skip the connector entries like `card0-DP-1`
+            if !card.starts_with("card") || card.contains('-') {
+                return None;
+            }
+            let hwmon = fs::read_dir(entry.path().join("device/hwmon"))
+                .ok()?
+                .flatten()
+                .next()?;
+            Some((card, hwmon.path()))
+        })
+        .collect::<Vec<_>>();
+    dirs.sort();
+    dirs
+}
+
+fn read_number(path: &Path) -> Option<f64> {
+    fs::read_to_string(path).ok()?.trim().parse().ok()
+}
+
+/// VRAM per process from `/proc/<pid>/fdinfo`, only readable for our own processes
+/// unless running as root.
+fn drm_fdinfo_vram() -> HashMap<u32, u64> {
+    let mut vram = HashMap::new();
+    let Ok(procs) = fs::read_dir("/proc") else {
+        return vram;
+    };
+
+    for entry in procs.flatten() {
+        let Some(pid) = entry.file_name().to_str().and_then(|pid| pid.parse().ok()) else {
+            continue;
+        };
+        let Ok(fds) = fs::read_dir(entry.path().join("fdinfo")) else {
+            continue;
+        };
+        // Every open fd of a DRM client repeats its usage, count each client once
+        let mut clients = HashSet::new();
+        for fd in fds.flatten() {
+            if let Some((client, bytes)) = parse_fdinfo(&fd.path()) {
+                if clients.insert(client) {
+                    *vram.entry(pid).or_default() += bytes;
+                }
+            }
+        }
+    }
+    vram
+}
+
+/// Parses `drm-client-id` and `drm-memory-vram` from a DRM fdinfo file.
+fn parse_fdinfo(path: &Path) -> Option<(u64, u64)> {
+    let fdinfo = fs::read_to_string(path).ok()?;
+    let mut client = None;
+    let mut bytes = None;
+    for line in fdinfo.lines() {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        match key {
+            "drm-client-id" => client = value.trim().parse().ok(),
+            "drm-memory-vram" => {
+                // e.g. "123456 KiB"
+                let mut parts = value.split_whitespace();
+                let amount: u64 = parts.next()?.parse().ok()?;
+                bytes = Some(match parts.next() {
+                    Some("KiB") => amount * 1024,
+                    Some("MiB") => amount * 1024 * 1024,
+                    Some("GiB") => amount * 1024 * 1024 * 1024,
+                    _ => amount,
+                });
+            }
+            _ => {}
+        }
+    }
+    Some((client?, bytes?))
+}
@@ -0,0 +1,246 @@
+use crate::config::HealthCheck;
+use crate::fl;
+use crate::layout;
+use crate::window::Message;
+use cosmic::iced::{Alignment, Color, Length};
+use cosmic::widget;
+use cosmic::Element;
+use std::collections::{BTreeMap, VecDeque};
+use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const CHECK_EVERY: Duration = Duration::from_secs(30);
+const TIMEOUT_SECONDS: &str = "10";
+/// Latencies kept for the sparkline
+const HISTORY: usize = 20;
+const SPARK_LEVELS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// Outcome of one request.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Probe {
+    /// HTTP status and latency in milliseconds
+    Response(u16, f64),
+    /// Connection refused, timed out, bad certificate, …
+    Failed(String),
+}
+
+impl Probe {
+    /// 2xx and 3xx responses count as healthy, redirects are not followed.
+    pub fn is_healthy(&self) -> bool {
+        matches!(self, Probe::Response(status, _) if (200..400).contains(status))
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+struct CheckState {
+    last: Option<Probe>,
+    latencies: VecDeque<f64>,
+}
+
+/// Requests the configured URLs with `curl` on a background thread.
+pub struct HealthMonitor {
+    states: Arc<Mutex<BTreeMap<String, CheckState>>>,
+    last_run: Option<Instant>,
+    /// Set while a round runs, so rounds of slow URLs don't overlap
+    checking: Arc<AtomicBool>,
+}
+
+impl HealthMonitor {
+    pub fn new() -> Self {
+        Self {
+            states: Arc::new(Mutex::new(BTreeMap::new())),
+            last_run: None,
+            checking: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Starts a new round of checks when due and the previous round finished.
+    pub fn poll(&mut self, checks: &[HealthCheck]) {
+        if self
+            .last_run
+            .is_some_and(|time| time.elapsed() < CHECK_EVERY)
+        {
+            return;
+        }
+        if self.checking.swap(true, Ordering::AcqRel) {
+            return;
+        }
+        self.last_run = Some(Instant::now());
+        let checks = checks.to_vec();
+        let states = self.states.clone();
+        let checking = self.checking.clone();
+        thread::spawn(move || {
+            // In parallel, so a round takes at most one timeout
+            let results = thread::scope(|scope| {
+                checks
+                    .iter()
+                    .map(|check| scope.spawn(move || (check.url.clone(), probe(&check.url))))
+                    .collect::<Vec<_>>()
+                    .into_iter()
+                    .filter_map(|handle| handle.join().ok())
+                    .collect::<Vec<_>>()
+            });
+            checking.store(false, Ordering::Release);
+            let Ok(mut states) = states.lock() else {
+                return;
+            };
+            // Checks removed from the config go away
+            states.retain(|url, _| checks.iter().any(|check| &check.url == url));
+            for (url, result) in results {
+                let state = states.entry(url).or_default();
+                if let Probe::Response(_, latency) = result {
+                    state.latencies.push_back(latency);
+                    if state.latencies.len() > HISTORY {
+                        state.latencies.pop_front();
+                    }
+                }
+                state.last = Some(result);
+            }
+        });
+    }
+
+    /// Whether any check failed its latest probe, for the panel.
+    pub fn any_down(&self) -> bool {
+        self.states.lock().is_ok_and(|states| {
+            states
+                .values()
+                .any(|state| state.last.as_ref().is_some_and(|last| !last.is_healthy()))
+        })
+    }
+
+    pub fn view<'a>(
+        &self,
+        checks: &'a [HealthCheck],
+        theme: &cosmic::cosmic_theme::Theme,
+    ) -> Element<'a, Message> {
+        let states = self
+            .states
+            .lock()
+            .map(|states| states.clone())
+            .unwrap_or_default();
+        let mut column = widget::column()
+            .spacing(4)
+            .width(Length::Fill)
+            .push(widget::text(fl!("health-checks")));
+
+        for check in checks {
+            let state = states.get(&check.url).cloned().unwrap_or_default();
+            let (dot, status) = match &state.last {
+                None => (theme.palette.neutral_6, fl!("loading")),
+                Some(probe @ Probe::Response(status, latency)) => (
+                    if probe.is_healthy() {
+                        theme.success_color()
+                    } else {
+                        theme.destructive_color()
+                    },
+                    format!("{} · {:.0} ms", status, latency),
+                ),
+                Some(Probe::Failed(err)) => (theme.destructive_color(), err.clone()),
+            };
+            let label = if check.label.is_empty() {
+                check.url.as_str()
+            } else {
+                check.label.as_str()
+            };
+
+            column = column.push(
+                layout::directional_row(vec![
+                    widget::text("●")
+                        .style(cosmic::theme::Text::Color(Color::from(dot)))
+                        .into(),
+                    widget::text(label)
+                        .width(Length::Fill)
+                        .horizontal_alignment(layout::start_alignment())
+                        .into(),
+                    widget::text(sparkline(&state.latencies)).into(),
+                    widget::text(status).into(),
+                ])
+                .spacing(8)
+                .align_items(Alignment::Center),
+            );
+        }
+
+        column.into()
+    }
+}
+
+/// Latencies as block characters, scaled to the slowest one.
+fn sparkline(values: &VecDeque<f64>) -> String {
+    let max = values.iter().copied().fold(0.0, f64::max);
+    values
+        .iter()
+        .map(|value| {
+            if max <= 0.0 {
+                return SPARK_LEVELS[0];
+            }
+            let level = (value / max * (SPARK_LEVELS.len() - 1) as f64).round() as usize;
+            SPARK_LEVELS[level.min(SPARK_LEVELS.len() - 1)]
+        })
+        .collect()
+}
+
+fn probe(url: &str) -> Probe {
+    let output = Command::new("curl")
+        .args([
+            "--silent",
+            "--show-error",
+            "--output",
+            "/dev/null",
+            "--max-time",
+            TIMEOUT_SECONDS,
+            "--write-out",
+            "%{http_code} %{time_total}",
+            // A URL starting with a dash is not an option
+            "--",
+            url,
+        ])
+        .output();
+    match output {
+        Ok(output) if output.status.success() => {
+            parse_write_out(&String::from_utf8_lossy(&output.stdout))
+                .unwrap_or_else(|| Probe::Failed(fl!("health-unreadable")))
+        }
+        Ok(output) => Probe::Failed(
+            // e.g. `curl: (7) Failed to connect to localhost port 8080`
+            String::from_utf8_lossy(&output.stderr)
+                .trim()
+                .trim_start_matches("curl: ")
+                .to_string(),
+        ),
+        Err(err) => Probe::Failed(format!("curl: {}", err)),
+    }
+}
+
+/// Parses `200 0.123456`, the status and total time in seconds.
+fn parse_write_out(output: &str) -> Option<Probe> {
+    let (status, seconds) = output.trim().split_once(' ')?;
+    Some(Probe::Response(
+        status.parse().ok()?,
+        seconds.parse::<f64>().ok()? * 1000.0,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_status_and_latency() {
+        assert_eq!(
+            parse_write_out("200 0.125\n"),
+            Some(Probe::Response(200, 125.0))
+        );
+        assert!(parse_write_out("200 0.125").unwrap().is_healthy());
+        assert!(!parse_write_out("503 0.010").unwrap().is_healthy());
+        assert_eq!(parse_write_out(""), None);
+    }
+
+    #[test]
+    fn sparkline_scales_to_the_slowest() {
+        assert_eq!(sparkline(&VecDeque::from([10.0, 80.0, 40.0])), "▂█▅");
+        assert_eq!(sparkline(&VecDeque::new()), "");
+    }
+}
@@ -0,0 +1,30 @@
+use crate::metrics::Scale;
+use crate::state;
+use serde::{Deserialize, Serialize};
+use std::io;
+
+const HISTORY_FILE: &str = "history.bin";
+
+/// Recent samples of every chart, persisted so charts start pre-filled after a restart.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct History {
+    pub series: Vec<SeriesHistory>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SeriesHistory {
+    pub source: String,
+    pub series: String,
+    pub title: String,
+    pub scale: Scale,
+    /// `(unix timestamp in milliseconds, value)`, newest first
+    pub points: Vec<(i64, f64)>,
+}
+
+pub fn load() -> Option<History> {
+    state::load(HISTORY_FILE)
+}
+
+pub fn save(history: &History) -> io::Result<()> {
+    state::save(HISTORY_FILE, history)
+}
@@ -0,0 +1,304 @@
+use crate::cpufreq;
+use crate::fl;
+use crate::layout;
+use crate::window::Message;
+use cosmic::iced::{Alignment, Length};
+use cosmic::iced_widget::ProgressBar;
+use cosmic::widget;
+use cosmic::Element;
+use std::collections::HashMap;
+use std::fs;
+use std::time::Instant;
+
+const INTERRUPTS: &str = "/proc/interrupts";
+const SOFTIRQS: &str = "/proc/softirqs";
+const SHOWN_SOURCES: usize = 5;
+const BAR_WIDTH: f32 = 120.0;
+
+/// Rates of one interrupt line or softirq, per CPU.
+#[derive(Clone, Debug, PartialEq)]
+struct SourceRate {
+    name: String,
+    per_cpu: Vec<f64>,
+}
+
+impl SourceRate {
+    fn total(&self) -> f64 {
+        self.per_cpu.iter().sum()
+    }
+
+    /// The CPU handling most of it and its share.
+    fn busiest_cpu(&self) -> Option<(usize, f64)> {
+        let total = self.total();
+        let (cpu, rate) = self
+            .per_cpu
+            .iter()
+            .enumerate()
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))?;
+        (total > 0.0).then(|| (cpu, rate / total * 100.0))
+    }
+}
+
+/// Which cores handle the interrupts and softirqs, from `/proc/interrupts` and
+/// `/proc/softirqs` deltas. A network card whose queues all land on one core shows
+/// up as a single busy row.
+#[derive(Default)]
+pub struct InterruptMonitor {
+    /// Cumulative counts per CPU at the previous refresh, keyed by `irq:` or `softirq:`
+    previous: HashMap<String, Vec<u64>>,
+    last_refresh: Option<Instant>,
+    /// Interrupts and softirqs per second by column, offline cores have none
+    cpus: Vec<(f64, f64)>,
+    /// Core number of each column
+    cpu_ids: Vec<usize>,
+    offline: Vec<usize>,
+    sources: Vec<SourceRate>,
+}
+
+impl InterruptMonitor {
+    pub fn refresh(&mut self) {
+        let now = Instant::now();
+        let elapsed = self
+            .last_refresh
+            .map(|time| now.duration_since(time).as_secs_f64())
+            .unwrap_or_default();
+        self.last_refresh = Some(now);
+
+        let read = |path: &str, prefix: &str| {
+            fs::read_to_string(path)
+                .map(|text| parse_counts(&text))
+                .unwrap_or_default()
+                .into_iter()
+                .map(move |(name, counts)| (format!("{}{}", prefix, name), counts))
+        };
+        self.cpu_ids = fs::read_to_string(INTERRUPTS)
+            .ok()
+            .and_then(|text| text.lines().next().map(cpu_ids))
+            .unwrap_or_default();
+        self.offline = cpufreq::offline_cores();
+        let current = read(INTERRUPTS, "irq:")
+            .chain(read(SOFTIRQS, "softirq:"))
+            .collect::<HashMap<_, _>>();
+
+        let mut cpus: Vec<(f64, f64)> = Vec::new();
+        let mut sources = Vec::new();
+        if elapsed > 0.0 {
+            for (key, counts) in &current {
+                let Some(previous) = self.previous.get(key) else {
+                    continue;
+                };
+                let per_cpu = counts
+                    .iter()
+                    .zip(previous)
+                    .map(|(now, then)| now.saturating_sub(*then) as f64 / elapsed)
+                    .collect::<Vec<_>>();
+                if cpus.len() < per_cpu.len() {
+                    cpus.resize(per_cpu.len(), (0.0, 0.0));
+                }
+                let softirq = key.starts_with("softirq:");
+                for (cpu, rate) in per_cpu.iter().enumerate() {
+                    if softirq {
+                        cpus[cpu].1 += rate;
+                    } else {
+                        cpus[cpu].0 += rate;
+                    }
+                }
+                sources.push(SourceRate {
+                    name: key
+                        .split_once(':')
+                        .map_or(key.as_str(), |(_, name)| name)
+                        .to_string(),
+                    per_cpu,
+                });
+            }
+        }
+        sources.sort_by(|a, b| b.total().total_cmp(&a.total()));
+        sources.truncate(SHOWN_SOURCES);
+
+        self.cpus = cpus;
+        self.sources = sources;
+        self.previous = current;
+    }
+
+    pub fn view(&self) -> Element<Message> {
+        let mut column = widget::column()
+            .spacing(4)
+            .width(Length::Fill)
+            .push(widget::text(fl!("interrupts-per-cpu")));
+        if self.cpus.is_empty() {
+            column = column.push(widget::text(fl!("loading")));
+        }
+
+        let busiest = self
+            .cpus
+            .iter()
+            .map(|(irqs, softirqs)| irqs + softirqs)
+            .fold(0.0, f64::max);
+        for (cpu, (irqs, softirqs)) in self.cpus.iter().enumerate() {
+            let share = if busiest > 0.0 {
+                (irqs + softirqs) / busiest
+            } else {
+                0.0
+            };
+            column = column.push(
+                layout::directional_row(vec![
+                    widget::text(format!("CPU{}", self.cpu_id(cpu)))
+                        .width(Length::Fill)
+                        .horizontal_alignment(layout::start_alignment())
+                        .into(),
+                    ProgressBar::new(0.0..=1.0, share as f32)
+                        .width(Length::Fixed(BAR_WIDTH))
+                        .height(Length::Fixed(8.0))
+                        .into(),
+                    widget::text(fl!(
+                        "interrupt-rates",
+                        irqs = format!("{:.0}", irqs),
+                        softirqs = format!("{:.0}", softirqs)
+                    ))
+                    .into(),
+                ])
+                .spacing(8)
+                .align_items(Alignment::Center),
+            );
+        }
+
+        if !self.offline.is_empty() {
+            let cores = self
+                .offline
+                .iter()
+                .map(|core| format!("CPU{}", core))
+                .collect::<Vec<_>>()
+                .join(", ");
+            column = column.push(widget::text(fl!("cpus-offline", cores = cores)));
+        }
+
+        for source in &self.sources {
+            let mut cells: Vec<Element<Message>> = vec![
+                widget::text(source.name.as_str())
+                    .width(Length::Fill)
+                    .horizontal_alignment(layout::start_alignment())
+                    .into(),
+                widget::text(format!("{:.0}/s", source.total())).into(),
+            ];
+            if let Some((cpu, percent)) = source.busiest_cpu() {
+                cells.push(
+                    widget::text(fl!(
+                        "interrupt-busiest-cpu",
+                        cpu = self.cpu_id(cpu),
+                        percent = format!("{:.0}", percent)
+                    ))
+                    .into(),
+                );
+            }
+            column = column.push(layout::directional_row(cells).spacing(8));
+        }
+
+        column.into()
+    }
+
+    fn cpu_id(&self, column: usize) -> usize {
+        self.cpu_ids.get(column).copied().unwrap_or(column)
+    }
+}
+
+/// Numbers of the online cores from the header, e.g. `CPU0 CPU1 CPU3` while the third
+/// core is offline.
+fn cpu_ids(header: &str) -> Vec<usize> {
+    header
+        .split_whitespace()
+        .filter_map(|cpu| cpu.strip_prefix("CPU")?.parse().ok())
+        .collect()
+}
+
+/// Per-CPU counts of every row of `/proc/interrupts` or `/proc/softirqs`, named after
+/// the device for interrupt lines (e.g. `nvme0q1`) and the kind for the rest (`NET_RX`).
+fn parse_counts(text: &str) -> Vec<(String, Vec<u64>)> {
+    let mut lines = text.lines();
+    let Some(header) = lines.next() else {
+        return Vec::new();
+    };
+    let cpus = header.split_whitespace().count();
+    lines
+        .filter_map(|line| {
+            let (id, rest) = line.split_once(':')?;
+            let mut fields = rest.split_whitespace().peekable();
+            let mut counts = Vec::with_capacity(cpus);
+            while counts.len() < cpus {
+                match fields.peek().and_then(|field| field.parse::<u64>().ok()) {
+                    Some(count) => {
+                        counts.push(count);
+                        fields.next();
+                    }
+                    None => break,
+                }
+            }
+            // `ERR` and `MIS` only have a total, not per CPU
+            if counts.len() != cpus {
+                return None;
+            }
+            let id = id.trim();
+            let name = match fields.last() {
+                Some(device) if id.parse::<u32>().is_ok() => format!("{} {}", id, device),
+                _ => id.to_string(),
+            };
+            Some((name, counts))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_interrupt_lines() {
+        let interrupts = "\
+           CPU0       CPU1
+  0:         26          0   IO-APIC   2-edge      timer
+ 128:     91234          7   PCI-MSI 524288-edge      enp3s0-rx-0
+NMI:          3          4   Non-maskable interrupts
+ERR:          0
+";
+        assert_eq!(
+            parse_counts(interrupts),
+            vec![
+                ("0 timer".to_string(), vec![26, 0]),
+                ("128 enp3s0-rx-0".to_string(), vec![91234, 7]),
+                ("NMI".to_string(), vec![3, 4]),
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_softirqs() {
+        let softirqs = "\
+                    CPU0       CPU1
+          HI:          1          0
+      NET_RX:      52012        310
+";
+        assert_eq!(
+            parse_counts(softirqs),
+            vec![
+                ("HI".to_string(), vec![1, 0]),
+                ("NET_RX".to_string(), vec![52012, 310]),
+            ]
+        );
+    }
+
+    #[test]
+    fn numbers_columns_after_online_cores() {
+        assert_eq!(
+            cpu_ids("           CPU0       CPU1       CPU3"),
+            vec![0, 1, 3]
+        );
+    }
+
+    #[test]
+    fn finds_the_busiest_cpu() {
+        let source = SourceRate {
+            name: "NET_RX".to_string(),
+            per_cpu: vec![10.0, 90.0],
+        };
+        assert_eq!(source.busiest_cpu(), Some((1, 90.0)));
+    }
+}
@@ -0,0 +1,149 @@
+//! Flows the metric views of the popup into rows, as many side by side as the width fits.
+
+use crate::config::ChartDensity;
+use crate::localize;
+use crate::window::Message;
+use cosmic::iced::alignment::Horizontal;
+use cosmic::iced::{widget::Row, Alignment, Length};
+use cosmic::iced_widget::Column;
+use cosmic::Element;
+use std::ops::Range;
+
+/// Space a view would like to take up, in logical pixels.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PreferredSize {
+    /// Narrowest width the view is still readable at, it grows to fill its row
+    pub width: f32,
+    pub height: f32,
+}
+
+/// Settings shared by every view.
+#[derive(Clone, Copy, Debug)]
+pub struct ViewOptions {
+    pub density: ChartDensity,
+    /// Bars and values instead of canvases
+    pub simple: bool,
+    /// Text describing the data under every chart
+    pub text_summary: bool,
+}
+
+/// Something shown in the metrics area of the popup, e.g. a chart.
+pub trait MetricView {
+    /// Stable name of the view, details for it are attached by this name
+    fn name(&self) -> &str;
+
+    fn preferred_size(&self, options: &ViewOptions) -> PreferredSize;
+
+    fn view(&self, options: &ViewOptions) -> Element<Message>;
+}
+
+/// Splits views of the given widths into rows fitting `available`, keeping their order.
+/// A view wider than the space gets a row of its own.
+pub fn rows(widths: &[f32], available: f32, spacing: f32) -> Vec<Range<usize>> {
+    let mut rows = Vec::new();
+    let mut start = 0;
+    let mut used = 0.0;
+    for (index, width) in widths.iter().enumerate() {
+        if index > start && used + spacing + width > available {
+            rows.push(start..index);
+            start = index;
+            used = 0.0;
+        }
+        used += if index > start {
+            spacing + width
+        } else {
+            *width
+        };
+    }
+    if start < widths.len() {
+        rows.push(start..widths.len());
+    }
+    rows
+}
+
+/// A row of `children` in reading order, starting on the right for right-to-left
+/// languages. iced always lays rows out from the left.
+pub fn directional_row<'a>(mut children: Vec<Element<'a, Message>>) -> Row<'a, Message> {
+    if localize::is_rtl() {
+        children.reverse();
+    }
+    Row::with_children(children)
+}
+
+/// Alignment of text at the start of a line, the right for right-to-left languages.
+pub fn start_alignment() -> Horizontal {
+    if localize::is_rtl() {
+        Horizontal::Right
+    } else {
+        Horizontal::Left
+    }
+}
+
+/// Lays out the elements in rows of `available` width, each sized by its preferred width.
+pub fn flow<'a>(
+    items: Vec<(PreferredSize, Element<'a, Message>)>,
+    available: f32,
+    spacing: f32,
+    padding: u16,
+) -> Element<'a, Message> {
+    let widths = items.iter().map(|(size, _)| size.width).collect::<Vec<_>>();
+    let mut items = items.into_iter();
+    let rows = rows(&widths, available, spacing)
+        .into_iter()
+        .map(|row| {
+            let cells = items
+                .by_ref()
+                .take(row.len())
+                .map(|(size, element)| {
+                    // Views grow in proportion to what they asked for
+                    Column::with_children(vec![element])
+                        .width(Length::FillPortion(size.width.max(1.0) as u16))
+                        .align_items(Alignment::Center)
+                        .into()
+                })
+                .collect::<Vec<Element<Message>>>();
+            directional_row(cells)
+                .spacing(spacing)
+                .padding(padding)
+                .width(Length::Fill)
+                .height(Length::Shrink)
+                .align_items(Alignment::Center)
+                .into()
+        })
+        .collect::<Vec<Element<Message>>>();
+
+    // Scrolled by the popup together with the other sections
+    Column::with_children(rows)
+        .width(Length::Fill)
+        .height(Length::Shrink)
+        .align_items(Alignment::Center)
+        .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rows_fit_as_many_views_as_the_width_allows() {
+        assert_eq!(
+            rows(&[300.0, 300.0, 300.0], 475.0, 8.0),
+            vec![0..1, 1..2, 2..3]
+        );
+        assert_eq!(rows(&[300.0, 300.0, 300.0], 1000.0, 8.0), vec![0..3]);
+        assert_eq!(rows(&[300.0, 300.0, 300.0], 700.0, 8.0), vec![0..2, 2..3]);
+        assert_eq!(
+            rows(&[100.0, 100.0, 100.0, 300.0], 320.0, 10.0),
+            vec![0..3, 3..4]
+        );
+    }
+
+    #[test]
+    fn view_wider_than_the_space_gets_its_own_row() {
+        assert_eq!(
+            rows(&[100.0, 600.0, 100.0], 475.0, 8.0),
+            vec![0..1, 1..2, 2..3]
+        );
+        assert!(rows(&[], 475.0, 8.0).is_empty());
+    }
+}
@@ -0,0 +1,62 @@
+//! System status applet for the COSMIC panel. The collectors and charts live in this
+//! library so the binary, the benchmarks and the `--dump` mode share them.
+
+mod actions;
+mod alerts;
+mod asahi;
+mod audio;
+mod badge;
+mod boot;
+mod budget;
+mod busy;
+mod cgroups;
+pub mod chart;
+mod clock;
+pub mod config;
+mod connections;
+mod containers;
+mod cpufreq;
+mod daily;
+mod detect;
+mod disks;
+pub mod dump;
+mod events;
+mod export;
+#[cfg(feature = "prometheus")]
+mod exporter;
+mod filesystems;
+mod flash;
+mod gpu;
+mod health;
+mod history;
+mod interrupts;
+mod layout;
+pub mod localize;
+pub mod logging;
+mod mdstat;
+pub mod metrics;
+mod mqtt;
+mod network;
+mod peripherals;
+mod pods;
+#[cfg(feature = "pools")]
+mod pools;
+mod power;
+mod processes;
+mod report;
+mod sensors;
+mod smart;
+mod state;
+#[cfg(feature = "sqlite")]
+mod store;
+mod swap;
+mod throttle;
+#[cfg(feature = "tray")]
+pub mod tray;
+mod units;
+mod updates;
+mod virt;
+#[cfg(feature = "libvirt")]
+mod vms;
+mod weekly;
+pub mod window;
@@ -1,5 +1,6 @@
 use i18n_embed::{
     fluent::{fluent_language_loader, FluentLanguageLoader},
+    unic_langid::CharacterDirection,
     DefaultLocalizer, LanguageLoader, Localizer,
 };
 use once_cell::sync::Lazy;
@@ -40,6 +41,11 @@ pub fn localize() {
     let requested_languages = i18n_embed::DesktopLanguageRequester::requested_languages();
 
     if let Err(error) = localizer.select(&requested_languages) {
-        eprintln!("Error while loading language for App List {}", error);
+        tracing::error!("Error while loading language for App List {}", error);
     }
 }
+
+/// Whether the selected language is written right to left, like Arabic or Hebrew.
+pub fn is_rtl() -> bool {
+    LANGUAGE_LOADER.current_language().character_direction() == CharacterDirection::RTL
+}
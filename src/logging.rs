@@ -0,0 +1,28 @@
+use std::fs::OpenOptions;
+use std::sync::Mutex;
+use tracing_subscriber::{fmt, prelude::*, EnvFilter};
+
+// Path of an optional file receiving a copy of the logs, handy when attaching logs to issues
+const LOG_FILE_ENV: &str = "COSMIC_SYS_STATUS_LOG_FILE";
+const DEFAULT_FILTER: &str = "warn,cosmic_applet_sys_status=info";
+
+pub fn init() {
+    let filter =
+        EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(DEFAULT_FILTER));
+
+    let file_layer = std::env::var_os(LOG_FILE_ENV).and_then(|path| {
+        match OpenOptions::new().create(true).append(true).open(&path) {
+            Ok(file) => Some(fmt::layer().with_ansi(false).with_writer(Mutex::new(file))),
+            Err(err) => {
+                eprintln!("failed to open log file {:?}: {}", path, err);
+                None
+            }
+        }
+    });
+
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(fmt::layer())
+        .with(file_layer)
+        .init();
+}
@@ -1,39 +1,59 @@
-use crate::window::Window;
-
-use config::{Config, CONFIG_VERSION};
 use cosmic::cosmic_config;
 use cosmic::cosmic_config::CosmicConfigEntry;
-mod config;
-use window::Flags;
-
-mod localize;
-mod window;
-mod chart;
+use cosmic_applet_sys_status::config::{Config, CONFIG_VERSION};
+use cosmic_applet_sys_status::window::{self, Flags, Window};
+use cosmic_applet_sys_status::{dump, localize, logging};
 
 fn main() -> cosmic::iced::Result {
+    logging::init();
     localize::localize();
 
-    let (config_handler, config) = match cosmic_config::Config::new(window::ID, CONFIG_VERSION)
-    {
+    let (config_handler, config) = match cosmic_config::Config::new(window::ID, CONFIG_VERSION) {
         Ok(config_handler) => {
             let config = match Config::get_entry(&config_handler) {
                 Ok(ok) => ok,
                 Err((errs, config)) => {
-                    eprintln!("errors loading config: {:?}", errs);
+                    tracing::error!("errors loading config: {:?}", errs);
                     config
                 }
             };
             (Some(config_handler), config)
         }
         Err(err) => {
-            eprintln!("failed to create config handler: {}", err);
+            tracing::error!("failed to create config handler: {}", err);
             (None, Config::default())
         }
     };
 
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let has_flag = |flag: &str| args.iter().any(|arg| arg == flag);
+    if has_flag("--dump") {
+        if let Err(err) = dump::run(&config, has_flag("--follow")) {
+            // A closed pipe, e.g. from `| head`, is a normal way to stop following
+            if err.kind() != std::io::ErrorKind::BrokenPipe {
+                tracing::error!("failed to dump metrics: {}", err);
+                std::process::exit(1);
+            }
+        }
+        return Ok(());
+    }
+    #[cfg(feature = "tray")]
+    if has_flag("--tray") {
+        cosmic_applet_sys_status::tray::run(&config);
+        return Ok(());
+    }
+
+    let windowed = has_flag("--windowed");
     let flags = Flags {
         config,
         config_handler,
+        windowed,
     };
-    cosmic::applet::run::<Window>(true, flags)
+
+    if windowed {
+        let settings = cosmic::app::Settings::default().size(cosmic::iced::Size::new(475.0, 800.0));
+        cosmic::app::run::<Window>(settings, flags)
+    } else {
+        cosmic::applet::run::<Window>(true, flags)
+    }
 }
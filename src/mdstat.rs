@@ -0,0 +1,186 @@
+use crate::alerts::{Alert, Severity};
+use crate::fl;
+use crate::layout;
+use crate::window::Message;
+use cosmic::iced::Length;
+use cosmic::widget;
+use cosmic::Element;
+use std::fs;
+
+const MDSTAT: &str = "/proc/mdstat";
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ArrayState {
+    Clean,
+    Degraded,
+    /// Resync, recovery, reshape or check in progress
+    Syncing {
+        action: String,
+        progress: f64,
+    },
+}
+
+#[derive(Clone, Debug)]
+pub struct ArrayStatus {
+    pub name: String,
+    pub level: String,
+    /// Member status as printed by the kernel, e.g. `[UU_]`
+    pub members: String,
+    pub state: ArrayState,
+}
+
+/// Software RAID arrays from `/proc/mdstat`.
+#[derive(Default)]
+pub struct RaidMonitor {
+    arrays: Vec<ArrayStatus>,
+}
+
+impl RaidMonitor {
+    /// Re-reads the array states and returns alerts for degraded arrays.
+    pub fn refresh(&mut self) -> Vec<Alert> {
+        self.arrays = fs::read_to_string(MDSTAT)
+            .map(|mdstat| parse(&mdstat))
+            .unwrap_or_default();
+
+        self.arrays
+            .iter()
+            .filter(|array| array.state == ArrayState::Degraded)
+            .map(|array| Alert {
+                key: format!("raid-{}", array.name),
+                summary: fl!("raid-degraded-alert", array = array.name.as_str()),
+                body: format!("{} {}", array.level, array.members),
+                severity: Severity::Critical,
+            })
+            .collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.arrays.is_empty()
+    }
+
+    pub fn view(&self) -> Element<Message> {
+        let mut column = widget::column()
+            .spacing(4)
+            .width(Length::Fill)
+            .push(widget::text(fl!("raid-arrays")));
+
+        for array in &self.arrays {
+            let state = match &array.state {
+                ArrayState::Clean => fl!("raid-clean"),
+                ArrayState::Degraded => fl!("raid-degraded"),
+                ArrayState::Syncing { action, progress } => fl!(
+                    "raid-syncing",
+                    action = action.as_str(),
+                    progress = format!("{:.1}", progress)
+                ),
+            };
+            column = column.push(
+                layout::directional_row(vec![
+                    widget::text(format!(
+                        "{} ({} {})",
+                        array.name, array.level, array.members
+                    ))
+                    .width(Length::Fill)
+                    .horizontal_alignment(layout::start_alignment())
+                    .into(),
+                    widget::text(state).into(),
+                ])
+                .spacing(8),
+            );
+        }
+
+        column.into()
+    }
+}
+
+fn parse(mdstat: &str) -> Vec<ArrayStatus> {
+    let mut arrays: Vec<ArrayStatus> = Vec::new();
+    for line in mdstat.lines() {
+        if let Some((name, rest)) = line.split_once(" : ") {
+            if !name.starts_with("md") {
+                continue;
+            }
+            // e.g. "active raid1 sdb1[1] sda1[0]"
+            let level = rest
+                .split_whitespace()
+                .find(|word| word.starts_with("raid") || *word == "linear")
+                .unwrap_or_default();
+            arrays.push(ArrayStatus {
+                name: name.trim().to_string(),
+                level: level.to_string(),
+                members: String::new(),
+                state: ArrayState::Clean,
+            });
+            continue;
+        }
+
+        let Some(array) = arrays.last_mut() else {
+            continue;
+        };
+        let line = line.trim();
+        if line.contains("blocks") {
+            // e.g. "976630464 blocks super 1.2 [2/1] [U_]"
+            if let Some(members) = line.split_whitespace().last() {
+                if members.starts_with('[') {
+                    array.members = members.to_string();
+                    if members.contains('_') {
+                        array.state = ArrayState::Degraded;
+                    }
+                }
+            }
+        } else if let Some((action, progress)) = sync_progress(line) {
+            array.state = ArrayState::Syncing { action, progress };
+        }
+    }
+    arrays
+}
+
+/// Parses lines like `[==>....]  recovery = 12.6% (123/456) finish=...`.
+fn sync_progress(line: &str) -> Option<(String, f64)> {
+    let (before, after) = line.split_once('=')?;
+    let action = before.split_whitespace().last()?;
+    if !["resync", "recovery", "reshape", "check"].contains(&action) {
+        return None;
+    }
+    let progress = after.trim().split('%').next()?.trim().parse().ok()?;
+    Some((action.to_string(), progress))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MDSTAT_SAMPLE: &str = "Personalities : [raid1] [raid6]
+md1 : active raid1 sdb2[1] sda2[0]
+      976630464 blocks super 1.2 [2/1] [U_]
+      bitmap: 2/8 pages [8KB], 65536KB chunk
+
+md0 : active raid1 sdb1[1] sda1[0]
+      1046528 blocks super 1.2 [2/2] [UU]
+      [=====>...............]  resync = 28.5% (298496/1046528) finish=0.3min speed=37312K/sec
+
+md2 : active raid6 sdc[0] sdd[1] sde[2] sdf[3]
+      1953260544 blocks super 1.2 level 6, 512k chunk, algorithm 2 [4/4] [UUUU]
+
+unused devices: <none>
+";
+
+    #[test]
+    fn parses_array_states() {
+        let arrays = parse(MDSTAT_SAMPLE);
+
+        assert_eq!(arrays.len(), 3);
+        assert_eq!(arrays[0].name, "md1");
+        assert_eq!(arrays[0].members, "[U_]");
+        assert_eq!(arrays[0].state, ArrayState::Degraded);
+        assert_eq!(
+            arrays[1].state,
+            ArrayState::Syncing {
+                action: "resync".to_string(),
+                progress: 28.5
+            }
+        );
+        assert_eq!(arrays[2].level, "raid6");
+        assert_eq!(arrays[2].state, ArrayState::Clean);
+    }
+}
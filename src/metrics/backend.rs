@@ -0,0 +1,131 @@
+use std::time::Duration;
+use sysinfo::{Networks, RefreshKind, System};
+
+/// The system state metric sources read, behind a trait so sampling can run against
+/// fixed values in tests.
+pub trait SystemBackend {
+    /// Refreshes the parts of the system state selected by `kind`.
+    fn refresh(&mut self, kind: RefreshKind);
+
+    /// Time needed between the first two refreshes before usage values are meaningful.
+    fn warm_up(&self) -> Duration {
+        Duration::ZERO
+    }
+
+    /// Usage of all cores combined, in percent.
+    fn cpu_usage(&self) -> f64;
+
+    /// Bytes of RAM in use.
+    fn used_memory(&self) -> u64;
+
+    /// Bytes of RAM installed, 0 when unknown.
+    fn total_memory(&self) -> u64;
+
+    /// Resident memory in bytes, summed over all processes called `name`.
+    fn process_memory(&self, name: &str) -> u64;
+
+    /// Bytes received and sent since boot, summed over every interface but loopback.
+    fn network_totals(&self) -> (u64, u64);
+}
+
+/// The real system, read through sysinfo.
+pub struct SysinfoBackend {
+    sys: System,
+}
+
+impl SysinfoBackend {
+    pub fn new(kind: RefreshKind) -> Self {
+        Self {
+            sys: System::new_with_specifics(kind),
+        }
+    }
+}
+
+impl SystemBackend for SysinfoBackend {
+    fn refresh(&mut self, kind: RefreshKind) {
+        self.sys.refresh_specifics(kind);
+    }
+
+    // CPU usage is computed between two refreshes, the first one reads 0
+    fn warm_up(&self) -> Duration {
+        sysinfo::MINIMUM_CPU_UPDATE_INTERVAL
+    }
+
+    fn cpu_usage(&self) -> f64 {
+        self.sys.global_cpu_info().cpu_usage() as f64
+    }
+
+    fn used_memory(&self) -> u64 {
+        self.sys.used_memory()
+    }
+
+    fn total_memory(&self) -> u64 {
+        self.sys.total_memory()
+    }
+
+    fn process_memory(&self, name: &str) -> u64 {
+        self.sys
+            .processes()
+            .values()
+            .filter(|process| process.name() == name)
+            .map(|process| process.memory())
+            .sum()
+    }
+
+    // Listed anew on every call, so interfaces that came up since are included
+    fn network_totals(&self) -> (u64, u64) {
+        Networks::new_with_refreshed_list()
+            .iter()
+            .filter(|(name, _)| name.as_str() != "lo")
+            .fold((0, 0), |(received, transmitted), (_, data)| {
+                (
+                    received + data.total_received(),
+                    transmitted + data.total_transmitted(),
+                )
+            })
+    }
+}
+
+/// Deterministic backend returning whatever the test set.
+#[cfg(test)]
+#[derive(Default)]
+pub struct FakeBackend {
+    pub cpu_usage: f64,
+    pub used_memory: u64,
+    pub total_memory: u64,
+    pub processes: std::collections::HashMap<String, u64>,
+    pub refreshes: usize,
+    pub warm_up: Duration,
+    pub network_totals: (u64, u64),
+}
+
+#[cfg(test)]
+impl SystemBackend for FakeBackend {
+    fn refresh(&mut self, _kind: RefreshKind) {
+        self.refreshes += 1;
+    }
+
+    fn warm_up(&self) -> Duration {
+        self.warm_up
+    }
+
+    fn cpu_usage(&self) -> f64 {
+        self.cpu_usage
+    }
+
+    fn used_memory(&self) -> u64 {
+        self.used_memory
+    }
+
+    fn total_memory(&self) -> u64 {
+        self.total_memory
+    }
+
+    fn process_memory(&self, name: &str) -> u64 {
+        self.processes.get(name).copied().unwrap_or_default()
+    }
+
+    fn network_totals(&self) -> (u64, u64) {
+        self.network_totals
+    }
+}
@@ -0,0 +1,36 @@
+use super::{MetricSource, SeriesId, SystemBackend};
+use crate::fl;
+use sysinfo::{CpuRefreshKind, RefreshKind};
+
+pub struct CpuSource {
+    /// Titled vCPU inside a virtual machine, where usage is of the cores the host lends
+    virtual_cpus: bool,
+}
+
+impl CpuSource {
+    pub fn new(virtual_cpus: bool) -> Self {
+        Self { virtual_cpus }
+    }
+}
+
+impl MetricSource for CpuSource {
+    fn name(&self) -> &str {
+        "CPU"
+    }
+
+    fn title(&self) -> String {
+        if self.virtual_cpus {
+            fl!("vcpu")
+        } else {
+            fl!("cpu")
+        }
+    }
+
+    fn refresh(&self, kind: RefreshKind) -> RefreshKind {
+        kind.with_cpu(CpuRefreshKind::new().with_cpu_usage())
+    }
+
+    fn sample(&mut self, sys: &dyn SystemBackend) -> Vec<(SeriesId, f64)> {
+        vec![(SeriesId::new("cpu"), sys.cpu_usage())]
+    }
+}
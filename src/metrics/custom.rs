@@ -0,0 +1,140 @@
+use super::{MetricSource, Scale, SeriesId, SystemBackend};
+use crate::config::CustomMetric;
+use std::io::Read;
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+use std::time::{Duration, Instant};
+use sysinfo::RefreshKind;
+
+const COMMAND_TIMEOUT: Duration = Duration::from_secs(2);
+const POLL_EVERY: Duration = Duration::from_millis(20);
+
+/// Plots the number printed by a user provided shell command.
+///
+/// The command runs on its own thread so a slow script never blocks the UI; each
+/// sample reports the most recent finished run and starts the next one.
+pub struct CustomSource {
+    label: String,
+    unit: String,
+    command: String,
+    series: SeriesId,
+    pending: Option<Receiver<Option<f64>>>,
+    last: Option<f64>,
+}
+
+impl CustomSource {
+    pub fn new(metric: &CustomMetric) -> Self {
+        Self {
+            label: metric.label.clone(),
+            unit: metric.unit.clone(),
+            command: metric.command.clone(),
+            series: SeriesId::new(format!("custom:{}", metric.label)),
+            pending: None,
+            last: None,
+        }
+    }
+}
+
+impl MetricSource for CustomSource {
+    fn name(&self) -> &str {
+        &self.label
+    }
+
+    fn refresh(&self, kind: RefreshKind) -> RefreshKind {
+        kind
+    }
+
+    fn scale(&self) -> Scale {
+        Scale::auto(self.unit.as_str())
+    }
+
+    fn sample(&mut self, _sys: &dyn SystemBackend) -> Vec<(SeriesId, f64)> {
+        if let Some(pending) = &self.pending {
+            match pending.try_recv() {
+                Ok(value) => {
+                    self.last = value;
+                    self.pending = None;
+                }
+                Err(TryRecvError::Empty) => {}
+                Err(TryRecvError::Disconnected) => self.pending = None,
+            }
+        }
+
+        if self.pending.is_none() {
+            let (tx, rx) = mpsc::channel();
+            let command = self.command.clone();
+            let label = self.label.clone();
+            thread::spawn(move || {
+                let value = run(&command).map_err(|err| {
+                    tracing::warn!("custom metric {:?} failed: {}", label, err);
+                });
+                let _ = tx.send(value.ok());
+            });
+            self.pending = Some(rx);
+        }
+
+        self.last
+            .map(|value| vec![(self.series.clone(), value)])
+            .unwrap_or_default()
+    }
+}
+
+fn run(command: &str) -> Result<f64, String> {
+    let mut child = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|err| err.to_string())?;
+
+    // Drained while the command runs, one printing more than the pipe buffer would
+    // otherwise block on writing and never exit
+    let (tx, rx) = mpsc::channel();
+    if let Some(mut stdout) = child.stdout.take() {
+        thread::spawn(move || {
+            let mut output = String::new();
+            let _ = tx.send(stdout.read_to_string(&mut output).map(|_| output));
+        });
+    }
+
+    let deadline = Instant::now() + COMMAND_TIMEOUT;
+    let timed_out = || format!("timed out after {:?}", COMMAND_TIMEOUT);
+    loop {
+        match child.try_wait().map_err(|err| err.to_string())? {
+            Some(_) => break,
+            None if Instant::now() >= deadline => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(timed_out());
+            }
+            None => thread::sleep(POLL_EVERY),
+        }
+    }
+
+    // A background process started by the command can keep stdout open past its exit
+    let output = rx
+        .recv_timeout(deadline.saturating_duration_since(Instant::now()))
+        .map_err(|_| timed_out())?
+        .map_err(|err| err.to_string())?;
+
+    let value = output
+        .split_whitespace()
+        .next()
+        .ok_or_else(|| "no output".to_string())?;
+    value
+        .parse()
+        .map_err(|_| format!("{:?} is not a number", value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_output_larger_than_the_pipe_buffer() {
+        assert_eq!(run("echo 42; head -c 200000 /dev/zero"), Ok(42.0));
+    }
+}
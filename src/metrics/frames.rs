@@ -0,0 +1,100 @@
+use super::{MetricSource, Scale, SeriesId, SystemBackend};
+use crate::fl;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use sysinfo::RefreshKind;
+
+/// Frames presented since the last sample, recorded by the UI
+static FRAMES: Mutex<FrameClock> = Mutex::new(FrameClock {
+    last: None,
+    intervals: Vec::new(),
+});
+
+struct FrameClock {
+    last: Option<Instant>,
+    intervals: Vec<Duration>,
+}
+
+/// Records that the compositor let the applet draw a frame at `at`.
+pub fn record_frame(at: Instant) {
+    if let Ok(mut clock) = FRAMES.lock() {
+        if let Some(last) = clock.last {
+            clock.intervals.push(at.saturating_duration_since(last));
+        }
+        clock.last = Some(at);
+    }
+}
+
+/// Forgets the last frame once the popup stopped drawing, so the time it was closed
+/// doesn't count as one long frame after reopening.
+pub fn reset_frames() {
+    if let Ok(mut clock) = FRAMES.lock() {
+        clock.last = None;
+        clock.intervals.clear();
+    }
+}
+
+/// Average and longest time between the compositor's frame callbacks to the popup.
+///
+/// Wayland presentation feedback is not exposed to applets, but the compositor only asks
+/// a continuously redrawing surface for its next frame once it presented the last one,
+/// so a stuttering compositor shows up as long intervals here.
+pub struct FrameTimeSource;
+
+impl MetricSource for FrameTimeSource {
+    fn name(&self) -> &str {
+        "Frame time"
+    }
+
+    fn title(&self) -> String {
+        fl!("frame-time")
+    }
+
+    fn refresh(&self, kind: RefreshKind) -> RefreshKind {
+        kind
+    }
+
+    fn scale(&self) -> Scale {
+        Scale::auto(" ms")
+    }
+
+    fn sample(&mut self, _sys: &dyn SystemBackend) -> Vec<(SeriesId, f64)> {
+        let Ok(mut clock) = FRAMES.lock() else {
+            return Vec::new();
+        };
+        let intervals = std::mem::take(&mut clock.intervals);
+        frame_times(&intervals)
+    }
+}
+
+fn frame_times(intervals: &[Duration]) -> Vec<(SeriesId, f64)> {
+    let Some(longest) = intervals.iter().max() else {
+        return Vec::new();
+    };
+    let average = intervals.iter().sum::<Duration>() / intervals.len() as u32;
+    vec![
+        (
+            SeriesId::new("frame_time_avg"),
+            average.as_secs_f64() * 1000.0,
+        ),
+        (
+            SeriesId::new("frame_time_max"),
+            longest.as_secs_f64() * 1000.0,
+        ),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_average_and_longest_interval() {
+        let intervals = [16, 17, 50].map(Duration::from_millis);
+        let times = frame_times(&intervals);
+        assert_eq!(times[0].0, SeriesId::new("frame_time_avg"));
+        assert!((times[0].1 - 27.667).abs() < 0.001);
+        assert_eq!(times[1], (SeriesId::new("frame_time_max"), 50.0));
+        assert!(frame_times(&[]).is_empty());
+    }
+}
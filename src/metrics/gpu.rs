@@ -0,0 +1,97 @@
+use super::{MetricSource, SeriesId, SystemBackend};
+use crate::fl;
+#[cfg(feature = "nvidia")]
+use nvml_wrapper::Nvml;
+use std::fs;
+use std::path::PathBuf;
+use sysinfo::RefreshKind;
+
+const DRM_ROOT: &str = "/sys/class/drm";
+
+/// GPU utilization, from amdgpu's `gpu_busy_percent` and, with the `nvidia` feature, NVML.
+pub struct GpuSource {
+    /// `gpu_busy_percent` files by card name
+    amdgpu: Vec<(String, PathBuf)>,
+    #[cfg(feature = "nvidia")]
+    nvml: Option<Nvml>,
+}
+
+impl GpuSource {
+    /// Returns `None` on machines without a supported GPU.
+    pub fn detect() -> Option<Self> {
+        let source = Self {
+            amdgpu: amdgpu_busy_files(),
+            #[cfg(feature = "nvidia")]
+            nvml: Nvml::init().ok(),
+        };
+        #[cfg(feature = "nvidia")]
+        if source.nvml.is_some() {
+            return Some(source);
+        }
+        (!source.amdgpu.is_empty()).then_some(source)
+    }
+}
+
+impl MetricSource for GpuSource {
+    fn name(&self) -> &str {
+        "GPU"
+    }
+
+    fn title(&self) -> String {
+        fl!("gpu")
+    }
+
+    fn refresh(&self, kind: RefreshKind) -> RefreshKind {
+        kind
+    }
+
+    fn sample(&mut self, _sys: &dyn SystemBackend) -> Vec<(SeriesId, f64)> {
+        #[allow(unused_mut)]
+        let mut series = self
+            .amdgpu
+            .iter()
+            .filter_map(|(card, path)| {
+                let busy: f64 = fs::read_to_string(path).ok()?.trim().parse().ok()?;
+                Some((SeriesId::new(format!("gpu:{}", card)), busy))
+            })
+            .collect::<Vec<_>>();
+
+        #[cfg(feature = "nvidia")]
+        if let Some(nvml) = &self.nvml {
+            let count = nvml.device_count().unwrap_or_default();
+            for index in 0..count {
+                let Ok(device) = nvml.device_by_index(index) else {
+                    continue;
+                };
+                if let Ok(utilization) = device.utilization_rates() {
+                    series.push((
+                        SeriesId::new(format!("gpu:nvidia{}", index)),
+                        utilization.gpu as f64,
+                    ));
+                }
+            }
+        }
+
+        series
+    }
+}
+
+fn amdgpu_busy_files() -> Vec<(String, PathBuf)> {
+    let Ok(entries) = fs::read_dir(DRM_ROOT) else {
+        return Vec::new();
+    };
+    let mut files = entries
+        .flatten()
+        .filter_map(|entry| {
+            let card = entry.file_name().to_str()?.to_string();
+            // Skip the connector entries like `card0-DP-1`
+            if !card.starts_with("card") || card.contains('-') {
+                return None;
+            }
+            let path = entry.path().join("device/gpu_busy_percent");
+            path.exists().then_some((card, path))
+        })
+        .collect::<Vec<_>>();
+    files.sort();
+    files
+}
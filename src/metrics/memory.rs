@@ -0,0 +1,58 @@
+use super::{MetricSource, Scale, SeriesId, SystemBackend};
+use crate::config::MemoryScale;
+use crate::fl;
+use sysinfo::{MemoryRefreshKind, RefreshKind};
+
+const GIB: f64 = 1024.0 * 1024.0 * 1024.0;
+
+pub struct MemorySource {
+    scale: MemoryScale,
+}
+
+impl MemorySource {
+    pub fn new(scale: MemoryScale) -> Self {
+        Self { scale }
+    }
+}
+
+impl MetricSource for MemorySource {
+    fn name(&self) -> &str {
+        "Memory"
+    }
+
+    fn title(&self) -> String {
+        fl!("memory")
+    }
+
+    fn refresh(&self, kind: RefreshKind) -> RefreshKind {
+        kind.with_memory(MemoryRefreshKind::new().with_ram())
+    }
+
+    fn scale(&self) -> Scale {
+        match self.scale {
+            MemoryScale::Percent => Scale::percent(),
+            MemoryScale::Absolute => Scale::auto(" GiB"),
+        }
+    }
+
+    fn sample(&mut self, sys: &dyn SystemBackend) -> Vec<(SeriesId, f64)> {
+        let total_memory = sys.total_memory() as f64;
+        if total_memory == 0.0 {
+            return Vec::new();
+        }
+
+        let used_memory = sys.used_memory() as f64;
+        match self.scale {
+            MemoryScale::Percent => {
+                vec![(
+                    SeriesId::new("memory"),
+                    (used_memory / total_memory) * 100.0,
+                )]
+            }
+            MemoryScale::Absolute => vec![
+                (SeriesId::new("memory_used"), used_memory / GIB),
+                (SeriesId::new("memory_total"), total_memory / GIB),
+            ],
+        }
+    }
+}
@@ -0,0 +1,308 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+use std::time::Instant;
+use sysinfo::RefreshKind;
+
+mod backend;
+mod cpu;
+mod custom;
+mod frames;
+mod gpu;
+mod memory;
+mod network;
+mod pipewire;
+mod plugin;
+mod process;
+mod stat;
+mod textfile;
+mod vmstat;
+
+#[cfg(test)]
+pub use backend::FakeBackend;
+pub use backend::{SysinfoBackend, SystemBackend};
+pub use cpu::CpuSource;
+pub use custom::CustomSource;
+pub use frames::{record_frame, reset_frames, FrameTimeSource};
+pub use gpu::GpuSource;
+pub use memory::MemorySource;
+pub use network::NetworkSource;
+pub use pipewire::XrunSource;
+pub use plugin::PluginSource;
+pub use process::ProcessMemorySource;
+pub use stat::{KernelCounterSource, RunQueueSource, StealTimeSource};
+pub use textfile::TextfileSource;
+pub use vmstat::{HugePageSource, PageFaultSource, SwapActivitySource};
+
+use crate::config::{Config, CustomMetric, MemoryScale};
+
+/// Identifies a single plotted series, e.g. `cpu` or `memory`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SeriesId(String);
+
+impl SeriesId {
+    pub fn new(id: impl Into<String>) -> Self {
+        Self(id.into())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for SeriesId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// How the values of a source are plotted.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Scale {
+    pub unit: String,
+    /// Fixed y-axis range, `None` to fit the plotted data.
+    pub range: Option<(f64, f64)>,
+    /// Plot the second series below the x-axis, e.g. upload under download
+    #[serde(default)]
+    pub mirrored: bool,
+}
+
+impl Scale {
+    pub fn percent() -> Self {
+        Self {
+            unit: "%".to_string(),
+            range: Some((0.0, 100.0)),
+            mirrored: false,
+        }
+    }
+
+    pub fn auto(unit: impl Into<String>) -> Self {
+        Self {
+            unit: unit.into(),
+            range: None,
+            mirrored: false,
+        }
+    }
+
+    pub fn mirrored(unit: impl Into<String>) -> Self {
+        Self {
+            unit: unit.into(),
+            range: None,
+            mirrored: true,
+        }
+    }
+}
+
+/// A collector plugged into the shared sampling loop.
+pub trait MetricSource {
+    /// Stable name of the metric, used to identify it in exports and integrations.
+    fn name(&self) -> &str;
+
+    /// Localized chart title.
+    fn title(&self) -> String {
+        self.name().to_string()
+    }
+
+    /// Adds the parts of the shared system state this source reads to `kind`.
+    fn refresh(&self, kind: RefreshKind) -> RefreshKind;
+
+    /// Reads the current values. Called right after the shared backend was refreshed.
+    fn sample(&mut self, sys: &dyn SystemBackend) -> Vec<(SeriesId, f64)>;
+
+    fn scale(&self) -> Scale {
+        Scale::percent()
+    }
+
+    /// Expensive sources are skipped while running on battery.
+    fn is_expensive(&self) -> bool {
+        false
+    }
+}
+
+/// The settings `sources` is built from. Compared before rebuilding the sources on a
+/// config change, which restarts the plugins and loses every rate baseline.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SourceConfig {
+    adapt_to_vm: bool,
+    memory_scale: MemoryScale,
+    advanced_memory_metrics: bool,
+    swap_activity_metric: bool,
+    scheduler_metrics: bool,
+    audio_metrics: bool,
+    frame_time_metric: bool,
+    watched_processes: Vec<String>,
+    custom_metrics: Vec<CustomMetric>,
+    textfile_directory: String,
+    textfile_metrics: Vec<String>,
+}
+
+impl SourceConfig {
+    pub fn new(config: &Config) -> Self {
+        Self {
+            adapt_to_vm: config.adapt_to_vm,
+            memory_scale: config.memory_scale,
+            advanced_memory_metrics: config.advanced_memory_metrics,
+            swap_activity_metric: config.swap_activity_metric,
+            scheduler_metrics: config.scheduler_metrics,
+            audio_metrics: config.audio_metrics,
+            frame_time_metric: config.frame_time_metric,
+            watched_processes: config.watched_processes.clone(),
+            custom_metrics: config.custom_metrics.clone(),
+            textfile_directory: config.textfile_directory.clone(),
+            textfile_metrics: config.textfile_metrics.clone(),
+        }
+    }
+}
+
+pub fn sources(config: &Config) -> Vec<Box<dyn MetricSource>> {
+    let mut sources: Vec<Box<dyn MetricSource>> = vec![
+        Box::new(CpuSource::new(crate::virt::is_guest(config))),
+        Box::new(MemorySource::new(config.memory_scale)),
+        Box::new(NetworkSource::new()),
+    ];
+    if crate::virt::is_guest(config) {
+        sources.push(Box::new(StealTimeSource::default()));
+    }
+    if let Some(gpu) = GpuSource::detect() {
+        sources.push(Box::new(gpu));
+    }
+    if config.advanced_memory_metrics {
+        sources.push(Box::new(PageFaultSource::new()));
+        sources.push(Box::new(HugePageSource));
+    }
+    if config.swap_activity_metric {
+        sources.push(Box::new(SwapActivitySource::new()));
+    }
+    if config.scheduler_metrics {
+        sources.push(Box::new(KernelCounterSource::context_switches()));
+        sources.push(Box::new(KernelCounterSource::interrupts()));
+        sources.push(Box::new(RunQueueSource::new()));
+    }
+    if config.audio_metrics {
+        sources.push(Box::new(XrunSource::new()));
+    }
+    if config.frame_time_metric {
+        sources.push(Box::new(FrameTimeSource));
+    }
+    if !config.watched_processes.is_empty() {
+        sources.push(Box::new(ProcessMemorySource::new(
+            config.watched_processes.clone(),
+        )));
+    }
+    sources.extend(
+        config
+            .custom_metrics
+            .iter()
+            .map(|metric| Box::new(CustomSource::new(metric)) as Box<dyn MetricSource>),
+    );
+    if !config.textfile_directory.is_empty() {
+        sources.extend(config.textfile_metrics.iter().map(|metric| {
+            Box::new(TextfileSource::new(&config.textfile_directory, metric))
+                as Box<dyn MetricSource>
+        }));
+    }
+    sources.extend(
+        PluginSource::discover()
+            .into_iter()
+            .map(|plugin| Box::new(plugin) as Box<dyn MetricSource>),
+    );
+    sources
+}
+
+#[derive(Clone, Debug)]
+pub struct Sample {
+    pub source: String,
+    pub title: String,
+    pub scale: Scale,
+    pub series: Vec<(SeriesId, f64)>,
+}
+
+/// Owns the system backend and refreshes only what the registered sources declared.
+pub struct Sampler {
+    backend: Box<dyn SystemBackend>,
+    refresh_kind: RefreshKind,
+    sources: Vec<Box<dyn MetricSource>>,
+    low_power: bool,
+    /// When the backend was first refreshed
+    primed_at: Option<Instant>,
+}
+
+impl Sampler {
+    pub fn new(sources: Vec<Box<dyn MetricSource>>) -> Self {
+        let backend = SysinfoBackend::new(refresh_kind(&sources, false));
+        Self::with_backend(Box::new(backend), sources)
+    }
+
+    pub fn with_backend(
+        backend: Box<dyn SystemBackend>,
+        sources: Vec<Box<dyn MetricSource>>,
+    ) -> Self {
+        Self {
+            backend,
+            refresh_kind: refresh_kind(&sources, false),
+            sources,
+            low_power: false,
+            primed_at: None,
+        }
+    }
+
+    /// Refreshes the backend once so the first real sample has a baseline to compare to.
+    pub fn prime(&mut self) {
+        self.backend.refresh(self.refresh_kind);
+        self.primed_at = Some(Instant::now());
+    }
+
+    /// Whether samples are meaningful yet, i.e. the backend was primed long enough ago.
+    pub fn is_warm(&self) -> bool {
+        self.primed_at
+            .is_some_and(|primed_at| primed_at.elapsed() >= self.backend.warm_up())
+    }
+
+    pub fn set_sources(&mut self, sources: Vec<Box<dyn MetricSource>>) {
+        self.sources = sources;
+        self.refresh_kind = refresh_kind(&self.sources, self.low_power);
+    }
+
+    pub fn set_low_power(&mut self, low_power: bool) {
+        self.low_power = low_power;
+        self.refresh_kind = refresh_kind(&self.sources, low_power);
+    }
+
+    pub fn sample(&mut self) -> Vec<Sample> {
+        let _span = tracing::debug_span!("sample").entered();
+        self.backend.refresh(self.refresh_kind);
+
+        let low_power = self.low_power;
+        self.sources
+            .iter_mut()
+            .filter(|source| !(low_power && source.is_expensive()))
+            .map(|source| {
+                let sys = self.backend.as_ref();
+                // A misbehaving collector must not take the whole applet down with it
+                let series = panic::catch_unwind(AssertUnwindSafe(|| source.sample(sys)))
+                    .unwrap_or_else(|_| {
+                        tracing::error!(
+                            "metric source {:?} panicked while sampling",
+                            source.name()
+                        );
+                        Vec::new()
+                    });
+
+                tracing::trace!(source = source.name(), ?series, "sampled");
+                Sample {
+                    source: source.name().to_string(),
+                    title: source.title(),
+                    scale: source.scale(),
+                    series,
+                }
+            })
+            .collect()
+    }
+}
+
+fn refresh_kind(sources: &[Box<dyn MetricSource>], low_power: bool) -> RefreshKind {
+    sources
+        .iter()
+        .filter(|source| !(low_power && source.is_expensive()))
+        .fold(RefreshKind::new(), |kind, source| source.refresh(kind))
+}
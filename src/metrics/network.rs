@@ -0,0 +1,89 @@
+use super::{MetricSource, Scale, SeriesId, SystemBackend};
+use crate::fl;
+use std::time::Instant;
+use sysinfo::RefreshKind;
+
+const KIB: f64 = 1024.0;
+
+/// Download and upload rate summed over every interface but loopback.
+#[derive(Default)]
+pub struct NetworkSource {
+    /// Totals of the previous sample and when it was taken
+    last: Option<((u64, u64), Instant)>,
+}
+
+impl NetworkSource {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl MetricSource for NetworkSource {
+    fn name(&self) -> &str {
+        "Network"
+    }
+
+    fn title(&self) -> String {
+        fl!("network")
+    }
+
+    // Interfaces are not part of the shared system state, the backend lists them anew
+    fn refresh(&self, kind: RefreshKind) -> RefreshKind {
+        kind
+    }
+
+    fn scale(&self) -> Scale {
+        Scale::mirrored(" KiB/s")
+    }
+
+    fn sample(&mut self, sys: &dyn SystemBackend) -> Vec<(SeriesId, f64)> {
+        let totals = sys.network_totals();
+        let now = Instant::now();
+        let Some((previous, since)) = self.last.replace((totals, now)) else {
+            return Vec::new();
+        };
+        let (received, transmitted) =
+            rates(previous, totals, now.duration_since(since).as_secs_f64());
+        vec![
+            (SeriesId::new("network_rx"), received),
+            (SeriesId::new("network_tx"), transmitted),
+        ]
+    }
+}
+
+/// KiB per second between two totals. Totals drop when an interface goes away, that
+/// sample reads 0 rather than a negative rate.
+fn rates(previous: (u64, u64), current: (u64, u64), elapsed: f64) -> (f64, f64) {
+    if elapsed <= 0.0 {
+        return (0.0, 0.0);
+    }
+    let rate =
+        |previous: u64, current: u64| current.saturating_sub(previous) as f64 / KIB / elapsed;
+    (rate(previous.0, current.0), rate(previous.1, current.1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::FakeBackend;
+
+    #[test]
+    fn first_sample_has_no_rates() {
+        let mut source = NetworkSource::new();
+        let mut backend = FakeBackend {
+            network_totals: (4096, 1024),
+            ..Default::default()
+        };
+
+        assert!(source.sample(&backend).is_empty());
+        backend.network_totals = (8192, 2048);
+        let values = source.sample(&backend);
+        assert_eq!(values.len(), 2);
+        assert!(values.iter().all(|(_, value)| *value > 0.0));
+    }
+
+    #[test]
+    fn rates_ignore_shrinking_totals() {
+        assert_eq!(rates((0, 4096), (2048, 1024), 2.0), (1.0, 0.0));
+    }
+}
@@ -0,0 +1,166 @@
+use super::vmstat::Rate;
+use super::{MetricSource, Scale, SeriesId, SystemBackend};
+use crate::fl;
+use std::collections::HashMap;
+use std::process::Command;
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+use sysinfo::RefreshKind;
+
+/// XRuns, i.e. audio buffer under- and overruns, per minute of all PipeWire nodes.
+///
+/// `pw-top` needs two iterations, about a second, to report counters, so it runs on its
+/// own thread and each sample reports the most recent finished run.
+pub struct XrunSource {
+    rate: Rate,
+    pending: Option<Receiver<Option<NodeErrors>>>,
+    /// Counters of the previous run, nodes come and go so their sum is not monotonic
+    nodes: NodeErrors,
+    /// XRuns of all nodes since the source started, fed to `rate`
+    total: u64,
+    last: Option<f64>,
+}
+
+/// `ERR` counter per node ID.
+type NodeErrors = HashMap<u32, u64>;
+
+impl XrunSource {
+    pub fn new() -> Self {
+        Self {
+            rate: Rate::default(),
+            pending: None,
+            nodes: NodeErrors::new(),
+            total: 0,
+            last: None,
+        }
+    }
+}
+
+impl MetricSource for XrunSource {
+    fn name(&self) -> &str {
+        "XRuns"
+    }
+
+    fn title(&self) -> String {
+        fl!("xruns")
+    }
+
+    fn refresh(&self, kind: RefreshKind) -> RefreshKind {
+        kind
+    }
+
+    fn scale(&self) -> Scale {
+        Scale::auto("/min")
+    }
+
+    fn sample(&mut self, _sys: &dyn SystemBackend) -> Vec<(SeriesId, f64)> {
+        if let Some(pending) = &self.pending {
+            match pending.try_recv() {
+                Ok(Some(nodes)) => {
+                    self.total += xruns_since(&self.nodes, &nodes);
+                    self.nodes = nodes;
+                    self.last = self.rate.update(self.total);
+                    self.pending = None;
+                }
+                Ok(None) => {
+                    self.last = None;
+                    self.pending = None;
+                }
+                Err(TryRecvError::Empty) => {}
+                Err(TryRecvError::Disconnected) => self.pending = None,
+            }
+        }
+
+        if self.pending.is_none() {
+            let (tx, rx) = mpsc::channel();
+            thread::spawn(move || {
+                let xruns = Command::new("pw-top")
+                    .args(["--batch-mode", "--iterations", "2"])
+                    .output()
+                    .ok()
+                    .filter(|output| output.status.success())
+                    .and_then(|output| parse_xruns(&String::from_utf8_lossy(&output.stdout)));
+                let _ = tx.send(xruns);
+            });
+            self.pending = Some(rx);
+        }
+
+        self.last
+            .map(|per_second| vec![(SeriesId::new("xruns"), per_second * 60.0)])
+            .unwrap_or_default()
+    }
+}
+
+/// XRuns since the previous counters. Nodes seen for the first time only set their
+/// baseline, and a counter that went down belongs to a new node that reused the ID.
+fn xruns_since(previous: &NodeErrors, current: &NodeErrors) -> u64 {
+    current
+        .iter()
+        .filter_map(|(id, errors)| {
+            let previous = previous.get(id)?;
+            Some(if errors >= previous {
+                errors - previous
+            } else {
+                *errors
+            })
+        })
+        .sum()
+}
+
+/// The `ERR` column of the last iteration `pw-top --batch-mode` printed, by node ID.
+fn parse_xruns(output: &str) -> Option<NodeErrors> {
+    let lines = output.lines().collect::<Vec<_>>();
+    // Every iteration starts with the header, the first one has no counters yet
+    let start = lines
+        .iter()
+        .rposition(|line| line.split_whitespace().nth(1) == Some("ID"))?;
+    let column = lines[start]
+        .split_whitespace()
+        .position(|name| name == "ERR")?;
+    Some(
+        lines[start + 1..]
+            .iter()
+            .filter_map(|line| {
+                let fields = line.split_whitespace().collect::<Vec<_>>();
+                Some((
+                    fields.get(1)?.parse().ok()?,
+                    fields.get(column)?.parse().ok()?,
+                ))
+            })
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PW_TOP: &str = "\
+S   ID  QUANT   RATE    WAIT    BUSY   W/Q   B/Q  ERR FORMAT           NAME
+S   30      0      0    ---     ---   ---   ---     0                  Dummy-Driver
+R   42    256  48000  18.2us  11.0us  0.00  0.00    0    S32LE 2 48000 alsa_output.pci
+S   ID  QUANT   RATE    WAIT    BUSY   W/Q   B/Q  ERR FORMAT           NAME
+S   30      0      0    ---     ---   ---   ---     0                  Dummy-Driver
+R   42    256  48000  18.2us  11.0us  0.00  0.00    3    S32LE 2 48000 alsa_output.pci
+R   77    256  48000   9.1us   4.2us  0.00  0.00    2    F32LE 2 48000  + Firefox
+";
+
+    #[test]
+    fn reads_the_errors_of_the_last_iteration() {
+        assert_eq!(
+            parse_xruns(PW_TOP),
+            Some(NodeErrors::from([(30, 0), (42, 3), (77, 2)]))
+        );
+        assert_eq!(parse_xruns(""), None);
+    }
+
+    #[test]
+    fn counts_xruns_per_node() {
+        let previous = NodeErrors::from([(42, 3), (77, 2)]);
+        // 77 went away, 90 is new and 42 got two more
+        let current = NodeErrors::from([(42, 5), (90, 7)]);
+        assert_eq!(xruns_since(&previous, &current), 2);
+        // A new node reusing ID 42
+        assert_eq!(xruns_since(&current, &NodeErrors::from([(42, 1)])), 1);
+    }
+}
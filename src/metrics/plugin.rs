@@ -0,0 +1,236 @@
+use super::{MetricSource, Scale, SeriesId, SystemBackend};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{BufRead, BufReader};
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+use sysinfo::RefreshKind;
+
+/// Below the user's config directory
+const PLUGIN_DIR: &str = "cosmic-sys-status/plugins";
+
+/// A line printed by a plugin.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum PluginMessage {
+    /// How to plot the values, usually the first line
+    Describe {
+        title: Option<String>,
+        #[serde(default)]
+        unit: String,
+        /// Fixed y-axis range, fitted to the data if missing
+        min: Option<f64>,
+        max: Option<f64>,
+    },
+    /// The current value of every series, keyed by series name
+    Sample { values: BTreeMap<String, f64> },
+}
+
+/// Plots the values an external executable prints as JSON lines, e.g.
+/// `{"type": "sample", "values": {"nozzle": 210.5, "bed": 60}}`.
+///
+/// Plugins run for as long as the source exists and print at their own pace; each
+/// sample reports the most recent values.
+pub struct PluginSource {
+    /// File name of the executable
+    name: String,
+    title: String,
+    scale: Scale,
+    messages: Receiver<PluginMessage>,
+    values: Vec<(SeriesId, f64)>,
+    child: Option<Child>,
+}
+
+impl PluginSource {
+    /// Starts every executable in the plugin directory.
+    pub fn discover() -> Vec<Self> {
+        let Some(dir) = dirs::config_dir().map(|dir| dir.join(PLUGIN_DIR)) else {
+            return Vec::new();
+        };
+        let Ok(entries) = fs::read_dir(&dir) else {
+            return Vec::new();
+        };
+        let mut paths = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| is_executable(path))
+            .collect::<Vec<_>>();
+        paths.sort();
+
+        paths
+            .into_iter()
+            .filter_map(|path| {
+                Self::spawn(&path)
+                    .map_err(|err| tracing::warn!("plugin {:?} failed to start: {}", path, err))
+                    .ok()
+            })
+            .collect()
+    }
+
+    fn spawn(path: &Path) -> std::io::Result<Self> {
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let mut child = Command::new(path)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()?;
+        let stdout = child.stdout.take().expect("stdout is piped");
+
+        let (tx, rx) = mpsc::channel();
+        let plugin = name.clone();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                let Ok(line) = line else {
+                    break;
+                };
+                if line.trim().is_empty() {
+                    continue;
+                }
+                match serde_json::from_str(&line) {
+                    Ok(message) => {
+                        if tx.send(message).is_err() {
+                            break;
+                        }
+                    }
+                    Err(err) => tracing::warn!("plugin {:?} printed {:?}: {}", plugin, line, err),
+                }
+            }
+            tracing::warn!("plugin {:?} stopped", plugin);
+        });
+
+        let mut source = Self::with_receiver(name, rx);
+        source.child = Some(child);
+        Ok(source)
+    }
+
+    fn with_receiver(name: String, messages: Receiver<PluginMessage>) -> Self {
+        Self {
+            title: name.clone(),
+            name,
+            scale: Scale::auto(""),
+            messages,
+            values: Vec::new(),
+            child: None,
+        }
+    }
+
+    fn apply(&mut self, message: PluginMessage) {
+        match message {
+            PluginMessage::Describe {
+                title,
+                unit,
+                min,
+                max,
+            } => {
+                if let Some(title) = title {
+                    self.title = title;
+                }
+                self.scale = Scale {
+                    unit,
+                    range: min.zip(max),
+                    mirrored: false,
+                };
+            }
+            PluginMessage::Sample { values } => {
+                self.values = values
+                    .into_iter()
+                    .map(|(series, value)| {
+                        (SeriesId::new(format!("{}:{}", self.name, series)), value)
+                    })
+                    .collect();
+            }
+        }
+    }
+}
+
+impl Drop for PluginSource {
+    fn drop(&mut self) {
+        if let Some(mut child) = self.child.take() {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
+
+impl MetricSource for PluginSource {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn title(&self) -> String {
+        self.title.clone()
+    }
+
+    fn refresh(&self, kind: RefreshKind) -> RefreshKind {
+        kind
+    }
+
+    fn scale(&self) -> Scale {
+        self.scale.clone()
+    }
+
+    fn sample(&mut self, _sys: &dyn SystemBackend) -> Vec<(SeriesId, f64)> {
+        loop {
+            match self.messages.try_recv() {
+                Ok(message) => self.apply(message),
+                Err(TryRecvError::Empty) => break,
+                // The plugin exited, its chart shows up as unavailable
+                Err(TryRecvError::Disconnected) => {
+                    self.values.clear();
+                    break;
+                }
+            }
+        }
+        self.values.clone()
+    }
+}
+
+fn is_executable(path: &Path) -> bool {
+    fs::metadata(path)
+        .is_ok_and(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::FakeBackend;
+
+    #[test]
+    fn plots_the_latest_values_in_the_described_scale() {
+        let (tx, rx) = mpsc::channel();
+        let mut source = PluginSource::with_receiver("printer".to_string(), rx);
+        for line in [
+            r#"{"type": "describe", "title": "3D printer", "unit": "°C", "min": 0, "max": 300}"#,
+            r#"{"type": "sample", "values": {"nozzle": 180.0, "bed": 55}}"#,
+            r#"{"type": "sample", "values": {"nozzle": 210.5, "bed": 60}}"#,
+        ] {
+            tx.send(serde_json::from_str(line).unwrap()).unwrap();
+        }
+
+        let values = source.sample(&FakeBackend::default());
+        assert_eq!(source.title(), "3D printer");
+        assert_eq!(source.scale().range, Some((0.0, 300.0)));
+        assert_eq!(
+            values,
+            vec![
+                (SeriesId::new("printer:bed"), 60.0),
+                (SeriesId::new("printer:nozzle"), 210.5),
+            ]
+        );
+
+        drop(tx);
+        assert!(source.sample(&FakeBackend::default()).is_empty());
+    }
+
+    #[test]
+    fn rejects_unknown_messages() {
+        assert!(serde_json::from_str::<PluginMessage>(r#"{"type": "hello"}"#).is_err());
+    }
+}
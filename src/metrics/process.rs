@@ -0,0 +1,49 @@
+use super::{MetricSource, Scale, SeriesId, SystemBackend};
+use crate::fl;
+use sysinfo::{ProcessRefreshKind, RefreshKind};
+
+const MIB: f64 = 1024.0 * 1024.0;
+
+/// Tracks the resident memory of processes selected by name, summed over all
+/// their instances, to spot leaks over time.
+pub struct ProcessMemorySource {
+    names: Vec<String>,
+}
+
+impl ProcessMemorySource {
+    pub fn new(names: Vec<String>) -> Self {
+        Self { names }
+    }
+}
+
+impl MetricSource for ProcessMemorySource {
+    fn name(&self) -> &str {
+        "Process memory"
+    }
+
+    fn title(&self) -> String {
+        fl!("process-memory")
+    }
+
+    fn refresh(&self, kind: RefreshKind) -> RefreshKind {
+        kind.with_processes(ProcessRefreshKind::new().with_memory())
+    }
+
+    fn scale(&self) -> Scale {
+        Scale::auto(" MiB")
+    }
+
+    fn sample(&mut self, sys: &dyn SystemBackend) -> Vec<(SeriesId, f64)> {
+        self.names
+            .iter()
+            .map(|name| {
+                let rss = sys.process_memory(name);
+                (SeriesId::new(name.as_str()), rss as f64 / MIB)
+            })
+            .collect()
+    }
+
+    fn is_expensive(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,198 @@
+use super::vmstat::{parse_counter, Rate};
+use super::{MetricSource, Scale, SeriesId, SystemBackend};
+use crate::fl;
+use std::fs;
+use sysinfo::RefreshKind;
+
+const STAT: &str = "/proc/stat";
+
+/// Rate of one of the cumulative counters in `/proc/stat`.
+pub struct KernelCounterSource {
+    /// `ctxt` for context switches, `intr` for interrupts
+    counter: &'static str,
+    name: &'static str,
+    rate: Rate,
+}
+
+impl KernelCounterSource {
+    pub fn context_switches() -> Self {
+        Self {
+            counter: "ctxt",
+            name: "Context switches",
+            rate: Rate::default(),
+        }
+    }
+
+    pub fn interrupts() -> Self {
+        Self {
+            counter: "intr",
+            name: "Interrupts",
+            rate: Rate::default(),
+        }
+    }
+}
+
+impl MetricSource for KernelCounterSource {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn title(&self) -> String {
+        match self.counter {
+            "ctxt" => fl!("context-switches"),
+            _ => fl!("interrupts"),
+        }
+    }
+
+    fn refresh(&self, kind: RefreshKind) -> RefreshKind {
+        kind
+    }
+
+    fn scale(&self) -> Scale {
+        Scale::auto("/s")
+    }
+
+    fn sample(&mut self, _sys: &dyn SystemBackend) -> Vec<(SeriesId, f64)> {
+        let Some(count) = fs::read_to_string(STAT)
+            .ok()
+            .and_then(|stat| parse_counter(&stat, self.counter))
+        else {
+            return Vec::new();
+        };
+        self.rate
+            .update(count)
+            .map(|rate| vec![(SeriesId::new(self.counter), rate)])
+            .unwrap_or_default()
+    }
+}
+
+/// Runnable tasks as a share of the cores: above 100% tasks wait for a core, even when
+/// the CPU chart looks fine between samples.
+pub struct RunQueueSource {
+    cores: f64,
+}
+
+impl RunQueueSource {
+    pub fn new() -> Self {
+        Self {
+            cores: std::thread::available_parallelism()
+                .map(|n| n.get())
+                .unwrap_or(1) as f64,
+        }
+    }
+}
+
+impl Default for RunQueueSource {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MetricSource for RunQueueSource {
+    fn name(&self) -> &str {
+        "Run queue"
+    }
+
+    fn title(&self) -> String {
+        fl!("run-queue")
+    }
+
+    fn refresh(&self, kind: RefreshKind) -> RefreshKind {
+        kind
+    }
+
+    fn scale(&self) -> Scale {
+        // Oversubscription goes past 100%
+        Scale::auto("%")
+    }
+
+    fn sample(&mut self, _sys: &dyn SystemBackend) -> Vec<(SeriesId, f64)> {
+        // A gauge, not a counter; it includes the applet itself reading it
+        let Some(running) = fs::read_to_string(STAT)
+            .ok()
+            .and_then(|stat| parse_counter(&stat, "procs_running"))
+        else {
+            return Vec::new();
+        };
+        vec![(
+            SeriesId::new("run_queue"),
+            running as f64 / self.cores * 100.0,
+        )]
+    }
+}
+
+/// Share of the time the hypervisor ran something else while a vCPU had work, from the
+/// `steal` column of `/proc/stat`. Apart from the CPU chart it tells a busy VM from a
+/// starved one.
+#[derive(Default)]
+pub struct StealTimeSource {
+    /// Steal and total ticks at the previous sample
+    last: Option<(u64, u64)>,
+}
+
+impl MetricSource for StealTimeSource {
+    fn name(&self) -> &str {
+        "CPU steal"
+    }
+
+    fn title(&self) -> String {
+        fl!("cpu-steal")
+    }
+
+    fn refresh(&self, kind: RefreshKind) -> RefreshKind {
+        kind
+    }
+
+    fn sample(&mut self, _sys: &dyn SystemBackend) -> Vec<(SeriesId, f64)> {
+        let Some((steal, total)) = fs::read_to_string(STAT)
+            .ok()
+            .and_then(|stat| steal_ticks(&stat))
+        else {
+            return Vec::new();
+        };
+        let previous = self.last.replace((steal, total));
+        match previous {
+            Some((last_steal, last_total)) if total > last_total => vec![(
+                SeriesId::new("cpu_steal"),
+                steal.saturating_sub(last_steal) as f64 / (total - last_total) as f64 * 100.0,
+            )],
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// Steal and total ticks of all CPUs from the `cpu` line: user, nice, system, idle,
+/// iowait, irq, softirq and steal. Guest time is already counted in user and nice.
+fn steal_ticks(stat: &str) -> Option<(u64, u64)> {
+    let line = stat.lines().find(|line| line.starts_with("cpu "))?;
+    let ticks = line
+        .split_whitespace()
+        .skip(1)
+        .take(8)
+        .map(|field| field.parse::<u64>().ok())
+        .collect::<Option<Vec<_>>>()?;
+    Some((*ticks.get(7)?, ticks.iter().sum()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_runnable_tasks() {
+        let stat = "ctxt 2718285\nbtime 1714000000\nprocs_running 6\nprocs_blocked 1\n";
+        assert_eq!(parse_counter(stat, "procs_running"), Some(6));
+    }
+
+    #[test]
+    fn reads_steal_of_all_cpus() {
+        let stat = "\
+cpu  4705 150 1120 16250 520 0 34 221 50 0
+cpu0 2352 75 560 8125 260 0 17 110 25 0
+intr 1462898 26 9 0 0
+";
+        assert_eq!(steal_ticks(stat), Some((221, 23000)));
+        // Kernels before 2.6.11 had no steal column
+        assert_eq!(steal_ticks("cpu  4705 150 1120 16250 520 0 34\n"), None);
+    }
+}
@@ -0,0 +1,127 @@
+use super::{MetricSource, Scale, SeriesId, SystemBackend};
+use std::fs;
+use std::path::PathBuf;
+use sysinfo::RefreshKind;
+
+/// Plots one metric from the `*.prom` files of a node_exporter textfile collector
+/// directory, with a series per label set.
+pub struct TextfileSource {
+    directory: PathBuf,
+    metric: String,
+    name: String,
+}
+
+impl TextfileSource {
+    pub fn new(directory: &str, metric: &str) -> Self {
+        Self {
+            directory: PathBuf::from(directory),
+            metric: metric.to_string(),
+            name: format!("textfile:{}", metric),
+        }
+    }
+}
+
+impl MetricSource for TextfileSource {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn title(&self) -> String {
+        self.metric.clone()
+    }
+
+    fn refresh(&self, kind: RefreshKind) -> RefreshKind {
+        kind
+    }
+
+    fn scale(&self) -> Scale {
+        Scale::auto("")
+    }
+
+    fn sample(&mut self, _sys: &dyn SystemBackend) -> Vec<(SeriesId, f64)> {
+        let Ok(entries) = fs::read_dir(&self.directory) else {
+            return Vec::new();
+        };
+        let mut paths = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.extension()
+                    .is_some_and(|extension| extension == "prom")
+            })
+            .collect::<Vec<_>>();
+        paths.sort();
+
+        paths
+            .iter()
+            .filter_map(|path| fs::read_to_string(path).ok())
+            .flat_map(|text| parse_samples(&text, &self.metric))
+            .map(|(labels, value)| {
+                // Labels tell the series apart in the legend
+                let id = if labels.is_empty() {
+                    self.metric.clone()
+                } else {
+                    format!("{}:{}", self.metric, labels)
+                };
+                (SeriesId::new(id), value)
+            })
+            .collect()
+    }
+}
+
+/// Samples of `metric` in the Prometheus text format with their labels, e.g.
+/// `job="photos"`, empty for a metric without labels.
+fn parse_samples(text: &str, metric: &str) -> Vec<(String, f64)> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| {
+            // Label values may contain spaces, the value follows the closing brace
+            let (name, labels, rest) = match line.find('{') {
+                Some(open) => {
+                    let close = open + line[open..].find('}')?;
+                    (&line[..open], &line[open + 1..close], &line[close + 1..])
+                }
+                None => {
+                    let (name, rest) = line.split_once(char::is_whitespace)?;
+                    (name, "", rest)
+                }
+            };
+            if name.trim() != metric {
+                return None;
+            }
+            // An optional timestamp follows the value
+            let value = rest.split_whitespace().next()?.parse::<f64>().ok()?;
+            value.is_finite().then(|| (labels.to_string(), value))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_every_label_set_of_the_metric() {
+        let text = r#"
+# HELP backup_age_seconds Time since the last successful backup.
+# TYPE backup_age_seconds gauge
+backup_age_seconds{job="home dir"} 3600
+backup_age_seconds{job="photos"} 7200 1714000000000
+backup_size_bytes{job="photos"} 1e9
+backup_failed 0
+backup_age_seconds{job="broken"} NaN
+"#;
+        assert_eq!(
+            parse_samples(text, "backup_age_seconds"),
+            vec![
+                (r#"job="home dir""#.to_string(), 3600.0),
+                (r#"job="photos""#.to_string(), 7200.0),
+            ]
+        );
+        assert_eq!(
+            parse_samples(text, "backup_failed"),
+            vec![(String::new(), 0.0)]
+        );
+    }
+}
@@ -0,0 +1,233 @@
+use super::{MetricSource, Scale, SeriesId, SystemBackend};
+use crate::fl;
+use std::fs;
+use std::time::Instant;
+use sysinfo::RefreshKind;
+
+const VMSTAT: &str = "/proc/vmstat";
+const MEMINFO: &str = "/proc/meminfo";
+
+/// Turns a counter that only ever grows into a per-second rate.
+#[derive(Default)]
+pub(super) struct Rate {
+    last: Option<(Instant, u64)>,
+}
+
+impl Rate {
+    /// Returns `None` on the first call, which only sets the baseline.
+    pub(super) fn update(&mut self, counter: u64) -> Option<f64> {
+        let now = Instant::now();
+        let rate = self.last.and_then(|(time, last)| {
+            let elapsed = now.duration_since(time).as_secs_f64();
+            (elapsed > 0.0).then(|| counter.saturating_sub(last) as f64 / elapsed)
+        });
+        self.last = Some((now, counter));
+        rate
+    }
+}
+
+/// Major page faults per second, i.e. pages that had to be read from disk.
+pub struct PageFaultSource {
+    rate: Rate,
+}
+
+impl PageFaultSource {
+    pub fn new() -> Self {
+        Self {
+            rate: Rate::default(),
+        }
+    }
+}
+
+impl Default for PageFaultSource {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MetricSource for PageFaultSource {
+    fn name(&self) -> &str {
+        "Page faults"
+    }
+
+    fn title(&self) -> String {
+        fl!("page-faults")
+    }
+
+    fn refresh(&self, kind: RefreshKind) -> RefreshKind {
+        kind
+    }
+
+    fn scale(&self) -> Scale {
+        Scale::auto("/s")
+    }
+
+    fn sample(&mut self, _sys: &dyn SystemBackend) -> Vec<(SeriesId, f64)> {
+        let Some(faults) = fs::read_to_string(VMSTAT)
+            .ok()
+            .and_then(|vmstat| parse_counter(&vmstat, "pgmajfault"))
+        else {
+            return Vec::new();
+        };
+        self.rate
+            .update(faults)
+            .map(|rate| vec![(SeriesId::new("major_faults"), rate)])
+            .unwrap_or_default()
+    }
+}
+
+/// Pages swapped in and out per second. Used swap only says something was swapped out
+/// at some point, these rates show whether swapping is slowing the machine right now.
+pub struct SwapActivitySource {
+    swap_in: Rate,
+    swap_out: Rate,
+}
+
+impl SwapActivitySource {
+    pub fn new() -> Self {
+        Self {
+            swap_in: Rate::default(),
+            swap_out: Rate::default(),
+        }
+    }
+}
+
+impl Default for SwapActivitySource {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MetricSource for SwapActivitySource {
+    fn name(&self) -> &str {
+        "Swap activity"
+    }
+
+    fn title(&self) -> String {
+        fl!("swap-activity")
+    }
+
+    fn refresh(&self, kind: RefreshKind) -> RefreshKind {
+        kind
+    }
+
+    fn scale(&self) -> Scale {
+        // Swap-out below the axis, like upload in the network chart
+        Scale::mirrored("/s")
+    }
+
+    fn sample(&mut self, _sys: &dyn SystemBackend) -> Vec<(SeriesId, f64)> {
+        let Ok(vmstat) = fs::read_to_string(VMSTAT) else {
+            return Vec::new();
+        };
+        let (Some(swapped_in), Some(swapped_out)) = (
+            parse_counter(&vmstat, "pswpin"),
+            parse_counter(&vmstat, "pswpout"),
+        ) else {
+            return Vec::new();
+        };
+        match (
+            self.swap_in.update(swapped_in),
+            self.swap_out.update(swapped_out),
+        ) {
+            (Some(swap_in), Some(swap_out)) => vec![
+                (SeriesId::new("swap_in"), swap_in),
+                (SeriesId::new("swap_out"), swap_out),
+            ],
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// Share of the reserved huge pages in use.
+pub struct HugePageSource;
+
+impl MetricSource for HugePageSource {
+    fn name(&self) -> &str {
+        "Huge pages"
+    }
+
+    fn title(&self) -> String {
+        fl!("huge-pages")
+    }
+
+    fn refresh(&self, kind: RefreshKind) -> RefreshKind {
+        kind
+    }
+
+    fn sample(&mut self, _sys: &dyn SystemBackend) -> Vec<(SeriesId, f64)> {
+        let Ok(meminfo) = fs::read_to_string(MEMINFO) else {
+            return Vec::new();
+        };
+        match huge_pages(&meminfo) {
+            // No huge pages reserved, shown as unavailable
+            Some((0, _)) | None => Vec::new(),
+            Some((total, free)) => vec![(
+                SeriesId::new("huge_pages"),
+                total.saturating_sub(free) as f64 / total as f64 * 100.0,
+            )],
+        }
+    }
+}
+
+/// Value of a `name value` line as found in `/proc/vmstat` and `/proc/stat`.
+pub(super) fn parse_counter(vmstat: &str, name: &str) -> Option<u64> {
+    vmstat.lines().find_map(|line| {
+        // `intr` in /proc/stat is followed by per-interrupt counts, the first is the total
+        let mut fields = line.split_whitespace();
+        (fields.next()? == name).then(|| fields.next()?.parse().ok())?
+    })
+}
+
+/// `HugePages_Total` and `HugePages_Free` from `/proc/meminfo`.
+fn huge_pages(meminfo: &str) -> Option<(u64, u64)> {
+    let field = |key: &str| {
+        meminfo.lines().find_map(|line| {
+            let (name, value) = line.split_once(':')?;
+            (name == key).then(|| value.trim().parse::<u64>().ok())?
+        })
+    };
+    Some((field("HugePages_Total")?, field("HugePages_Free")?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_major_faults() {
+        let vmstat = "pgfault 123456789\npgmajfault 4321\npgrefill 0\n";
+
+        assert_eq!(parse_counter(vmstat, "pgmajfault"), Some(4321));
+        assert_eq!(parse_counter(vmstat, "pgsteal"), None);
+    }
+
+    #[test]
+    fn parses_swap_counters() {
+        let vmstat = "pswpin 1024\npswpout 2048\npgpgin 123\n";
+
+        assert_eq!(parse_counter(vmstat, "pswpin"), Some(1024));
+        assert_eq!(parse_counter(vmstat, "pswpout"), Some(2048));
+    }
+
+    #[test]
+    fn parses_the_interrupt_total() {
+        let stat = "cpu  4705 150 1120 16250 520 0 34 0 0 0\nintr 1462898 26 9 0 0\nctxt 2718285\n";
+
+        assert_eq!(parse_counter(stat, "intr"), Some(1462898));
+        assert_eq!(parse_counter(stat, "ctxt"), Some(2718285));
+    }
+
+    #[test]
+    fn parses_huge_pages() {
+        let meminfo = "\
+MemTotal:       32768000 kB
+HugePages_Total:     512
+HugePages_Free:      128
+HugePages_Rsvd:        0
+Hugepagesize:       2048 kB
+";
+
+        assert_eq!(huge_pages(meminfo), Some((512, 128)));
+    }
+}
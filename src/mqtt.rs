@@ -0,0 +1,116 @@
+use crate::metrics::Sample;
+use chrono::{SecondsFormat, Utc};
+use rumqttc::{Client, MqttOptions, QoS};
+use serde_json::{json, Map, Value};
+use std::thread;
+use std::time::Duration;
+
+const DEFAULT_PORT: u16 = 1883;
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MqttSettings {
+    pub broker_url: String,
+    pub topic: String,
+    pub username: String,
+    pub password: String,
+}
+
+/// Publishes every sample as a JSON document to an MQTT topic.
+pub struct Publisher {
+    settings: MqttSettings,
+    client: Client,
+}
+
+impl Publisher {
+    pub fn connect(settings: MqttSettings) -> Result<Self, String> {
+        let (host, port) = parse_broker_url(&settings.broker_url)?;
+        let mut options = MqttOptions::new(crate::window::ID, host, port);
+        options.set_keep_alive(Duration::from_secs(30));
+        if !settings.username.is_empty() {
+            options.set_credentials(settings.username.clone(), settings.password.clone());
+        }
+
+        let (client, mut connection) = Client::new(options, 10);
+        thread::Builder::new()
+            .name("mqtt".into())
+            .spawn(move || {
+                // The connection has to be polled for anything to be sent; it ends once
+                // the client is dropped
+                for event in connection.iter() {
+                    if let Err(err) = event {
+                        match err {
+                            rumqttc::ConnectionError::RequestsDone => break,
+                            err => {
+                                tracing::warn!("mqtt connection error: {}", err);
+                                thread::sleep(RECONNECT_DELAY);
+                            }
+                        }
+                    }
+                }
+            })
+            .map_err(|err| err.to_string())?;
+
+        Ok(Self { settings, client })
+    }
+
+    pub fn settings(&self) -> &MqttSettings {
+        &self.settings
+    }
+
+    pub fn publish(&self, samples: &[Sample]) {
+        let payload = payload(samples).to_string();
+        if let Err(err) = self.client.try_publish(
+            self.settings.topic.as_str(),
+            QoS::AtMostOnce,
+            false,
+            payload,
+        ) {
+            tracing::debug!("dropping mqtt sample: {}", err);
+        }
+    }
+}
+
+impl Drop for Publisher {
+    fn drop(&mut self) {
+        let _ = self.client.try_disconnect();
+    }
+}
+
+fn parse_broker_url(url: &str) -> Result<(String, u16), String> {
+    let address = url
+        .strip_prefix("mqtt://")
+        .or_else(|| url.strip_prefix("tcp://"))
+        .unwrap_or(url)
+        .trim_end_matches('/');
+    if address.is_empty() {
+        return Err("empty broker url".into());
+    }
+
+    match address.rsplit_once(':') {
+        Some((host, port)) => port
+            .parse()
+            .map(|port| (host.to_string(), port))
+            .map_err(|_| format!("invalid broker port {:?}", port)),
+        None => Ok((address.to_string(), DEFAULT_PORT)),
+    }
+}
+
+fn payload(samples: &[Sample]) -> Value {
+    let metrics = samples
+        .iter()
+        .map(|sample| {
+            let series = sample
+                .series
+                .iter()
+                .map(|(id, value)| (id.to_string(), json!(value)))
+                .collect::<Map<_, _>>();
+            (sample.source.clone(), Value::Object(series))
+        })
+        .collect::<Map<_, _>>();
+
+    json!({
+        "timestamp": Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true),
+        "metrics": metrics,
+    })
+}
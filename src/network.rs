@@ -0,0 +1,114 @@
+use crate::fl;
+use crate::layout;
+use crate::window::Message;
+use cosmic::iced::{Alignment, Length};
+use cosmic::widget;
+use cosmic::Element;
+use human_bytes::human_bytes;
+use std::collections::BTreeMap;
+use sysinfo::Networks;
+
+#[derive(Clone, Copy, Debug, Default)]
+struct Counters {
+    received: u64,
+    transmitted: u64,
+}
+
+/// Cumulative traffic per interface, since boot and since the applet started
+/// (or the counters were last reset).
+pub struct NetworkTotals {
+    networks: Networks,
+    /// Kernel counters when the session started
+    baseline: BTreeMap<String, Counters>,
+    /// Latest kernel counters, which count since boot
+    current: BTreeMap<String, Counters>,
+}
+
+impl NetworkTotals {
+    pub fn new() -> Self {
+        let mut totals = Self {
+            networks: Networks::new_with_refreshed_list(),
+            baseline: BTreeMap::new(),
+            current: BTreeMap::new(),
+        };
+        totals.refresh();
+        totals.reset();
+        totals
+    }
+
+    pub fn refresh(&mut self) {
+        self.networks.refresh_list();
+        self.current = self
+            .networks
+            .iter()
+            .filter(|(name, _)| name.as_str() != "lo")
+            .map(|(name, data)| {
+                (
+                    name.clone(),
+                    Counters {
+                        received: data.total_received(),
+                        transmitted: data.total_transmitted(),
+                    },
+                )
+            })
+            .collect();
+    }
+
+    /// Bytes received and transmitted by `interface` since boot.
+    pub fn boot_total(&self, interface: &str) -> Option<u64> {
+        self.current
+            .get(interface)
+            .map(|counters| counters.received + counters.transmitted)
+    }
+
+    /// Bytes received and transmitted by every interface since boot.
+    pub fn boot_total_all(&self) -> u64 {
+        self.current
+            .values()
+            .map(|counters| counters.received + counters.transmitted)
+            .sum()
+    }
+
+    pub fn reset(&mut self) {
+        self.baseline = self.current.clone();
+    }
+
+    fn session(&self, name: &str, boot: Counters) -> Counters {
+        // Interfaces that appeared later, or whose counters restarted, count from zero
+        let baseline = self.baseline.get(name).copied().unwrap_or_default();
+        Counters {
+            received: boot.received.saturating_sub(baseline.received),
+            transmitted: boot.transmitted.saturating_sub(baseline.transmitted),
+        }
+    }
+
+    pub fn view(&self) -> Element<Message> {
+        let mut column = widget::column().spacing(4).width(Length::Fill).push(
+            layout::directional_row(vec![
+                widget::text(fl!("network-totals"))
+                    .width(Length::Fill)
+                    .horizontal_alignment(layout::start_alignment())
+                    .into(),
+                widget::button::standard(fl!("network-reset"))
+                    .on_press(Message::ResetNetworkTotals)
+                    .into(),
+            ])
+            .spacing(8)
+            .align_items(Alignment::Center),
+        );
+
+        for (name, boot) in &self.current {
+            let session = self.session(name, *boot);
+            column = column.push(widget::text(fl!(
+                "network-interface-totals",
+                interface = name.as_str(),
+                session_rx = human_bytes(session.received as f64),
+                session_tx = human_bytes(session.transmitted as f64),
+                boot_rx = human_bytes(boot.received as f64),
+                boot_tx = human_bytes(boot.transmitted as f64)
+            )));
+        }
+
+        column.into()
+    }
+}
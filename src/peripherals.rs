@@ -0,0 +1,153 @@
+use crate::fl;
+use crate::layout;
+use crate::power::UPowerProxyBlocking;
+use crate::window::Message;
+use cosmic::iced::Length;
+use cosmic::widget;
+use cosmic::Element;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// Peripheral batteries drain over hours, no need to ask UPower more often
+const REFRESH_EVERY: Duration = Duration::from_secs(60);
+
+/// UPower device types that are not peripherals: unknown, line power, the system's own
+/// battery and UPSes
+const SYSTEM_DEVICE_TYPES: [u32; 4] = [0, 1, 2, 3];
+
+#[zbus::proxy(
+    interface = "org.freedesktop.UPower.Device",
+    default_service = "org.freedesktop.UPower"
+)]
+trait Device {
+    #[zbus(property, name = "Type")]
+    fn kind(&self) -> zbus::Result<u32>;
+
+    #[zbus(property)]
+    fn model(&self) -> zbus::Result<String>;
+
+    #[zbus(property)]
+    fn percentage(&self) -> zbus::Result<f64>;
+
+    /// Whether the device powers the system
+    #[zbus(property)]
+    fn power_supply(&self) -> zbus::Result<bool>;
+}
+
+#[derive(Clone, Debug)]
+pub struct Peripheral {
+    pub model: String,
+    pub percentage: f64,
+}
+
+/// Battery levels of mice, keyboards, headsets and other peripherals known to UPower,
+/// queried on a background thread.
+#[derive(Default)]
+pub struct PeripheralMonitor {
+    devices: Arc<Mutex<Vec<Peripheral>>>,
+    last_run: Option<Instant>,
+}
+
+impl PeripheralMonitor {
+    /// Starts a new query when due, the results show up once it finished.
+    pub fn refresh(&mut self) {
+        if self
+            .last_run
+            .is_some_and(|time| time.elapsed() < REFRESH_EVERY)
+        {
+            return;
+        }
+
+        self.last_run = Some(Instant::now());
+        let devices = self.devices.clone();
+        thread::spawn(move || match query_peripherals() {
+            Ok(peripherals) => {
+                if let Ok(mut devices) = devices.lock() {
+                    *devices = peripherals;
+                }
+            }
+            Err(err) => tracing::warn!("peripheral batteries unavailable: {}", err),
+        });
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.devices
+            .lock()
+            .map_or(true, |devices| devices.is_empty())
+    }
+
+    pub fn view(&self) -> Element<Message> {
+        let mut column = widget::column()
+            .spacing(4)
+            .width(Length::Fill)
+            .push(widget::text(fl!("peripherals")));
+
+        let devices = self
+            .devices
+            .lock()
+            .map(|devices| devices.clone())
+            .unwrap_or_default();
+        for device in devices {
+            let model = if device.model.is_empty() {
+                fl!("peripheral-unknown")
+            } else {
+                device.model
+            };
+            column = column.push(
+                layout::directional_row(vec![
+                    widget::text(model)
+                        .width(Length::Fill)
+                        .horizontal_alignment(layout::start_alignment())
+                        .into(),
+                    widget::text(format!("{:.0}%", device.percentage)).into(),
+                ])
+                .spacing(8),
+            );
+        }
+
+        column.into()
+    }
+}
+
+fn is_peripheral(kind: u32, power_supply: bool) -> bool {
+    !power_supply && !SYSTEM_DEVICE_TYPES.contains(&kind)
+}
+
+/// Peripherals reporting a battery level, emptiest first.
+fn query_peripherals() -> zbus::Result<Vec<Peripheral>> {
+    let connection = zbus::blocking::Connection::system()?;
+    let upower = UPowerProxyBlocking::new(&connection)?;
+
+    let mut peripherals = Vec::new();
+    for path in upower.enumerate_devices()? {
+        let device = DeviceProxyBlocking::builder(&connection)
+            .path(path)?
+            .build()?;
+        if !is_peripheral(device.kind()?, device.power_supply()?) {
+            continue;
+        }
+        peripherals.push(Peripheral {
+            model: device.model()?,
+            percentage: device.percentage()?,
+        });
+    }
+    peripherals.sort_by(|a, b| a.percentage.total_cmp(&b.percentage));
+    Ok(peripherals)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn system_batteries_are_not_peripherals() {
+        // Mouse and headset
+        assert!(is_peripheral(5, false));
+        assert!(is_peripheral(17, false));
+        // Laptop battery, line power and a battery powering the system
+        assert!(!is_peripheral(2, true));
+        assert!(!is_peripheral(1, false));
+        assert!(!is_peripheral(5, true));
+    }
+}
@@ -0,0 +1,321 @@
+use crate::fl;
+use crate::layout;
+use crate::window::Message;
+use cosmic::iced::Length;
+use cosmic::widget;
+use cosmic::Element;
+use human_bytes::human_bytes;
+use serde::Deserialize;
+use std::net::IpAddr;
+use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// The kubelet itself only updates its summary every 10 to 15 seconds
+const REFRESH_EVERY: Duration = Duration::from_secs(15);
+const SHOWN_PODS: usize = 15;
+/// Passed to every `kubectl` call, an unreachable API server would hang it for minutes
+const REQUEST_TIMEOUT: &str = "--request-timeout=5s";
+
+/// Usage of one pod, from the kubelet summary API.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PodUsage {
+    pub namespace: String,
+    pub name: String,
+    pub node: String,
+    /// Thousandths of a CPU core
+    pub cpu_millicores: f64,
+    /// Working set in bytes, what the kubelet uses for evictions
+    pub memory: u64,
+}
+
+#[derive(Clone, Debug)]
+enum PodState {
+    Pending,
+    Unavailable(String),
+    Pods(Vec<PodUsage>),
+}
+
+/// The parts of `/stats/summary` shown in the popup.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Summary {
+    node: NodeStats,
+    #[serde(default)]
+    pods: Vec<PodStats>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct NodeStats {
+    node_name: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PodStats {
+    pod_ref: PodRef,
+    cpu: Option<CpuStats>,
+    memory: Option<MemoryStats>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PodRef {
+    name: String,
+    namespace: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CpuStats {
+    usage_nano_cores: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MemoryStats {
+    working_set_bytes: Option<u64>,
+}
+
+/// Periodically reads the kubelet summary of every node of the configured `kubectl`
+/// context on a background thread, or of the current one if it's a local kind or k3s
+/// cluster.
+pub struct PodMonitor {
+    state: Arc<Mutex<PodState>>,
+    last_run: Option<Instant>,
+    /// Set while a query runs, so a slow API server doesn't pile up threads
+    querying: Arc<AtomicBool>,
+}
+
+impl PodMonitor {
+    pub fn new() -> Self {
+        Self {
+            state: Arc::new(Mutex::new(PodState::Pending)),
+            last_run: None,
+            querying: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Starts a new query when due and the previous one completed.
+    pub fn poll(&mut self, context: &str) {
+        if self
+            .last_run
+            .is_some_and(|time| time.elapsed() < REFRESH_EVERY)
+        {
+            return;
+        }
+        if self.querying.swap(true, Ordering::AcqRel) {
+            return;
+        }
+        self.last_run = Some(Instant::now());
+        let state = self.state.clone();
+        let querying = self.querying.clone();
+        let context = context.to_string();
+        thread::spawn(move || {
+            let result = match query_pods(&context) {
+                Ok(pods) => PodState::Pods(pods),
+                Err(err) => {
+                    tracing::warn!("kubelet summary unavailable: {}", err);
+                    PodState::Unavailable(err)
+                }
+            };
+            if let Ok(mut state) = state.lock() {
+                *state = result;
+            }
+            querying.store(false, Ordering::Release);
+        });
+    }
+
+    pub fn view(&self) -> Element<Message> {
+        let mut column = widget::column()
+            .spacing(4)
+            .width(Length::Fill)
+            .push(widget::text(fl!("pods")));
+
+        let state = self
+            .state
+            .lock()
+            .map(|state| state.clone())
+            .unwrap_or(PodState::Pending);
+        match state {
+            PodState::Pending => column = column.push(widget::text(fl!("loading"))),
+            PodState::Unavailable(err) => {
+                column = column.push(widget::text(fl!("pods-unavailable", error = err)))
+            }
+            PodState::Pods(pods) if pods.is_empty() => {
+                column = column.push(widget::text(fl!("pods-none")))
+            }
+            PodState::Pods(pods) => {
+                // Single node clusters are the common case, don't repeat their name
+                let multi_node = pods.iter().any(|pod| pod.node != pods[0].node);
+                for pod in pods.iter().take(SHOWN_PODS) {
+                    let mut cells: Vec<Element<Message>> =
+                        vec![widget::text(format!("{}/{}", pod.namespace, pod.name))
+                            .width(Length::Fill)
+                            .horizontal_alignment(layout::start_alignment())
+                            .into()];
+                    if multi_node {
+                        cells.push(widget::text(pod.node.as_str()).into());
+                    }
+                    cells.push(widget::text(format!("{:.0}m", pod.cpu_millicores)).into());
+                    cells.push(widget::text(human_bytes(pod.memory as f64)).into());
+                    column = column.push(layout::directional_row(cells).spacing(8));
+                }
+            }
+        }
+
+        column.into()
+    }
+}
+
+fn kubectl(args: &[&str]) -> Result<Vec<u8>, String> {
+    let output = Command::new("kubectl")
+        .arg(REQUEST_TIMEOUT)
+        .args(args)
+        .output()
+        .map_err(|err| format!("kubectl: {}", err))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    Ok(output.stdout)
+}
+
+/// The configured context, or the current one if its API server is on this machine. Never
+/// follows the current context to a remote cluster the user happens to have selected.
+fn resolve_context(configured: &str) -> Result<String, String> {
+    if !configured.is_empty() {
+        return Ok(configured.to_string());
+    }
+    let current = kubectl(&["config", "current-context"])?;
+    let current = String::from_utf8_lossy(&current).trim().to_string();
+    let server = kubectl(&[
+        "config",
+        "view",
+        "--minify",
+        "--context",
+        &current,
+        "--output",
+        "jsonpath={.clusters[0].cluster.server}",
+    ])?;
+    if !is_local_server(String::from_utf8_lossy(&server).trim()) {
+        return Err(format!(
+            "context {} is not a local cluster, set pods_context to list its pods",
+            current
+        ));
+    }
+    Ok(current)
+}
+
+/// Whether an API server URL like `https://127.0.0.1:6443` points at this machine.
+fn is_local_server(server: &str) -> bool {
+    let authority = server
+        .split_once("://")
+        .map_or(server, |(_, rest)| rest)
+        .split('/')
+        .next()
+        .unwrap_or_default();
+    let host = match authority.strip_prefix('[') {
+        Some(bracketed) => bracketed.split(']').next().unwrap_or_default(),
+        None => authority.split(':').next().unwrap_or_default(),
+    };
+    host == "localhost" || host.parse::<IpAddr>().is_ok_and(|ip| ip.is_loopback())
+}
+
+/// Pods of every node, the busiest first. The kubelet port needs client certificates, so
+/// it is reached through the API server proxy with the credentials `kubectl` already has.
+fn query_pods(context: &str) -> Result<Vec<PodUsage>, String> {
+    let context = resolve_context(context)?;
+    let nodes = kubectl(&["--context", &context, "get", "nodes", "--output", "name"])?;
+    let mut pods = Vec::new();
+    for node in String::from_utf8_lossy(&nodes).lines() {
+        let Some(node) = node.trim().strip_prefix("node/") else {
+            continue;
+        };
+        let path = format!("/api/v1/nodes/{}/proxy/stats/summary", node);
+        pods.extend(parse_summary(&kubectl(&[
+            "--context",
+            &context,
+            "get",
+            "--raw",
+            &path,
+        ])?)?);
+    }
+    pods.sort_by(|a, b| b.cpu_millicores.total_cmp(&a.cpu_millicores));
+    Ok(pods)
+}
+
+fn parse_summary(json: &[u8]) -> Result<Vec<PodUsage>, String> {
+    let summary: Summary =
+        serde_json::from_slice(json).map_err(|err| format!("kubelet summary: {}", err))?;
+    Ok(summary
+        .pods
+        .into_iter()
+        .map(|pod| PodUsage {
+            namespace: pod.pod_ref.namespace,
+            name: pod.pod_ref.name,
+            node: summary.node.node_name.clone(),
+            cpu_millicores: pod
+                .cpu
+                .and_then(|cpu| cpu.usage_nano_cores)
+                .unwrap_or_default() as f64
+                / 1_000_000.0,
+            memory: pod
+                .memory
+                .and_then(|memory| memory.working_set_bytes)
+                .unwrap_or_default(),
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_loopback_servers_are_local() {
+        assert!(is_local_server("https://127.0.0.1:6443"));
+        assert!(is_local_server("https://localhost:6443"));
+        assert!(is_local_server("https://[::1]:6443/"));
+        assert!(!is_local_server("https://10.0.0.5:6443"));
+        assert!(!is_local_server("https://prod.example.com"));
+    }
+
+    #[test]
+    fn reads_cpu_and_memory_of_every_pod() {
+        let summary = br#"{
+            "node": {"nodeName": "kind-control-plane", "cpu": {"usageNanoCores": 900000000}},
+            "pods": [
+                {
+                    "podRef": {"name": "coredns-5d78c9869d-8xk2p", "namespace": "kube-system", "uid": "1"},
+                    "cpu": {"time": "2024-05-01T10:00:00Z", "usageNanoCores": 2500000},
+                    "memory": {"time": "2024-05-01T10:00:00Z", "workingSetBytes": 16777216}
+                },
+                {
+                    "podRef": {"name": "web", "namespace": "default", "uid": "2"}
+                }
+            ]
+        }"#;
+
+        assert_eq!(
+            parse_summary(summary).unwrap(),
+            vec![
+                PodUsage {
+                    namespace: "kube-system".to_string(),
+                    name: "coredns-5d78c9869d-8xk2p".to_string(),
+                    node: "kind-control-plane".to_string(),
+                    cpu_millicores: 2.5,
+                    memory: 16_777_216,
+                },
+                PodUsage {
+                    namespace: "default".to_string(),
+                    name: "web".to_string(),
+                    node: "kind-control-plane".to_string(),
+                    cpu_millicores: 0.0,
+                    memory: 0,
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,240 @@
+use crate::alerts::{Alert, Severity};
+use crate::fl;
+use crate::layout;
+use crate::window::Message;
+use cosmic::iced::Length;
+use cosmic::widget;
+use cosmic::Element;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const REFRESH_EVERY: Duration = Duration::from_secs(60);
+const BTRFS_SYSFS: &str = "/sys/fs/btrfs";
+
+#[derive(Clone, Copy, Debug)]
+pub enum PoolKind {
+    Btrfs,
+    Zfs,
+}
+
+#[derive(Clone, Debug)]
+pub struct PoolHealth {
+    pub kind: PoolKind,
+    pub name: String,
+    pub healthy: bool,
+    /// `zpool` health, or the btrfs device error count
+    pub status: String,
+    /// Scrub progress in percent while a scrub is running
+    pub scrub: Option<f64>,
+}
+
+/// Btrfs and ZFS pool health, collected on a background thread.
+pub struct PoolMonitor {
+    pools: Arc<Mutex<Vec<PoolHealth>>>,
+    last_run: Option<Instant>,
+}
+
+impl PoolMonitor {
+    pub fn new() -> Self {
+        Self {
+            pools: Arc::new(Mutex::new(Vec::new())),
+            last_run: None,
+        }
+    }
+
+    /// Starts a new query when due and returns alerts for unhealthy pools.
+    pub fn poll(&mut self) -> Vec<Alert> {
+        if self
+            .last_run
+            .map_or(true, |time| time.elapsed() > REFRESH_EVERY)
+        {
+            self.last_run = Some(Instant::now());
+            let pools = self.pools.clone();
+            thread::spawn(move || {
+                let mut result = btrfs_pools();
+                result.extend(zfs_pools());
+                if let Ok(mut pools) = pools.lock() {
+                    *pools = result;
+                }
+            });
+        }
+
+        self.pools()
+            .into_iter()
+            .filter(|pool| !pool.healthy)
+            .map(|pool| Alert {
+                key: format!("pool-{}", pool.name),
+                summary: fl!("pool-unhealthy-alert", pool = pool.name.as_str()),
+                body: pool.status,
+                severity: Severity::Critical,
+            })
+            .collect()
+    }
+
+    fn pools(&self) -> Vec<PoolHealth> {
+        self.pools
+            .lock()
+            .map(|pools| pools.clone())
+            .unwrap_or_default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pools().is_empty()
+    }
+
+    pub fn view(&self) -> Element<Message> {
+        let mut column = widget::column()
+            .spacing(4)
+            .width(Length::Fill)
+            .push(widget::text(fl!("storage-pools")));
+
+        for pool in self.pools() {
+            let kind = match pool.kind {
+                PoolKind::Btrfs => "btrfs",
+                PoolKind::Zfs => "ZFS",
+            };
+            let mut details = vec![pool.status];
+            if let Some(progress) = pool.scrub {
+                details.push(fl!("pool-scrubbing", progress = format!("{:.1}", progress)));
+            }
+            column = column.push(
+                layout::directional_row(vec![
+                    widget::text(format!("{} ({})", pool.name, kind))
+                        .width(Length::Fill)
+                        .horizontal_alignment(layout::start_alignment())
+                        .into(),
+                    widget::text(details.join(", ")).into(),
+                ])
+                .spacing(8),
+            );
+        }
+
+        column.into()
+    }
+}
+
+fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8(output.stdout).ok()
+}
+
+/// Reads the per-device error counters the kernel exposes in sysfs (5.14+), which
+/// unlike `btrfs device stats` does not need root.
+fn btrfs_pools() -> Vec<PoolHealth> {
+    let Ok(filesystems) = fs::read_dir(BTRFS_SYSFS) else {
+        return Vec::new();
+    };
+
+    filesystems
+        .flatten()
+        .filter(|entry| entry.path().join("devinfo").is_dir())
+        .map(|entry| {
+            let path = entry.path();
+            let uuid = entry.file_name().to_string_lossy().to_string();
+            let label = fs::read_to_string(path.join("label"))
+                .map(|label| label.trim().to_string())
+                .unwrap_or_default();
+            let errors = btrfs_errors(&path);
+            let scrub = btrfs_mount_point(&path)
+                .and_then(|mount| command_output("btrfs", &["scrub", "status", &mount]))
+                .and_then(|status| btrfs_scrub_progress(&status));
+
+            PoolHealth {
+                kind: PoolKind::Btrfs,
+                name: if label.is_empty() { uuid } else { label },
+                healthy: errors == 0,
+                status: fl!("pool-errors", count = errors),
+                scrub,
+            }
+        })
+        .collect()
+}
+
+fn btrfs_errors(path: &Path) -> u64 {
+    let Ok(devices) = fs::read_dir(path.join("devinfo")) else {
+        return 0;
+    };
+    devices
+        .flatten()
+        .filter_map(|device| fs::read_to_string(device.path().join("error_stats")).ok())
+        .flat_map(|stats| {
+            // e.g. "write_errs 0\nread_errs 0\n..."
+            stats
+                .lines()
+                .filter_map(|line| line.split_whitespace().nth(1)?.parse::<u64>().ok())
+                .collect::<Vec<_>>()
+        })
+        .sum()
+}
+
+/// Finds where the filesystem is mounted by matching its member devices against `/proc/mounts`.
+fn btrfs_mount_point(path: &Path) -> Option<String> {
+    let members = fs::read_dir(path.join("devices"))
+        .ok()?
+        .flatten()
+        .map(|device| device.file_name().to_string_lossy().to_string())
+        .collect::<Vec<_>>();
+    let mounts = fs::read_to_string("/proc/mounts").ok()?;
+
+    mounts.lines().find_map(|line| {
+        let mut fields = line.split_whitespace();
+        let device = fields.next()?;
+        let mount = fields.next()?;
+        if fields.next()? != "btrfs" {
+            return None;
+        }
+        let device = fs::canonicalize(device).ok()?;
+        let name = device.file_name()?.to_string_lossy();
+        members
+            .iter()
+            .any(|member| *member == name)
+            .then(|| mount.to_string())
+    })
+}
+
+/// Parses `Bytes scrubbed: 12.00GiB (35.21%)` from a running `btrfs scrub status`.
+fn btrfs_scrub_progress(status: &str) -> Option<f64> {
+    if !status.contains("running") {
+        return None;
+    }
+    percent_in(status)
+}
+
+fn zfs_pools() -> Vec<PoolHealth> {
+    let Some(list) = command_output("zpool", &["list", "-H", "-o", "name,health"]) else {
+        return Vec::new();
+    };
+
+    list.lines()
+        .filter_map(|line| {
+            let (name, health) = line.split_once('\t')?;
+            let scrub = command_output("zpool", &["status", name])
+                .filter(|status| status.contains("scrub in progress"))
+                .and_then(|status| percent_in(&status));
+            Some(PoolHealth {
+                kind: PoolKind::Zfs,
+                name: name.to_string(),
+                healthy: health == "ONLINE",
+                status: health.to_string(),
+                scrub,
+            })
+        })
+        .collect()
+}
+
+/// First `NN.N%` found in the text.
+fn percent_in(text: &str) -> Option<f64> {
+    text.split_whitespace().find_map(|word| {
+        word.trim_matches(|c| c == '(' || c == ')' || c == ',')
+            .strip_suffix('%')?
+            .parse()
+            .ok()
+    })
+}
@@ -0,0 +1,161 @@
+use cosmic::iced::subscription;
+use cosmic::iced_futures::futures::{SinkExt, StreamExt};
+use cosmic::iced_futures::Subscription;
+use std::time::Duration;
+
+// Applied while running on battery, to reduce the applet's own power draw
+pub const BATTERY_SAMPLE_EVERY: Duration = Duration::from_millis(5000);
+
+#[zbus::proxy(
+    interface = "org.freedesktop.UPower",
+    default_service = "org.freedesktop.UPower",
+    default_path = "/org/freedesktop/UPower"
+)]
+pub(crate) trait UPower {
+    #[zbus(property)]
+    fn on_battery(&self) -> zbus::Result<bool>;
+
+    fn enumerate_devices(&self) -> zbus::Result<Vec<zbus::zvariant::OwnedObjectPath>>;
+}
+
+/// Profiles of power-profiles-daemon, in the order they are offered
+pub const POWER_PROFILES: [&str; 3] = ["power-saver", "balanced", "performance"];
+
+#[zbus::proxy(
+    interface = "net.hadess.PowerProfiles",
+    default_service = "net.hadess.PowerProfiles",
+    default_path = "/net/hadess/PowerProfiles"
+)]
+trait PowerProfiles {
+    #[zbus(property)]
+    fn active_profile(&self) -> zbus::Result<String>;
+
+    #[zbus(property)]
+    fn set_active_profile(&self, profile: &str) -> zbus::Result<()>;
+}
+
+#[zbus::proxy(
+    interface = "org.freedesktop.login1.Manager",
+    default_service = "org.freedesktop.login1",
+    default_path = "/org/freedesktop/login1"
+)]
+trait Login1Manager {
+    #[zbus(signal)]
+    fn prepare_for_sleep(&self, start: bool) -> zbus::Result<()>;
+}
+
+/// Emits `true` while the system runs on battery and `false` on AC.
+pub fn on_battery_subscription() -> Subscription<bool> {
+    struct OnBatterySubscription;
+
+    subscription::channel(
+        std::any::TypeId::of::<OnBatterySubscription>(),
+        1,
+        |mut output| async move {
+            if let Err(err) = watch_on_battery(&mut output).await {
+                tracing::warn!("power source monitoring unavailable: {}", err);
+            }
+            std::future::pending().await
+        },
+    )
+}
+
+async fn watch_on_battery(
+    output: &mut cosmic::iced_futures::futures::channel::mpsc::Sender<bool>,
+) -> zbus::Result<()> {
+    let connection = zbus::Connection::system().await?;
+    let upower = UPowerProxy::new(&connection).await?;
+
+    let _ = output.send(upower.on_battery().await?).await;
+
+    let mut changes = upower.receive_on_battery_changed().await;
+    while let Some(change) = changes.next().await {
+        if let Ok(on_battery) = change.get().await {
+            tracing::info!(on_battery, "power source changed");
+            let _ = output.send(on_battery).await;
+        }
+    }
+
+    Ok(())
+}
+
+/// Emits the active power profile whenever it changes.
+pub fn power_profile_subscription() -> Subscription<String> {
+    struct PowerProfileSubscription;
+
+    subscription::channel(
+        std::any::TypeId::of::<PowerProfileSubscription>(),
+        1,
+        |mut output| async move {
+            if let Err(err) = watch_power_profile(&mut output).await {
+                tracing::warn!("power profiles unavailable: {}", err);
+            }
+            std::future::pending().await
+        },
+    )
+}
+
+async fn watch_power_profile(
+    output: &mut cosmic::iced_futures::futures::channel::mpsc::Sender<String>,
+) -> zbus::Result<()> {
+    let connection = zbus::Connection::system().await?;
+    let profiles = PowerProfilesProxy::new(&connection).await?;
+
+    let _ = output.send(profiles.active_profile().await?).await;
+
+    let mut changes = profiles.receive_active_profile_changed().await;
+    while let Some(change) = changes.next().await {
+        if let Ok(profile) = change.get().await {
+            tracing::info!(profile, "power profile changed");
+            let _ = output.send(profile).await;
+        }
+    }
+
+    Ok(())
+}
+
+/// Switches the power profile, the change is reported through [`power_profile_subscription`].
+pub async fn set_power_profile(profile: String) {
+    let result = async {
+        let connection = zbus::Connection::system().await?;
+        let profiles = PowerProfilesProxy::new(&connection).await?;
+        profiles.set_active_profile(&profile).await
+    }
+    .await;
+    if let Err(err) = result {
+        tracing::warn!("failed to set power profile {}: {}", profile, err);
+    }
+}
+
+/// Emits `true` right before the system suspends and `false` once it resumed.
+pub fn sleep_subscription() -> Subscription<bool> {
+    struct SleepSubscription;
+
+    subscription::channel(
+        std::any::TypeId::of::<SleepSubscription>(),
+        1,
+        |mut output| async move {
+            if let Err(err) = watch_sleep(&mut output).await {
+                tracing::warn!("suspend monitoring unavailable: {}", err);
+            }
+            std::future::pending().await
+        },
+    )
+}
+
+async fn watch_sleep(
+    output: &mut cosmic::iced_futures::futures::channel::mpsc::Sender<bool>,
+) -> zbus::Result<()> {
+    let connection = zbus::Connection::system().await?;
+    let manager = Login1ManagerProxy::new(&connection).await?;
+
+    let mut signals = manager.receive_prepare_for_sleep().await?;
+    while let Some(signal) = signals.next().await {
+        if let Ok(args) = signal.args() {
+            tracing::info!(suspending = args.start, "prepare for sleep");
+            let _ = output.send(args.start).await;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,562 @@
+use crate::cgroups;
+use crate::fl;
+use crate::layout;
+use crate::window::Message;
+use cosmic::iced::{Alignment, Length};
+use cosmic::widget;
+use cosmic::Element;
+use human_bytes::human_bytes;
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::PathBuf;
+use std::time::Instant;
+use sysinfo::{ProcessRefreshKind, System};
+
+const SHOWN_PROCESSES: usize = 10;
+/// Per level of the process tree
+const TREE_INDENT: f32 = 16.0;
+const FLATPAK_EXPORTS: &str = "/var/lib/flatpak/exports/share/applications";
+const HOST_NETWORK_NAMESPACE: &str = "/proc/self/ns/net";
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum ProcessSort {
+    Cpu,
+    #[default]
+    Memory,
+    /// Disk reads and writes combined
+    Disk,
+    Name,
+}
+
+/// A process, or all processes of a Flatpak application.
+#[derive(Clone, Debug)]
+pub struct ProcessEntry {
+    pub name: String,
+    pub pid: Option<u32>,
+    /// Parent process, `None` for applications
+    pub parent: Option<u32>,
+    pub app_id: Option<String>,
+    /// Share of one core, summed for applications
+    pub cpu: f32,
+    pub memory: u64,
+    /// Bytes per second read from disk, from `/proc/<pid>/io`
+    pub read_rate: f64,
+    /// Bytes per second written to disk
+    pub write_rate: f64,
+    /// Bytes per second received and sent, only known for processes in a network
+    /// namespace of their own, like containers and sandboxes. Linux doesn't account
+    /// traffic per process otherwise.
+    pub network_rate: Option<(f64, f64)>,
+    pub count: usize,
+}
+
+/// Running processes, with Flatpak sandboxes folded into one entry per app.
+pub struct ProcessList {
+    sys: System,
+    entries: Vec<ProcessEntry>,
+    app_names: HashMap<String, String>,
+    last_refresh: Option<Instant>,
+    /// Received and sent bytes per network namespace at the previous refresh
+    network_counters: HashMap<PathBuf, (u64, u64)>,
+}
+
+impl ProcessList {
+    pub fn new() -> Self {
+        Self {
+            sys: System::new(),
+            entries: Vec::new(),
+            app_names: HashMap::new(),
+            last_refresh: None,
+            network_counters: HashMap::new(),
+        }
+    }
+
+    /// Forgets the previous I/O counters, so the next refresh does not report a rate
+    /// spanning e.g. a suspend.
+    pub fn reset_rates(&mut self) {
+        self.last_refresh = None;
+    }
+
+    pub fn refresh(&mut self) {
+        self.sys.refresh_processes_specifics(
+            ProcessRefreshKind::new()
+                .with_cpu()
+                .with_memory()
+                .with_disk_usage(),
+        );
+        let now = Instant::now();
+        // The first refresh has no previous counters, so it reports no rates
+        let elapsed = self
+            .last_refresh
+            .map(|time| now.duration_since(time).as_secs_f64())
+            .unwrap_or_default();
+        self.last_refresh = Some(now);
+        let rate = |bytes: u64| {
+            if elapsed > 0.0 {
+                bytes as f64 / elapsed
+            } else {
+                0.0
+            }
+        };
+
+        let network = self.namespace_rates(&rate);
+
+        let mut apps: HashMap<String, ProcessEntry> = HashMap::new();
+        let mut entries = Vec::new();
+        for (pid, process) in self.sys.processes() {
+            // Threads show up as processes on Linux, only count the thread group leader
+            if process.thread_kind().is_some() {
+                continue;
+            }
+
+            let pid = pid.as_u32();
+            let disk = process.disk_usage();
+            match cgroups::flatpak_app_id(pid) {
+                Some(app_id) => {
+                    let entry = apps.entry(app_id.clone()).or_insert_with(|| ProcessEntry {
+                        name: app_id.clone(),
+                        pid: None,
+                        parent: None,
+                        app_id: Some(app_id),
+                        cpu: 0.0,
+                        memory: 0,
+                        read_rate: 0.0,
+                        write_rate: 0.0,
+                        network_rate: None,
+                        count: 0,
+                    });
+                    entry.cpu += process.cpu_usage();
+                    entry.memory += process.memory();
+                    entry.read_rate += rate(disk.read_bytes);
+                    entry.write_rate += rate(disk.written_bytes);
+                    if let Some((rx, tx)) = network.get(&pid) {
+                        let (total_rx, total_tx) = entry.network_rate.get_or_insert((0.0, 0.0));
+                        *total_rx += rx;
+                        *total_tx += tx;
+                    }
+                    entry.count += 1;
+                }
+                None => entries.push(ProcessEntry {
+                    name: process.name().to_string(),
+                    pid: Some(pid),
+                    parent: process.parent().map(|parent| parent.as_u32()),
+                    app_id: None,
+                    cpu: process.cpu_usage(),
+                    memory: process.memory(),
+                    read_rate: rate(disk.read_bytes),
+                    write_rate: rate(disk.written_bytes),
+                    network_rate: network.get(&pid).copied(),
+                    count: 1,
+                }),
+            }
+        }
+
+        for mut app in apps.into_values() {
+            if let Some(app_id) = &app.app_id {
+                app.name = self
+                    .app_names
+                    .entry(app_id.clone())
+                    .or_insert_with(|| desktop_name(app_id).unwrap_or_else(|| app_id.clone()))
+                    .clone();
+            }
+            entries.push(app);
+        }
+
+        self.entries = entries;
+    }
+
+    /// Traffic of every network namespace other than the host's, by the lowest PID in
+    /// it, so a container's traffic is counted once.
+    fn namespace_rates(&mut self, rate: &dyn Fn(u64) -> f64) -> HashMap<u32, (f64, f64)> {
+        let Ok(host) = fs::read_link(HOST_NETWORK_NAMESPACE) else {
+            return HashMap::new();
+        };
+        let mut leaders: HashMap<PathBuf, u32> = HashMap::new();
+        for pid in self.sys.processes().keys() {
+            let pid = pid.as_u32();
+            // Unreadable for processes of other users
+            let Ok(namespace) = fs::read_link(format!("/proc/{}/ns/net", pid)) else {
+                continue;
+            };
+            if namespace != host {
+                let leader = leaders.entry(namespace).or_insert(pid);
+                *leader = (*leader).min(pid);
+            }
+        }
+
+        let mut counters = HashMap::new();
+        let mut rates = HashMap::new();
+        for (namespace, pid) in leaders {
+            let Ok(dev) = fs::read_to_string(format!("/proc/{}/net/dev", pid)) else {
+                continue;
+            };
+            let (rx, tx) = parse_net_dev(&dev);
+            if let Some((previous_rx, previous_tx)) = self.network_counters.get(&namespace) {
+                rates.insert(
+                    pid,
+                    (
+                        rate(rx.saturating_sub(*previous_rx)),
+                        rate(tx.saturating_sub(*previous_tx)),
+                    ),
+                );
+            }
+            counters.insert(namespace, (rx, tx));
+        }
+        self.network_counters = counters;
+        rates
+    }
+
+    /// Shows the top entries matching `filter` by name or PID, ordered by `sort`. With
+    /// `tree`, processes are nested under their parent, showing the children of the
+    /// `expanded` PIDs.
+    pub fn view<'a>(
+        &'a self,
+        filter: &'a str,
+        sort: ProcessSort,
+        tree: bool,
+        expanded: &BTreeSet<u32>,
+    ) -> Element<'a, Message> {
+        let mut sort_row: Vec<Element<Message>> = vec![widget::text(fl!("top-processes"))
+            .width(Length::Fill)
+            .horizontal_alignment(layout::start_alignment())
+            .into()];
+        for (option, label) in [
+            (ProcessSort::Cpu, fl!("sort-cpu")),
+            (ProcessSort::Memory, fl!("sort-memory")),
+            (ProcessSort::Disk, fl!("sort-disk")),
+            (ProcessSort::Name, fl!("sort-name")),
+        ] {
+            let button = if option == sort {
+                widget::button::suggested(label)
+            } else {
+                widget::button::standard(label)
+            };
+            sort_row.push(button.on_press(Message::SortProcesses(option)).into());
+        }
+        let tree_button = if tree {
+            widget::button::suggested(fl!("process-tree"))
+        } else {
+            widget::button::standard(fl!("process-tree"))
+        };
+        sort_row.push(tree_button.on_press(Message::ProcessTree(!tree)).into());
+        let sort_row = layout::directional_row(sort_row)
+            .spacing(8)
+            .align_items(Alignment::Center);
+
+        let mut column = widget::column()
+            .spacing(4)
+            .width(Length::Fill)
+            .push(sort_row)
+            .push(
+                widget::search_input(fl!("filter-processes"), filter)
+                    .on_input(Message::FilterProcesses)
+                    .on_clear(Message::FilterProcesses(String::new())),
+            );
+
+        let filter = filter.trim().to_lowercase();
+        if tree {
+            let mut nodes = build_tree(&self.entries);
+            if !filter.is_empty() {
+                nodes = filter_tree(nodes, &filter);
+            }
+            sort_tree(&mut nodes, sort);
+            for node in nodes.iter().take(SHOWN_PROCESSES) {
+                // Matches are revealed wherever they are nested
+                column = push_node(column, node, 0, |pid| {
+                    !filter.is_empty() || expanded.contains(&pid)
+                });
+            }
+            return column.into();
+        }
+
+        let mut entries = self
+            .entries
+            .iter()
+            .filter(|entry| matches(entry, &filter))
+            .collect::<Vec<_>>();
+        entries.sort_by(|a, b| compare(sort, a, b));
+
+        for entry in entries.into_iter().take(SHOWN_PROCESSES) {
+            column = column.push(entry_row(entry, 0, None));
+        }
+
+        column.into()
+    }
+}
+
+/// A process with the usage of all its descendants added up, for the tree view.
+struct TreeNode {
+    /// Sums of the whole subtree, `count` is the number of processes in it
+    total: ProcessEntry,
+    children: Vec<TreeNode>,
+}
+
+/// Nests the entries under their parents; entries whose parent is not listed, and
+/// applications, become roots.
+fn build_tree(entries: &[ProcessEntry]) -> Vec<TreeNode> {
+    let pids = entries
+        .iter()
+        .filter_map(|entry| entry.pid)
+        .collect::<BTreeSet<_>>();
+    let mut children: HashMap<u32, Vec<&ProcessEntry>> = HashMap::new();
+    let mut roots = Vec::new();
+    for entry in entries {
+        match entry.parent.filter(|parent| pids.contains(parent)) {
+            Some(parent) => children.entry(parent).or_default().push(entry),
+            None => roots.push(entry),
+        }
+    }
+
+    fn node(entry: &ProcessEntry, children: &HashMap<u32, Vec<&ProcessEntry>>) -> TreeNode {
+        let nested = entry
+            .pid
+            .and_then(|pid| children.get(&pid))
+            .map(|nested| {
+                nested
+                    .iter()
+                    .map(|child| node(child, children))
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        let mut total = entry.clone();
+        for child in &nested {
+            total.cpu += child.total.cpu;
+            total.memory += child.total.memory;
+            total.read_rate += child.total.read_rate;
+            total.write_rate += child.total.write_rate;
+            if let Some((rx, tx)) = child.total.network_rate {
+                let (total_rx, total_tx) = total.network_rate.get_or_insert((0.0, 0.0));
+                *total_rx += rx;
+                *total_tx += tx;
+            }
+            total.count += child.total.count;
+        }
+        TreeNode {
+            total,
+            children: nested,
+        }
+    }
+
+    roots
+        .into_iter()
+        .map(|root| node(root, &children))
+        .collect()
+}
+
+/// Keeps the nodes matching `filter` and the ancestors of matching nodes.
+fn filter_tree(nodes: Vec<TreeNode>, filter: &str) -> Vec<TreeNode> {
+    nodes
+        .into_iter()
+        .filter_map(|mut node| {
+            node.children = filter_tree(node.children, filter);
+            (matches(&node.total, filter) || !node.children.is_empty()).then_some(node)
+        })
+        .collect()
+}
+
+fn sort_tree(nodes: &mut [TreeNode], sort: ProcessSort) {
+    nodes.sort_by(|a, b| compare(sort, &a.total, &b.total));
+    for node in nodes {
+        sort_tree(&mut node.children, sort);
+    }
+}
+
+fn push_node<'a>(
+    mut column: widget::Column<'a, Message>,
+    node: &TreeNode,
+    depth: u16,
+    expanded: impl Fn(u32) -> bool + Copy,
+) -> widget::Column<'a, Message> {
+    let toggle = node
+        .total
+        .pid
+        .filter(|_| !node.children.is_empty())
+        .map(|pid| (pid, expanded(pid)));
+    column = column.push(entry_row(&node.total, depth, toggle));
+    if toggle.is_some_and(|(_, expanded)| expanded) {
+        for child in &node.children {
+            column = push_node(column, child, depth + 1, expanded);
+        }
+    }
+    column
+}
+
+/// `filter` is expected in lower case.
+fn matches(entry: &ProcessEntry, filter: &str) -> bool {
+    filter.is_empty()
+        || entry.name.to_lowercase().contains(filter)
+        || entry.pid.is_some_and(|pid| pid.to_string() == filter)
+}
+
+fn compare(sort: ProcessSort, a: &ProcessEntry, b: &ProcessEntry) -> std::cmp::Ordering {
+    match sort {
+        ProcessSort::Cpu => b.cpu.total_cmp(&a.cpu),
+        ProcessSort::Memory => b.memory.cmp(&a.memory),
+        ProcessSort::Disk => (b.read_rate + b.write_rate).total_cmp(&(a.read_rate + a.write_rate)),
+        ProcessSort::Name => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+    }
+}
+
+/// One entry, indented by `depth` and with a button expanding or collapsing the
+/// children of the PID in `toggle`.
+fn entry_row<'a>(
+    entry: &ProcessEntry,
+    depth: u16,
+    toggle: Option<(u32, bool)>,
+) -> Element<'a, Message> {
+    let icon = widget::icon::from_name(
+        entry
+            .app_id
+            .as_deref()
+            .unwrap_or("application-x-executable-symbolic"),
+    )
+    .size(16);
+    let name = if entry.count > 1 {
+        fl!(
+            "process-group",
+            name = entry.name.as_str(),
+            count = entry.count
+        )
+    } else {
+        entry.name.clone()
+    };
+
+    let mut cells: Vec<Element<Message>> = Vec::new();
+    if depth > 0 {
+        cells.push(widget::horizontal_space(Length::Fixed(f32::from(depth) * TREE_INDENT)).into());
+    }
+    if let Some((pid, expanded)) = toggle {
+        let label = if expanded { "▾" } else { "▸" };
+        cells.push(
+            widget::button::standard(label)
+                .on_press(Message::ExpandProcess(pid, !expanded))
+                .into(),
+        );
+    }
+    cells.push(icon.into());
+    cells.push(
+        widget::text(name)
+            .width(Length::Fill)
+            .horizontal_alignment(layout::start_alignment())
+            .into(),
+    );
+    cells.push(widget::text(format!("{:.1}%", entry.cpu)).into());
+    cells.push(widget::text(human_bytes(entry.memory as f64)).into());
+    cells.push(
+        widget::text(fl!(
+            "disk-rates",
+            read = human_bytes(entry.read_rate),
+            write = human_bytes(entry.write_rate)
+        ))
+        .into(),
+    );
+    if let Some((rx, tx)) = entry.network_rate {
+        cells.push(
+            widget::text(fl!(
+                "process-network-rates",
+                rx = human_bytes(rx),
+                tx = human_bytes(tx)
+            ))
+            .into(),
+        );
+    }
+    layout::directional_row(cells)
+        .spacing(8)
+        .align_items(Alignment::Center)
+        .into()
+}
+
+/// Received and sent bytes of all interfaces but loopback in `/proc/<pid>/net/dev`.
+fn parse_net_dev(dev: &str) -> (u64, u64) {
+    dev.lines()
+        .skip(2)
+        .filter_map(|line| {
+            let (interface, counters) = line.split_once(':')?;
+            if interface.trim() == "lo" {
+                return None;
+            }
+            let counters = counters
+                .split_whitespace()
+                .map(|field| field.parse::<u64>().ok())
+                .collect::<Vec<_>>();
+            // Received bytes come first, sent bytes after the eight receive counters
+            Some((counters.first().copied()??, counters.get(8).copied()??))
+        })
+        .fold((0, 0), |(rx, tx), (r, t)| (rx + r, tx + t))
+}
+
+fn desktop_file(app_id: &str) -> Option<PathBuf> {
+    let file = format!("{}.desktop", app_id);
+    let user_exports =
+        dirs::data_dir().map(|dir| dir.join("flatpak/exports/share/applications").join(&file));
+    [
+        Some(PathBuf::from(FLATPAK_EXPORTS).join(&file)),
+        user_exports,
+    ]
+    .into_iter()
+    .flatten()
+    .find(|path| path.exists())
+}
+
+/// Display name from the app's exported desktop entry.
+fn desktop_name(app_id: &str) -> Option<String> {
+    let contents = fs::read_to_string(desktop_file(app_id)?).ok()?;
+    contents
+        .lines()
+        .skip_while(|line| line.trim() != "[Desktop Entry]")
+        .find_map(|line| line.strip_prefix("Name="))
+        .map(|name| name.trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn process(pid: u32, parent: u32, name: &str, cpu: f32) -> ProcessEntry {
+        ProcessEntry {
+            name: name.to_string(),
+            pid: Some(pid),
+            parent: Some(parent),
+            app_id: None,
+            cpu,
+            memory: 0,
+            read_rate: 0.0,
+            write_rate: 0.0,
+            network_rate: None,
+            count: 1,
+        }
+    }
+
+    #[test]
+    fn tree_adds_up_the_children() {
+        let entries = vec![
+            process(10, 1, "ninja", 1.0),
+            process(11, 10, "cc1plus", 90.0),
+            process(12, 10, "cc1plus", 80.0),
+            process(20, 1, "bash", 0.5),
+        ];
+
+        let mut tree = build_tree(&entries);
+        sort_tree(&mut tree, ProcessSort::Cpu);
+        assert_eq!(tree.len(), 2);
+        assert_eq!(tree[0].total.name, "ninja");
+        assert_eq!(tree[0].total.count, 3);
+        assert_eq!(tree[0].total.cpu, 171.0);
+        assert_eq!(tree[0].children.len(), 2);
+
+        let filtered = filter_tree(build_tree(&entries), "cc1plus");
+        assert_eq!(filtered.len(), 1);
+        assert_eq!(filtered[0].total.name, "ninja");
+    }
+
+    #[test]
+    fn sums_namespace_traffic_without_loopback() {
+        let dev = "\
+Inter-|   Receive                                                |  Transmit
+ face |bytes    packets errs drop fifo frame compressed multicast|bytes    packets errs drop fifo colls carrier compressed
+    lo:    1000      10    0    0    0     0          0         0     1000      10    0    0    0     0       0          0
+  eth0: 5242880    4000    0    0    0     0          0         0   102400     900    0    0    0     0       0          0
+";
+        assert_eq!(parse_net_dev(dev), (5242880, 102400));
+    }
+}
@@ -0,0 +1,78 @@
+use crate::events::EventLog;
+use crate::fl;
+use crate::metrics::Sample;
+use chrono::{DateTime, Local};
+use std::fmt::Write;
+
+/// Current values and recent events as Markdown, for pasting into support threads.
+pub fn markdown(samples: &[Sample], events: &EventLog, now: DateTime<Local>) -> String {
+    let mut report = String::new();
+    let _ = writeln!(report, "## {}", fl!("report-title"));
+    let _ = writeln!(report, "{}", now.format("%Y-%m-%d %H:%M:%S %:z"));
+
+    let _ = writeln!(report, "\n### {}", fl!("report-metrics"));
+    for sample in samples {
+        let values = sample
+            .series
+            .iter()
+            .map(|(id, value)| {
+                let value = format!("{:.1}{}", value, sample.scale.unit);
+                // Single series sources are named by their title already
+                if sample.series.len() == 1 {
+                    value
+                } else {
+                    format!("{} {}", id, value)
+                }
+            })
+            .collect::<Vec<_>>();
+        let values = if values.is_empty() {
+            fl!("report-unavailable")
+        } else {
+            values.join(", ")
+        };
+        let _ = writeln!(report, "- {}: {}", sample.title, values);
+    }
+
+    let mut events = events.iter().peekable();
+    if events.peek().is_some() {
+        let _ = writeln!(report, "\n### {}", fl!("events"));
+        for event in events {
+            let _ = writeln!(report, "- {}", event.describe("%Y-%m-%d %H:%M"));
+        }
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::{Scale, SeriesId};
+    use chrono::TimeZone;
+
+    #[test]
+    fn lists_every_source() {
+        let samples = vec![
+            Sample {
+                source: "CPU".to_string(),
+                title: "CPU".to_string(),
+                scale: Scale::percent(),
+                series: vec![(SeriesId::new("cpu"), 12.34)],
+            },
+            Sample {
+                source: "Memory".to_string(),
+                title: "Memory".to_string(),
+                scale: Scale::auto(" GiB"),
+                series: vec![
+                    (SeriesId::new("memory_used"), 3.2),
+                    (SeriesId::new("memory_total"), 16.0),
+                ],
+            },
+        ];
+        let now = Local.with_ymd_and_hms(2024, 5, 2, 14, 30, 0).unwrap();
+
+        let report = markdown(&samples, &EventLog::default(), now);
+
+        assert!(report.contains("- CPU: 12.3%\n"));
+        assert!(report.contains("- Memory: memory_used 3.2 GiB, memory_total 16.0 GiB\n"));
+    }
+}
@@ -0,0 +1,384 @@
+use crate::alerts::{Alert, Severity};
+use crate::asahi;
+use crate::config::{Config, SensorLabel};
+use crate::fl;
+use crate::layout;
+use crate::virt;
+use crate::window::Message;
+use cosmic::iced::{Alignment, Color, Length};
+use cosmic::widget;
+use cosmic::Element;
+use std::collections::{BTreeMap, VecDeque};
+use std::fs;
+use std::path::Path;
+
+pub const ALERT_PREFIX: &str = "temperature-";
+const HWMON: &str = "/sys/class/hwmon";
+/// Readings of the hottest sensor kept for the sparkline strip, one per refresh
+const HOTTEST_SAMPLES: usize = 60;
+
+/// How close a reading is to the limits of its sensor.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub enum Level {
+    Normal,
+    High,
+    Critical,
+}
+
+#[derive(Clone, Debug)]
+pub struct Reading {
+    /// Chip and sensor label as reported by the hardware, e.g. `k10temp Tctl`
+    pub id: String,
+    pub celsius: f32,
+    /// `temp*_max` of the sensor
+    pub max: Option<f32>,
+    /// `temp*_crit` of the sensor
+    pub critical: Option<f32>,
+}
+
+impl Reading {
+    /// Compares against the hardware limits, the configured `temperature_alert` is only
+    /// used for sensors that do not report any.
+    pub fn level(&self, config: &Config) -> Level {
+        if self
+            .critical
+            .is_some_and(|critical| self.celsius >= critical)
+        {
+            Level::Critical
+        } else if self.max.is_some_and(|max| self.celsius >= max) {
+            Level::High
+        } else if self.max.is_none()
+            && self.critical.is_none()
+            && config.temperature_alert > 0
+            && self.celsius >= config.temperature_alert as f32
+        {
+            Level::High
+        } else {
+            Level::Normal
+        }
+    }
+}
+
+/// Temperatures of the hwmon sensors.
+#[derive(Default)]
+pub struct SensorMonitor {
+    readings: Vec<Reading>,
+    /// Temperature of the hottest sensor at every refresh, newest first
+    hottest: VecDeque<f32>,
+}
+
+impl SensorMonitor {
+    /// Reads the sensors and returns alerts for the ones at or above their limits.
+    pub fn refresh(&mut self, config: &Config) -> Vec<Alert> {
+        self.readings = read_hwmon(virt::is_guest(config));
+        self.readings.sort_by(|a, b| a.id.cmp(&b.id));
+        if let Some(hottest) = self.hottest() {
+            self.hottest.push_front(hottest);
+            self.hottest.truncate(HOTTEST_SAMPLES);
+        }
+
+        self.readings
+            .iter()
+            .filter_map(|reading| {
+                let (level, severity, limit) = match reading.level(config) {
+                    Level::Normal => return None,
+                    Level::High => (
+                        "high",
+                        Severity::Warning,
+                        reading.max.unwrap_or(config.temperature_alert as f32),
+                    ),
+                    Level::Critical => ("critical", Severity::Critical, reading.critical?),
+                };
+                Some(Alert {
+                    // Escalating from high to critical notifies again
+                    key: format!("{}{}-{}", ALERT_PREFIX, reading.id, level),
+                    summary: fl!(
+                        "temperature-alert",
+                        sensor = display_name(config, &reading.id),
+                        temperature = format!("{:.0}", reading.celsius)
+                    ),
+                    body: fl!(
+                        "temperature-limit",
+                        sensor = reading.id.as_str(),
+                        limit = format!("{:.0}", limit)
+                    ),
+                    severity,
+                })
+            })
+            .collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.readings.is_empty()
+    }
+
+    /// Temperature of the hottest sensor.
+    pub fn hottest(&self) -> Option<f32> {
+        self.readings
+            .iter()
+            .map(|reading| reading.celsius)
+            .max_by(f32::total_cmp)
+    }
+
+    /// Recent temperatures of the hottest sensor, newest first.
+    pub fn hottest_history(&self) -> Vec<f64> {
+        self.hottest.iter().map(|celsius| *celsius as f64).collect()
+    }
+
+    pub fn view<'a>(
+        &'a self,
+        config: &'a Config,
+        theme: &cosmic::cosmic_theme::Theme,
+        editing: bool,
+    ) -> Element<'a, Message> {
+        let header = layout::directional_row(vec![
+            widget::text(fl!("temperatures"))
+                .width(Length::Fill)
+                .horizontal_alignment(layout::start_alignment())
+                .into(),
+            widget::button::standard(if editing {
+                fl!("done")
+            } else {
+                fl!("rename-sensors")
+            })
+            .on_press(Message::EditSensors(!editing))
+            .into(),
+        ])
+        .spacing(8)
+        .align_items(Alignment::Center);
+        let mut column = widget::column().spacing(4).width(Length::Fill).push(header);
+
+        if editing {
+            for reading in &self.readings {
+                let label = config.sensor_labels.get(&reading.id);
+                let id = reading.id.clone();
+                let group_id = reading.id.clone();
+                column = column.push(
+                    layout::directional_row(vec![
+                        widget::text(reading.id.as_str())
+                            .width(Length::Fill)
+                            .horizontal_alignment(layout::start_alignment())
+                            .into(),
+                        widget::text_input(
+                            reading.id.as_str(),
+                            label.map_or("", |label| label.name.as_str()),
+                        )
+                        .on_input(move |name| Message::RenameSensor(id.clone(), name))
+                        .width(Length::Fixed(140.0))
+                        .into(),
+                        widget::text_input(
+                            fl!("sensor-group"),
+                            label.map_or("", |label| label.group.as_str()),
+                        )
+                        .on_input(move |group| Message::RegroupSensor(group_id.clone(), group))
+                        .width(Length::Fixed(100.0))
+                        .into(),
+                    ])
+                    .spacing(8)
+                    .align_items(Alignment::Center),
+                );
+            }
+            return column.into();
+        }
+
+        // Ungrouped sensors sort first, under no heading
+        let mut groups: BTreeMap<&str, Vec<&Reading>> = BTreeMap::new();
+        for reading in &self.readings {
+            let group = config
+                .sensor_labels
+                .get(&reading.id)
+                .map_or("", |label| label.group.as_str());
+            groups.entry(group).or_default().push(reading);
+        }
+        for (group, readings) in groups {
+            if !group.is_empty() {
+                column = column.push(widget::text(group));
+            }
+            for reading in readings {
+                let mut value = widget::text(format!("{:.0}°C", reading.celsius));
+                match reading.level(config) {
+                    Level::Normal => {}
+                    Level::High => {
+                        value = value.style(cosmic::theme::Text::Color(Color::from(
+                            theme.warning_color(),
+                        )))
+                    }
+                    Level::Critical => {
+                        value = value.style(cosmic::theme::Text::Color(Color::from(
+                            theme.destructive_color(),
+                        )))
+                    }
+                }
+                column = column.push(
+                    layout::directional_row(vec![
+                        widget::text(display_name(config, &reading.id))
+                            .width(Length::Fill)
+                            .horizontal_alignment(layout::start_alignment())
+                            .into(),
+                        value.into(),
+                    ])
+                    .spacing(8),
+                );
+            }
+        }
+
+        column.into()
+    }
+}
+
+/// The user's name for a sensor, falling back to the hardware label.
+pub fn display_name(config: &Config, id: &str) -> String {
+    config
+        .sensor_labels
+        .get(id)
+        .map(|label| label.name.trim())
+        .filter(|name| !name.is_empty())
+        .unwrap_or(id)
+        .to_string()
+}
+
+/// Returns the labels with the sensor's entry updated, dropping it once it is back to defaults.
+pub fn update_label(
+    config: &Config,
+    id: String,
+    update: impl FnOnce(&mut SensorLabel),
+) -> BTreeMap<String, SensorLabel> {
+    let mut labels = config.sensor_labels.clone();
+    let label = labels.entry(id.clone()).or_default();
+    update(label);
+    if label.name.is_empty() && label.group.is_empty() {
+        labels.remove(&id);
+    }
+    labels
+}
+
+/// Inside a virtual machine only chips of passed through devices are read, the ones the
+/// hypervisor emulates (e.g. `acpitz`) report made up values.
+fn read_hwmon(skip_emulated: bool) -> Vec<Reading> {
+    let Ok(chips) = fs::read_dir(HWMON) else {
+        return Vec::new();
+    };
+
+    let mut readings = Vec::new();
+    for chip in chips.flatten() {
+        let path = chip.path();
+        if skip_emulated && is_emulated(&path) {
+            continue;
+        }
+        let chip_name = read_string(&path.join("name")).unwrap_or_default();
+        let Ok(files) = fs::read_dir(&path) else {
+            continue;
+        };
+        for file in files.flatten() {
+            let file_name = file.file_name();
+            let Some(sensor) = file_name
+                .to_str()
+                .and_then(|name| name.strip_suffix("_input"))
+                .filter(|name| name.starts_with("temp"))
+            else {
+                continue;
+            };
+            let Some(celsius) = read_millidegrees(&file.path()) else {
+                continue;
+            };
+            let mut label = read_string(&path.join(format!("{}_label", sensor)))
+                .unwrap_or_else(|| sensor.to_string());
+            if asahi::is_smc(&chip_name) {
+                label = asahi::sensor_name(&label).unwrap_or(label);
+            }
+
+            readings.push(Reading {
+                id: format!("{} {}", chip_name, label).trim().to_string(),
+                celsius,
+                max: read_limit(&path.join(format!("{}_max", sensor))),
+                critical: read_limit(&path.join(format!("{}_crit", sensor))),
+            });
+        }
+    }
+    readings
+}
+
+/// Chips without a real device behind them live under `/sys/devices/virtual`.
+fn is_emulated(chip: &Path) -> bool {
+    fs::canonicalize(chip).map_or(true, |path| path.starts_with("/sys/devices/virtual"))
+}
+
+fn read_string(path: &Path) -> Option<String> {
+    Some(fs::read_to_string(path).ok()?.trim().to_string())
+}
+
+/// hwmon reports millidegrees Celsius
+fn read_millidegrees(path: &Path) -> Option<f32> {
+    let millidegrees: i64 = read_string(path)?.parse().ok()?;
+    Some(millidegrees as f32 / 1000.0)
+}
+
+/// Drivers without a limit often report 0 instead of leaving the file out
+fn read_limit(path: &Path) -> Option<f32> {
+    read_millidegrees(path).filter(|limit| *limit > 0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reading(celsius: f32, max: Option<f32>, critical: Option<f32>) -> Reading {
+        Reading {
+            id: "k10temp Tctl".to_string(),
+            celsius,
+            max,
+            critical,
+        }
+    }
+
+    #[test]
+    fn level_follows_hardware_limits() {
+        let config = Config::default();
+
+        assert_eq!(
+            reading(60.0, Some(80.0), Some(95.0)).level(&config),
+            Level::Normal
+        );
+        assert_eq!(
+            reading(85.0, Some(80.0), Some(95.0)).level(&config),
+            Level::High
+        );
+        assert_eq!(
+            reading(95.0, Some(80.0), Some(95.0)).level(&config),
+            Level::Critical
+        );
+        assert_eq!(
+            reading(99.0, None, Some(95.0)).level(&config),
+            Level::Critical
+        );
+    }
+
+    #[test]
+    fn configured_limit_only_applies_without_hardware_limits() {
+        let config = Config {
+            temperature_alert: 70,
+            ..Default::default()
+        };
+
+        assert_eq!(reading(75.0, None, None).level(&config), Level::High);
+        assert_eq!(
+            reading(75.0, Some(80.0), None).level(&config),
+            Level::Normal
+        );
+    }
+
+    #[test]
+    fn display_name_prefers_the_friendly_name() {
+        let mut config = Config::default();
+        config.sensor_labels.insert(
+            "k10temp Tctl".to_string(),
+            SensorLabel {
+                name: "CPU".to_string(),
+                group: String::new(),
+            },
+        );
+
+        assert_eq!(display_name(&config, "k10temp Tctl"), "CPU");
+        assert_eq!(display_name(&config, "nvme Composite"), "nvme Composite");
+    }
+}
@@ -0,0 +1,201 @@
+use crate::alerts::{Alert, Severity};
+use crate::fl;
+use crate::layout;
+use crate::window::Message;
+use cosmic::iced::Length;
+use cosmic::widget;
+use cosmic::Element;
+use serde_json::Value;
+use std::process::Command;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// SMART data changes slowly and querying it can wake up sleeping disks
+const REFRESH_EVERY: Duration = Duration::from_secs(10 * 60);
+const REALLOCATED_SECTOR_COUNT: u64 = 5;
+const WEAR_LEVELING_COUNT: u64 = 177;
+const SSD_LIFE_LEFT: u64 = 231;
+
+#[derive(Clone, Debug)]
+pub struct DriveHealth {
+    pub device: String,
+    pub model: String,
+    pub passed: bool,
+    pub reallocated_sectors: Option<u64>,
+    /// Percentage of the rated endurance already used
+    pub wear: Option<u64>,
+}
+
+#[derive(Clone, Debug)]
+enum SmartState {
+    Pending,
+    Unavailable(String),
+    Drives(Vec<DriveHealth>),
+}
+
+/// Periodically queries `smartctl --json` on a background thread.
+pub struct SmartMonitor {
+    state: Arc<Mutex<SmartState>>,
+    last_run: Option<Instant>,
+}
+
+impl SmartMonitor {
+    pub fn new() -> Self {
+        Self {
+            state: Arc::new(Mutex::new(SmartState::Pending)),
+            last_run: None,
+        }
+    }
+
+    /// Starts a new query when due and returns alerts for drives reporting failing health.
+    pub fn poll(&mut self) -> Vec<Alert> {
+        if self
+            .last_run
+            .map_or(true, |time| time.elapsed() > REFRESH_EVERY)
+        {
+            self.last_run = Some(Instant::now());
+            let state = self.state.clone();
+            thread::spawn(move || {
+                let result = match query_drives() {
+                    Ok(drives) => SmartState::Drives(drives),
+                    Err(err) => {
+                        tracing::warn!("SMART data unavailable: {}", err);
+                        SmartState::Unavailable(err)
+                    }
+                };
+                if let Ok(mut state) = state.lock() {
+                    *state = result;
+                }
+            });
+        }
+
+        self.drives()
+            .into_iter()
+            .filter(|drive| !drive.passed)
+            .map(|drive| Alert {
+                key: format!("smart-{}", drive.device),
+                summary: fl!("smart-failing-alert", device = drive.device.as_str()),
+                body: drive.model,
+                severity: Severity::Critical,
+            })
+            .collect()
+    }
+
+    fn drives(&self) -> Vec<DriveHealth> {
+        match self.state.lock().as_deref() {
+            Ok(SmartState::Drives(drives)) => drives.clone(),
+            _ => Vec::new(),
+        }
+    }
+
+    pub fn view(&self) -> Element<Message> {
+        let mut column = widget::column()
+            .spacing(4)
+            .width(Length::Fill)
+            .push(widget::text(fl!("smart-health")));
+
+        let state = self
+            .state
+            .lock()
+            .map(|state| state.clone())
+            .unwrap_or(SmartState::Pending);
+        match state {
+            SmartState::Pending => column = column.push(widget::text(fl!("loading"))),
+            SmartState::Unavailable(err) => {
+                column = column.push(widget::text(fl!("smart-unavailable", error = err)))
+            }
+            SmartState::Drives(drives) => {
+                for drive in drives {
+                    let status = if drive.passed {
+                        fl!("smart-passed")
+                    } else {
+                        fl!("smart-failing")
+                    };
+                    let mut details = vec![status];
+                    if let Some(sectors) = drive.reallocated_sectors {
+                        details.push(fl!("smart-reallocated", count = sectors));
+                    }
+                    if let Some(wear) = drive.wear {
+                        details.push(fl!("smart-wear", percent = wear));
+                    }
+
+                    column = column.push(
+                        layout::directional_row(vec![
+                            widget::text(format!("{} ({})", drive.device, drive.model))
+                                .width(Length::Fill)
+                                .horizontal_alignment(layout::start_alignment())
+                                .into(),
+                            widget::text(details.join(", ")).into(),
+                        ])
+                        .spacing(8),
+                    );
+                }
+            }
+        }
+
+        column.into()
+    }
+}
+
+fn smartctl(args: &[&str]) -> Result<Value, String> {
+    let output = Command::new("smartctl")
+        .args(args)
+        .arg("--json")
+        .output()
+        .map_err(|err| format!("smartctl: {}", err))?;
+    // smartctl uses its exit status as a bit mask of findings, so only trust the JSON
+    serde_json::from_slice(&output.stdout).map_err(|err| format!("smartctl: {}", err))
+}
+
+fn query_drives() -> Result<Vec<DriveHealth>, String> {
+    let scan = smartctl(&["--scan"])?;
+    let devices = scan["devices"]
+        .as_array()
+        .map(|devices| {
+            devices
+                .iter()
+                .filter_map(|device| device["name"].as_str().map(str::to_string))
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+
+    let drives = devices
+        .into_iter()
+        .filter_map(|device| {
+            let info = smartctl(&["-H", "-A", "-i", device.as_str()]).ok()?;
+            let passed = info["smart_status"]["passed"].as_bool()?;
+            Some(DriveHealth {
+                model: info["model_name"].as_str().unwrap_or_default().to_string(),
+                passed,
+                reallocated_sectors: ata_attribute(&info, REALLOCATED_SECTOR_COUNT)
+                    .and_then(|attr| attr["raw"]["value"].as_u64()),
+                wear: wear(&info),
+                device,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    if drives.is_empty() {
+        return Err(fl!("smart-no-drives"));
+    }
+    Ok(drives)
+}
+
+fn ata_attribute(info: &Value, id: u64) -> Option<&Value> {
+    info["ata_smart_attributes"]["table"]
+        .as_array()?
+        .iter()
+        .find(|attr| attr["id"].as_u64() == Some(id))
+}
+
+fn wear(info: &Value) -> Option<u64> {
+    if let Some(used) = info["nvme_smart_health_information_log"]["percentage_used"].as_u64() {
+        return Some(used);
+    }
+    // The normalized value of the ATA wear attributes counts down from 100
+    [WEAR_LEVELING_COUNT, SSD_LIFE_LEFT]
+        .into_iter()
+        .find_map(|id| ata_attribute(info, id)?["value"].as_u64())
+        .map(|left| 100u64.saturating_sub(left))
+}
@@ -0,0 +1,45 @@
+use serde::{de::DeserializeOwned, Serialize};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+/// Location of an applet state file in the XDG state directory.
+pub fn path(file: &str) -> Option<PathBuf> {
+    dirs::state_dir().map(|dir| dir.join(crate::window::ID).join(file))
+}
+
+pub fn load<T: DeserializeOwned>(file: &str) -> Option<T> {
+    let path = path(file)?;
+    let bytes = match fs::read(&path) {
+        Ok(bytes) => bytes,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return None,
+        Err(err) => {
+            tracing::warn!("failed to read {:?}: {}", path, err);
+            return None;
+        }
+    };
+
+    match bincode::deserialize(&bytes) {
+        Ok(state) => Some(state),
+        Err(err) => {
+            tracing::warn!("discarding unreadable {:?}: {}", path, err);
+            None
+        }
+    }
+}
+
+pub fn save<T: Serialize>(file: &str, state: &T) -> io::Result<()> {
+    let path = path(file)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no XDG state directory"))?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    let bytes =
+        bincode::serialize(state).map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
+
+    // Write to a temporary file first so a crash mid-write can't corrupt the state
+    let tmp_path = path.with_extension("tmp");
+    fs::write(&tmp_path, bytes)?;
+    fs::rename(tmp_path, path)
+}
@@ -0,0 +1,335 @@
+//! Long-term history in an SQLite database, only built with the `sqlite` feature.
+//!
+//! Samples are kept at full resolution for a day, then averaged per minute for a month and
+//! per hour for a year, so months of history stay small.
+
+use crate::history::{History, SeriesHistory};
+use crate::metrics::{Sample, Scale};
+use crate::state;
+use rusqlite::{params, Connection, OpenFlags, OptionalExtension};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+const STORE_FILE: &str = "history.sqlite";
+const DAY_MS: i64 = 24 * 60 * 60 * 1000;
+/// How long reading the history waits for a flush to commit
+const READ_BUSY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// `(bucket length, how long rows of it are kept)` in milliseconds, finest first.
+const RESOLUTIONS: [(i64, i64); 3] = [
+    (1000, DAY_MS),
+    (60 * 1000, 30 * DAY_MS),
+    (60 * 60 * 1000, 365 * DAY_MS),
+];
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS series (
+    source TEXT NOT NULL,
+    series TEXT NOT NULL,
+    title TEXT NOT NULL,
+    scale TEXT NOT NULL,
+    PRIMARY KEY (source, series)
+);
+CREATE TABLE IF NOT EXISTS samples (
+    resolution INTEGER NOT NULL,
+    source TEXT NOT NULL,
+    series TEXT NOT NULL,
+    time INTEGER NOT NULL,
+    value REAL NOT NULL,
+    PRIMARY KEY (resolution, source, series, time)
+);
+-- Rollups, retention and history read time ranges of all series at once
+CREATE INDEX IF NOT EXISTS samples_by_time ON samples (resolution, time);
+CREATE TABLE IF NOT EXISTS rollups (
+    resolution INTEGER PRIMARY KEY,
+    until INTEGER NOT NULL
+);
+";
+
+/// Buffers samples in memory and writes them in one transaction per flush.
+pub struct Store {
+    /// Database file, `None` in memory
+    path: Option<PathBuf>,
+    /// Shared with the thread of a running flush
+    connection: Arc<Mutex<Connection>>,
+    /// `(unix timestamp in milliseconds, sample)` since the last flush
+    pending: Vec<(i64, Sample)>,
+    /// Set while a flush writes on its background thread
+    flushing: Arc<AtomicBool>,
+}
+
+impl Store {
+    pub fn open() -> Option<Self> {
+        let path = state::path(STORE_FILE)?;
+        if let Some(dir) = path.parent() {
+            if let Err(err) = std::fs::create_dir_all(dir) {
+                tracing::warn!("failed to create {:?}: {}", dir, err);
+                return None;
+            }
+        }
+        Self::open_at(&path)
+            .map_err(|err| tracing::warn!("failed to open {:?}: {}", path, err))
+            .ok()
+    }
+
+    fn open_at(path: &Path) -> rusqlite::Result<Self> {
+        let mut store = Self::with_connection(Connection::open(path)?)?;
+        store.path = Some(path.to_path_buf());
+        Ok(store)
+    }
+
+    fn with_connection(connection: Connection) -> rusqlite::Result<Self> {
+        connection.execute_batch(SCHEMA)?;
+        Ok(Self {
+            path: None,
+            connection: Arc::new(Mutex::new(connection)),
+            pending: Vec::new(),
+            flushing: Arc::new(AtomicBool::new(false)),
+        })
+    }
+
+    /// Queues the samples taken at `at`, a unix timestamp in milliseconds.
+    pub fn record(&mut self, at: i64, samples: &[Sample]) {
+        self.pending
+            .extend(samples.iter().map(|sample| (at, sample.clone())));
+    }
+
+    /// Writes the queued samples on a background thread, rolls them up into coarser
+    /// resolutions and drops rows past their retention. While a write is still running
+    /// the samples stay queued for the next flush.
+    pub fn flush(&mut self, now: i64) {
+        if self.pending.is_empty() || self.flushing.swap(true, Ordering::AcqRel) {
+            return;
+        }
+        let pending = std::mem::take(&mut self.pending);
+        let connection = self.connection.clone();
+        let flushing = self.flushing.clone();
+        thread::spawn(move || {
+            if let Ok(mut connection) = connection.lock() {
+                if let Err(err) = write(&mut connection, &pending, now) {
+                    tracing::warn!("failed to write long-term history: {}", err);
+                }
+            }
+            flushing.store(false, Ordering::Release);
+        });
+    }
+
+    /// The database file, for reading the history on another thread with `read_history`.
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+}
+
+/// Everything stored in the database at `path`, read through a connection of its own
+/// rather than the store's, which a running flush holds for the whole write.
+pub fn read_history(path: &Path, now: i64) -> rusqlite::Result<History> {
+    let connection = Connection::open_with_flags(
+        path,
+        OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+    )?;
+    connection.busy_timeout(READ_BUSY_TIMEOUT)?;
+    history(&connection, now)
+}
+
+/// Everything stored, each period at the finest resolution still kept for it.
+fn history(connection: &Connection, now: i64) -> rusqlite::Result<History> {
+    let mut series: BTreeMap<(String, String), SeriesHistory> = BTreeMap::new();
+    let mut metadata = connection.prepare("SELECT source, series, title, scale FROM series")?;
+    let rows = metadata.query_map([], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, String>(2)?,
+            row.get::<_, String>(3)?,
+        ))
+    })?;
+    for row in rows {
+        let (source, id, title, scale) = row?;
+        series.insert(
+            (source.clone(), id.clone()),
+            SeriesHistory {
+                source,
+                series: id,
+                title,
+                scale: serde_json::from_str(&scale).unwrap_or_else(|_| Scale::auto("")),
+                points: Vec::new(),
+            },
+        );
+    }
+
+    // Each resolution covers the time its finer neighbour no longer keeps
+    let mut newest = i64::MAX;
+    let mut points = connection.prepare(
+        "SELECT source, series, time, value FROM samples
+         WHERE resolution = ?1 AND time >= ?2 AND time < ?3
+         ORDER BY time DESC",
+    )?;
+    for (resolution, retention) in RESOLUTIONS {
+        let oldest = now - retention;
+        let rows = points.query_map(params![resolution, oldest, newest], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, i64>(2)?,
+                row.get::<_, f64>(3)?,
+            ))
+        })?;
+        for row in rows {
+            let (source, id, time, value) = row?;
+            if let Some(series) = series.get_mut(&(source, id)) {
+                series.points.push((time, value));
+            }
+        }
+        newest = oldest;
+    }
+
+    Ok(History {
+        series: series.into_values().collect(),
+    })
+}
+
+/// Writes `pending` in one transaction, then rolls up and drops rows past retention.
+fn write(connection: &mut Connection, pending: &[(i64, Sample)], now: i64) -> rusqlite::Result<()> {
+    let transaction = connection.transaction()?;
+    {
+        let mut series = transaction.prepare_cached(
+            "INSERT OR REPLACE INTO series (source, series, title, scale) VALUES (?1, ?2, ?3, ?4)",
+        )?;
+        let mut insert = transaction.prepare_cached(
+            "INSERT OR REPLACE INTO samples (resolution, source, series, time, value)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+        )?;
+        for (at, sample) in pending {
+            let scale = serde_json::to_string(&sample.scale).unwrap_or_default();
+            for (id, value) in &sample.series {
+                series.execute(params![sample.source, id.as_str(), sample.title, scale])?;
+                insert.execute(params![
+                    RESOLUTIONS[0].0,
+                    sample.source,
+                    id.as_str(),
+                    at,
+                    value
+                ])?;
+            }
+        }
+    }
+
+    for pair in RESOLUTIONS.windows(2) {
+        let (fine, coarse) = (pair[0].0, pair[1].0);
+        // Only buckets that can't receive more samples
+        let until = now / coarse * coarse;
+        let from: i64 = transaction
+            .query_row(
+                "SELECT until FROM rollups WHERE resolution = ?1",
+                params![coarse],
+                |row| row.get(0),
+            )
+            .optional()?
+            .unwrap_or_default();
+        if until <= from {
+            continue;
+        }
+        transaction.execute(
+            "INSERT OR REPLACE INTO samples (resolution, source, series, time, value)
+                 SELECT ?1, source, series, time / ?1 * ?1, AVG(value) FROM samples
+                 WHERE resolution = ?2 AND time >= ?3 AND time < ?4
+                 GROUP BY source, series, time / ?1",
+            params![coarse, fine, from, until],
+        )?;
+        transaction.execute(
+            "INSERT OR REPLACE INTO rollups (resolution, until) VALUES (?1, ?2)",
+            params![coarse, until],
+        )?;
+    }
+
+    for (resolution, retention) in RESOLUTIONS {
+        transaction.execute(
+            "DELETE FROM samples WHERE resolution = ?1 AND time < ?2",
+            params![resolution, now - retention],
+        )?;
+    }
+    transaction.commit()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::SeriesId;
+
+    fn cpu(value: f64) -> Sample {
+        Sample {
+            source: "CPU".to_string(),
+            title: "CPU".to_string(),
+            scale: Scale::percent(),
+            series: vec![(SeriesId::new("cpu"), value)],
+        }
+    }
+
+    /// Writes the queued samples right away instead of on a background thread.
+    fn flush(store: &mut Store, now: i64) {
+        let pending = std::mem::take(&mut store.pending);
+        write(&mut store.connection.lock().unwrap(), &pending, now).unwrap();
+    }
+
+    #[test]
+    fn rolls_samples_up_into_minutes() {
+        let mut store = Store::with_connection(Connection::open_in_memory().unwrap()).unwrap();
+        let minute = 60 * 1000;
+        store.record(0, &[cpu(10.0)]);
+        store.record(1000, &[cpu(30.0)]);
+        store.record(minute, &[cpu(50.0)]);
+        flush(&mut store, minute + 1000);
+
+        let minutes = store
+            .connection
+            .lock()
+            .unwrap()
+            .prepare("SELECT time, value FROM samples WHERE resolution = ?1")
+            .unwrap()
+            .query_map(params![minute], |row| {
+                Ok((row.get::<_, i64>(0)?, row.get::<_, f64>(1)?))
+            })
+            .unwrap()
+            .collect::<rusqlite::Result<Vec<_>>>()
+            .unwrap();
+        // The second minute is still open
+        assert_eq!(minutes, vec![(0, 20.0)]);
+    }
+
+    #[test]
+    fn history_falls_back_to_coarser_resolutions() {
+        let mut store = Store::with_connection(Connection::open_in_memory().unwrap()).unwrap();
+        let now = 3 * DAY_MS;
+        // Old enough that only its minute average is kept
+        store.record(DAY_MS, &[cpu(10.0)]);
+        store.record(DAY_MS + 1000, &[cpu(20.0)]);
+        store.record(now - 1000, &[cpu(40.0)]);
+        flush(&mut store, now);
+
+        let history = history(&store.connection.lock().unwrap(), now).unwrap();
+        assert_eq!(history.series.len(), 1);
+        assert_eq!(
+            history.series[0].points,
+            vec![(now - 1000, 40.0), (DAY_MS, 15.0)]
+        );
+    }
+
+    #[test]
+    fn reads_history_through_its_own_connection() {
+        let path = std::env::temp_dir().join(format!("history-{}.sqlite", std::process::id()));
+        let mut store = Store::open_at(&path).unwrap();
+        store.record(1000, &[cpu(10.0)]);
+        flush(&mut store, 2000);
+
+        // Also while the store's connection is taken, as by a running flush
+        let _flushing = store.connection.lock().unwrap();
+        let history = read_history(store.path().unwrap(), 2000).unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(history.series[0].points, vec![(1000, 10.0)]);
+    }
+}
@@ -0,0 +1,158 @@
+use crate::fl;
+use crate::layout;
+use crate::window::Message;
+use cosmic::iced::Length;
+use cosmic::widget;
+use cosmic::Element;
+use human_bytes::human_bytes;
+use std::fs;
+
+const SYS_BLOCK: &str = "/sys/block";
+const MEMINFO: &str = "/proc/meminfo";
+
+/// Data held by a zram device or by zswap.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CompressedPool {
+    /// zram device name, or `zswap`
+    pub name: String,
+    /// Bytes before compression
+    pub original: u64,
+    pub compressed: u64,
+}
+
+impl CompressedPool {
+    pub fn ratio(&self) -> f64 {
+        if self.compressed == 0 {
+            return 0.0;
+        }
+        self.original as f64 / self.compressed as f64
+    }
+}
+
+/// zram devices from their `mm_stat` and zswap from `/proc/meminfo`.
+#[derive(Default)]
+pub struct CompressedSwap {
+    pools: Vec<CompressedPool>,
+}
+
+impl CompressedSwap {
+    pub fn refresh(&mut self) {
+        let mut pools = Vec::new();
+        if let Ok(devices) = fs::read_dir(SYS_BLOCK) {
+            for device in devices.flatten() {
+                let name = device.file_name().to_string_lossy().to_string();
+                if !name.starts_with("zram") {
+                    continue;
+                }
+                if let Some(pool) = fs::read_to_string(device.path().join("mm_stat"))
+                    .ok()
+                    .and_then(|mm_stat| parse_mm_stat(name, &mm_stat))
+                {
+                    pools.push(pool);
+                }
+            }
+        }
+        pools.sort_by(|a, b| a.name.cmp(&b.name));
+        pools.extend(
+            fs::read_to_string(MEMINFO)
+                .ok()
+                .and_then(|meminfo| parse_meminfo(&meminfo)),
+        );
+        // Configured but unused devices are not worth a row
+        pools.retain(|pool| pool.original > 0);
+        self.pools = pools;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pools.is_empty()
+    }
+
+    pub fn view(&self) -> Element<Message> {
+        let mut column = widget::column()
+            .spacing(4)
+            .width(Length::Fill)
+            .push(widget::text(fl!("compressed-swap")));
+
+        for pool in &self.pools {
+            column = column.push(
+                layout::directional_row(vec![
+                    widget::text(pool.name.as_str())
+                        .width(Length::Fill)
+                        .horizontal_alignment(layout::start_alignment())
+                        .into(),
+                    widget::text(fl!(
+                        "compressed-size",
+                        compressed = human_bytes(pool.compressed as f64),
+                        original = human_bytes(pool.original as f64),
+                        ratio = format!("{:.1}", pool.ratio())
+                    ))
+                    .into(),
+                ])
+                .spacing(8),
+            );
+        }
+
+        column.into()
+    }
+}
+
+/// The first two fields of `mm_stat` are the original and the compressed data size.
+fn parse_mm_stat(name: String, mm_stat: &str) -> Option<CompressedPool> {
+    let mut fields = mm_stat.split_whitespace();
+    Some(CompressedPool {
+        name,
+        original: fields.next()?.parse().ok()?,
+        compressed: fields.next()?.parse().ok()?,
+    })
+}
+
+/// `Zswap` is the compressed and `Zswapped` the original size, both in KiB. Only
+/// present on kernels built with zswap.
+fn parse_meminfo(meminfo: &str) -> Option<CompressedPool> {
+    let field = |key: &str| -> Option<u64> {
+        meminfo.lines().find_map(|line| {
+            let (name, value) = line.split_once(':')?;
+            if name != key {
+                return None;
+            }
+            value.trim().trim_end_matches("kB").trim().parse().ok()
+        })
+    };
+    Some(CompressedPool {
+        name: "zswap".to_string(),
+        original: field("Zswapped")? * 1024,
+        compressed: field("Zswap")? * 1024,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_zram_mm_stat() {
+        let pool = parse_mm_stat(
+            "zram0".to_string(),
+            "1073741824 268435456 276824064        0 300000000     1024        0        0        0\n",
+        )
+        .unwrap();
+
+        assert_eq!(pool.original, 1073741824);
+        assert_eq!(pool.compressed, 268435456);
+        assert_eq!(pool.ratio(), 4.0);
+    }
+
+    #[test]
+    fn parses_zswap_from_meminfo() {
+        let meminfo = "\
+SwapTotal:       8388604 kB
+SwapFree:        8000000 kB
+Zswap:             10240 kB
+Zswapped:          30720 kB
+";
+        let pool = parse_meminfo(meminfo).unwrap();
+
+        assert_eq!(pool.compressed, 10240 * 1024);
+        assert_eq!(pool.original, 30720 * 1024);
+    }
+}
@@ -0,0 +1,247 @@
+use crate::fl;
+use crate::layout;
+use crate::window::Message;
+use cosmic::iced::{Color, Length};
+use cosmic::widget;
+use cosmic::Element;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Exposed by the Raspberry Pi firmware driver, same value as `vcgencmd get_throttled`
+const FIRMWARE_THROTTLED: &str = "/sys/devices/platform/soc/soc:firmware/get_throttled";
+const THERMAL: &str = "/sys/class/thermal";
+/// How often `vcgencmd` is asked when the firmware driver doesn't expose the mask
+const VCGENCMD_EVERY: Duration = Duration::from_secs(5);
+
+/// Bits of the firmware throttling mask, the same conditions repeat 16 bits higher for
+/// "has occurred since boot".
+const UNDER_VOLTAGE: u32 = 1 << 0;
+const FREQUENCY_CAPPED: u32 = 1 << 1;
+const THROTTLED: u32 = 1 << 2;
+const SOFT_TEMPERATURE_LIMIT: u32 = 1 << 3;
+const OCCURRED_SHIFT: u32 = 16;
+
+/// Where the throttling flags come from.
+#[derive(Clone, Debug)]
+enum FlagSource {
+    Sysfs,
+    Vcgencmd,
+}
+
+/// Firmware throttling flags and SoC temperature of a Raspberry Pi or similar board.
+pub struct ThrottleMonitor {
+    flags: Option<FlagSource>,
+    /// `temp` of the CPU or SoC thermal zone
+    thermal_zone: Option<PathBuf>,
+    /// Written by the `vcgencmd` thread with `FlagSource::Vcgencmd`
+    mask: Arc<Mutex<u32>>,
+    last_run: Option<Instant>,
+    soc_celsius: Option<f32>,
+}
+
+impl ThrottleMonitor {
+    /// `None` on machines without a firmware throttling mask or SoC thermal zone, i.e.
+    /// most PCs, whose sensors show up under hwmon.
+    pub fn detect() -> Option<Self> {
+        // Don't spawn `vcgencmd` on PCs, which also have their CPU temperature under hwmon
+        // already
+        let board = cfg!(any(target_arch = "arm", target_arch = "aarch64"));
+        let flags = if Path::new(FIRMWARE_THROTTLED).exists() {
+            Some(FlagSource::Sysfs)
+        } else if board && vcgencmd_throttled().is_some() {
+            Some(FlagSource::Vcgencmd)
+        } else {
+            None
+        };
+        let thermal_zone = if board { soc_thermal_zone() } else { None };
+        if flags.is_none() && thermal_zone.is_none() {
+            return None;
+        }
+
+        let mut monitor = Self {
+            flags,
+            thermal_zone,
+            mask: Arc::new(Mutex::new(0)),
+            last_run: None,
+            soc_celsius: None,
+        };
+        monitor.refresh();
+        Some(monitor)
+    }
+
+    /// Reads the sysfs mask and temperature, `vcgencmd` is asked on a background thread.
+    pub fn refresh(&mut self) {
+        match self.flags {
+            Some(FlagSource::Sysfs) => {
+                let current = fs::read_to_string(FIRMWARE_THROTTLED)
+                    .ok()
+                    .and_then(|mask| parse_mask(&mask))
+                    .unwrap_or_default();
+                if let Ok(mut mask) = self.mask.lock() {
+                    *mask = current;
+                }
+            }
+            Some(FlagSource::Vcgencmd)
+                if self
+                    .last_run
+                    .map_or(true, |time| time.elapsed() > VCGENCMD_EVERY) =>
+            {
+                self.last_run = Some(Instant::now());
+                let mask = self.mask.clone();
+                thread::spawn(move || {
+                    let current = vcgencmd_throttled().unwrap_or_default();
+                    if let Ok(mut mask) = mask.lock() {
+                        *mask = current;
+                    }
+                });
+            }
+            _ => {}
+        }
+        self.soc_celsius = self
+            .thermal_zone
+            .as_ref()
+            .and_then(|zone| fs::read_to_string(zone).ok())
+            .and_then(|millidegrees| millidegrees.trim().parse::<i64>().ok())
+            .map(|millidegrees| millidegrees as f32 / 1000.0);
+    }
+
+    fn mask(&self) -> u32 {
+        self.mask.lock().map(|mask| *mask).unwrap_or_default()
+    }
+
+    /// Whether the board is being slowed down right now.
+    pub fn is_throttled(&self) -> bool {
+        self.mask() & (UNDER_VOLTAGE | FREQUENCY_CAPPED | THROTTLED | SOFT_TEMPERATURE_LIMIT) != 0
+    }
+
+    /// Shown in the popup header while throttled.
+    pub fn badge(&self, theme: &cosmic::cosmic_theme::Theme) -> Element<Message> {
+        widget::text(fl!("throttled"))
+            .style(cosmic::theme::Text::Color(Color::from(
+                theme.warning_color(),
+            )))
+            .into()
+    }
+
+    pub fn view(&self) -> Element<Message> {
+        let mut column = widget::column()
+            .spacing(4)
+            .width(Length::Fill)
+            .push(widget::text(fl!("board")));
+
+        if let Some(celsius) = self.soc_celsius {
+            column = column.push(
+                layout::directional_row(vec![
+                    widget::text(fl!("soc-temperature"))
+                        .width(Length::Fill)
+                        .horizontal_alignment(layout::start_alignment())
+                        .into(),
+                    widget::text(format!("{:.0}°C", celsius)).into(),
+                ])
+                .spacing(8),
+            );
+        }
+        if self.flags.is_some() {
+            let conditions = conditions(self.mask());
+            if conditions.is_empty() {
+                column = column.push(widget::text(fl!("throttling-none")));
+            }
+            for (condition, now) in conditions {
+                column = column.push(
+                    layout::directional_row(vec![
+                        widget::text(condition)
+                            .width(Length::Fill)
+                            .horizontal_alignment(layout::start_alignment())
+                            .into(),
+                        widget::text(if now {
+                            fl!("throttling-now")
+                        } else {
+                            fl!("throttling-since-boot")
+                        })
+                        .into(),
+                    ])
+                    .spacing(8),
+                );
+            }
+        }
+
+        column.into()
+    }
+}
+
+/// Every condition that happened since boot, with whether it still applies.
+fn conditions(mask: u32) -> Vec<(String, bool)> {
+    [
+        (UNDER_VOLTAGE, fl!("under-voltage")),
+        (FREQUENCY_CAPPED, fl!("frequency-capped")),
+        (THROTTLED, fl!("throttling")),
+        (SOFT_TEMPERATURE_LIMIT, fl!("soft-temperature-limit")),
+    ]
+    .into_iter()
+    .filter(|(bit, _)| mask & (bit | bit << OCCURRED_SHIFT) != 0)
+    .map(|(bit, name)| (name, mask & bit != 0))
+    .collect()
+}
+
+fn vcgencmd_throttled() -> Option<u32> {
+    let output = Command::new("vcgencmd")
+        .arg("get_throttled")
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+    parse_mask(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Parses `throttled=0x50005` from `vcgencmd` or the bare hex value from sysfs.
+fn parse_mask(output: &str) -> Option<u32> {
+    let value = output.trim();
+    let value = value.strip_prefix("throttled=").unwrap_or(value);
+    let hex = value.strip_prefix("0x").unwrap_or(value);
+    u32::from_str_radix(hex, 16).ok()
+}
+
+/// The thermal zone of the CPU or SoC, named e.g. `cpu-thermal` or `soc-thermal` by
+/// ARM device trees.
+fn soc_thermal_zone() -> Option<PathBuf> {
+    let mut zones = fs::read_dir(THERMAL)
+        .ok()?
+        .flatten()
+        .map(|zone| zone.path())
+        .filter(|zone| {
+            fs::read_to_string(zone.join("type")).is_ok_and(|kind| {
+                let kind = kind.trim();
+                kind.starts_with("cpu") || kind.starts_with("soc")
+            })
+        })
+        .collect::<Vec<_>>();
+    zones.sort();
+    zones.into_iter().next().map(|zone| zone.join("temp"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_both_mask_formats() {
+        assert_eq!(parse_mask("throttled=0x50005\n"), Some(0x50005));
+        assert_eq!(parse_mask("50000\n"), Some(0x50000));
+        assert_eq!(parse_mask("0\n"), Some(0));
+        assert_eq!(parse_mask("error=1"), None);
+    }
+
+    #[test]
+    fn conditions_tell_current_from_past() {
+        // Under-voltage now, throttled earlier
+        let current = conditions(0x50001)
+            .into_iter()
+            .map(|(_, now)| now)
+            .collect::<Vec<_>>();
+        assert_eq!(current, vec![true, false]);
+        assert!(conditions(0).is_empty());
+    }
+}
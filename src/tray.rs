@@ -0,0 +1,139 @@
+//! Runs as a StatusNotifierItem tray icon for desktops other than COSMIC, with the
+//! current values in its tooltip and menu. Only built with the `tray` feature.
+
+use crate::chart;
+use crate::config::Config;
+use crate::fl;
+use crate::metrics::{self, Sample, Sampler};
+use crate::sensors::SensorMonitor;
+use std::process::Command;
+use std::thread;
+use std::time::Duration;
+
+const REFRESH_EVERY: Duration = Duration::from_secs(2);
+const ICON_NAME: &str = "utilities-system-monitor-symbolic";
+
+struct SysStatusTray {
+    /// One `Title: value` line per metric with data
+    lines: Vec<String>,
+}
+
+impl ksni::Tray for SysStatusTray {
+    fn id(&self) -> String {
+        crate::window::ID.to_string()
+    }
+
+    fn title(&self) -> String {
+        fl!("tray-title")
+    }
+
+    fn icon_name(&self) -> String {
+        ICON_NAME.to_string()
+    }
+
+    fn tool_tip(&self) -> ksni::ToolTip {
+        ksni::ToolTip {
+            title: fl!("tray-title"),
+            description: self.lines.join("\n"),
+            icon_name: ICON_NAME.to_string(),
+            icon_pixmap: Vec::new(),
+        }
+    }
+
+    fn menu(&self) -> Vec<ksni::MenuItem<Self>> {
+        let mut menu = self
+            .lines
+            .iter()
+            .map(|line| {
+                ksni::menu::StandardItem {
+                    label: line.clone(),
+                    enabled: false,
+                    ..Default::default()
+                }
+                .into()
+            })
+            .collect::<Vec<ksni::MenuItem<Self>>>();
+        if menu.is_empty() {
+            menu.push(
+                ksni::menu::StandardItem {
+                    label: fl!("loading"),
+                    enabled: false,
+                    ..Default::default()
+                }
+                .into(),
+            );
+        }
+        menu.push(ksni::MenuItem::Separator);
+        menu.push(
+            ksni::menu::StandardItem {
+                label: fl!("expand"),
+                activate: Box::new(|_: &mut Self| open_window()),
+                ..Default::default()
+            }
+            .into(),
+        );
+        menu.push(
+            ksni::menu::StandardItem {
+                label: fl!("tray-quit"),
+                activate: Box::new(|_: &mut Self| std::process::exit(0)),
+                ..Default::default()
+            }
+            .into(),
+        );
+        menu
+    }
+}
+
+/// Shows the tray icon and refreshes its values until quit from the menu. Uses the same
+/// metric sources and config as the applet.
+pub fn run(config: &Config) {
+    let service = ksni::TrayService::new(SysStatusTray { lines: Vec::new() });
+    let handle = service.handle();
+    service.spawn();
+
+    let mut sampler = Sampler::new(metrics::sources(config));
+    sampler.prime();
+    let mut sensors = SensorMonitor::default();
+    loop {
+        thread::sleep(REFRESH_EVERY);
+        if !sampler.is_warm() {
+            continue;
+        }
+        let samples = sampler.sample();
+        // Alerts are left to the applet, the tray only shows values
+        sensors.refresh(config);
+        let mut lines = summary(&samples);
+        if let Some(celsius) = sensors.hottest() {
+            lines.push(format!("{}: {:.0}°C", fl!("temperature"), celsius));
+        }
+        handle.update(|tray| tray.lines = lines);
+    }
+}
+
+/// The values of every sample, formatted like the quick glance.
+fn summary(samples: &[Sample]) -> Vec<String> {
+    samples
+        .iter()
+        .filter(|sample| !sample.series.is_empty())
+        .map(|sample| {
+            let values = sample
+                .series
+                .iter()
+                .map(|(_, value)| chart::format_value(*value, &sample.scale.unit))
+                .collect::<Vec<_>>()
+                .join(" / ");
+            format!("{}: {}", sample.title, values)
+        })
+        .collect()
+}
+
+/// The full view in a regular window, there is no panel popup to open.
+fn open_window() {
+    match std::env::current_exe().and_then(|exe| Command::new(exe).arg("--windowed").spawn()) {
+        // Reaped once closed
+        Ok(mut child) => {
+            thread::spawn(move || child.wait());
+        }
+        Err(err) => tracing::warn!("failed to open the window: {}", err),
+    }
+}
@@ -0,0 +1,224 @@
+use crate::fl;
+use crate::layout;
+use crate::window::Message;
+use cosmic::iced::{Alignment, Length};
+use cosmic::widget;
+use cosmic::Element;
+use human_bytes::human_bytes;
+use std::collections::HashMap;
+use std::process::Command;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const REFRESH_EVERY: Duration = Duration::from_secs(5);
+const PROPERTIES: &str = "Id,ActiveState,SubState,MemoryCurrent,CPUUsageNSec";
+
+/// State and resource usage of a watched systemd unit.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct UnitStatus {
+    pub id: String,
+    /// `active`, `failed`, `inactive`, …
+    pub active_state: String,
+    /// `running`, `exited`, `dead`, …
+    pub sub_state: String,
+    /// Bytes, `None` without memory accounting or while stopped
+    pub memory: Option<u64>,
+    /// CPU time in nanoseconds, cumulative
+    cpu_nsec: Option<u64>,
+    /// Share of one core since the previous refresh
+    pub cpu_percent: Option<f64>,
+}
+
+/// Asks `systemctl show` for the units in `watched_units` on a background thread.
+pub struct UnitMonitor {
+    units: Arc<Mutex<Vec<UnitStatus>>>,
+    last_refresh: Option<Instant>,
+}
+
+impl UnitMonitor {
+    pub fn new() -> Self {
+        Self {
+            units: Arc::new(Mutex::new(Vec::new())),
+            last_refresh: None,
+        }
+    }
+
+    pub fn poll(&mut self, watched: &[String]) {
+        if self
+            .last_refresh
+            .is_some_and(|time| time.elapsed() < REFRESH_EVERY)
+        {
+            return;
+        }
+        let elapsed = self.last_refresh.map(|time| time.elapsed());
+        self.last_refresh = Some(Instant::now());
+        let watched = watched.to_vec();
+        let units = self.units.clone();
+        thread::spawn(move || {
+            let output = Command::new("systemctl")
+                .arg("show")
+                .arg(format!("--property={}", PROPERTIES))
+                .args(&watched)
+                .output();
+            let current = match output {
+                Ok(output) if output.status.success() => {
+                    parse_show(&String::from_utf8_lossy(&output.stdout))
+                }
+                Ok(output) => {
+                    tracing::warn!(
+                        "systemctl show failed: {}",
+                        String::from_utf8_lossy(&output.stderr).trim()
+                    );
+                    return;
+                }
+                Err(err) => {
+                    tracing::warn!("failed to run systemctl: {}", err);
+                    return;
+                }
+            };
+            let Ok(mut units) = units.lock() else {
+                return;
+            };
+            let previous = units
+                .iter()
+                .filter_map(|unit| Some((unit.id.clone(), unit.cpu_nsec?)))
+                .collect::<HashMap<_, _>>();
+            *units = current
+                .into_iter()
+                .map(|mut unit| {
+                    unit.cpu_percent =
+                        cpu_percent(previous.get(&unit.id).copied(), unit.cpu_nsec, elapsed);
+                    unit
+                })
+                .collect();
+        });
+    }
+
+    pub fn view(&self) -> Element<Message> {
+        let units = self
+            .units
+            .lock()
+            .map(|units| units.clone())
+            .unwrap_or_default();
+        let mut column = widget::column()
+            .spacing(4)
+            .width(Length::Fill)
+            .push(widget::text(fl!("systemd-units")));
+        if units.is_empty() {
+            column = column.push(widget::text(fl!("loading")));
+        }
+
+        for unit in units {
+            let usage = match (unit.cpu_percent, unit.memory) {
+                (Some(cpu), Some(memory)) => {
+                    format!("{:.1}% · {}", cpu, human_bytes(memory as f64))
+                }
+                (None, Some(memory)) => human_bytes(memory as f64),
+                _ => String::new(),
+            };
+            column = column.push(
+                layout::directional_row(vec![
+                    widget::text(unit.id.clone())
+                        .width(Length::Fill)
+                        .horizontal_alignment(layout::start_alignment())
+                        .into(),
+                    widget::text(format!("{} ({})", unit.active_state, unit.sub_state)).into(),
+                    widget::text(usage).into(),
+                    widget::button::standard(fl!("restart-unit"))
+                        .on_press(Message::RestartUnit(unit.id))
+                        .into(),
+                ])
+                .spacing(8)
+                .align_items(Alignment::Center),
+            );
+        }
+
+        column.into()
+    }
+}
+
+/// Restarts `unit` in the background. `systemctl` asks polkit, which prompts for
+/// authentication unless the rules allow the user to manage units.
+pub fn restart(unit: String) {
+    thread::spawn(
+        move || match Command::new("systemctl").arg("restart").arg(&unit).status() {
+            Ok(status) if status.success() => tracing::info!("restarted {}", unit),
+            Ok(status) => tracing::warn!("failed to restart {}: systemctl {}", unit, status),
+            Err(err) => tracing::warn!("failed to restart {}: {}", unit, err),
+        },
+    );
+}
+
+fn cpu_percent(
+    previous: Option<u64>,
+    current: Option<u64>,
+    elapsed: Option<Duration>,
+) -> Option<f64> {
+    let elapsed = elapsed?.as_nanos() as f64;
+    if elapsed <= 0.0 {
+        return None;
+    }
+    Some(current?.saturating_sub(previous?) as f64 / elapsed * 100.0)
+}
+
+/// Parses `systemctl show` output, one block of `Key=value` lines per unit separated by
+/// blank lines. Unset numbers read `[not set]` or the maximum `u64`.
+fn parse_show(output: &str) -> Vec<UnitStatus> {
+    let number = |value: &str| value.parse::<u64>().ok().filter(|n| *n != u64::MAX);
+    output
+        .split("\n\n")
+        .filter_map(|block| {
+            let mut unit = UnitStatus::default();
+            for line in block.lines() {
+                let Some((key, value)) = line.split_once('=') else {
+                    continue;
+                };
+                match key {
+                    "Id" => unit.id = value.to_string(),
+                    "ActiveState" => unit.active_state = value.to_string(),
+                    "SubState" => unit.sub_state = value.to_string(),
+                    "MemoryCurrent" => unit.memory = number(value),
+                    "CPUUsageNSec" => unit.cpu_nsec = number(value),
+                    _ => {}
+                }
+            }
+            (!unit.id.is_empty()).then_some(unit)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_one_block_per_unit() {
+        let output = "\
+Id=nginx.service
+ActiveState=active
+SubState=running
+MemoryCurrent=10485760
+CPUUsageNSec=2000000000
+
+Id=backup.service
+ActiveState=failed
+SubState=failed
+MemoryCurrent=[not set]
+CPUUsageNSec=18446744073709551615
+";
+        let units = parse_show(output);
+        assert_eq!(units.len(), 2);
+        assert_eq!(units[0].memory, Some(10485760));
+        assert_eq!(units[1].active_state, "failed");
+        assert_eq!(units[1].memory, None);
+        assert_eq!(units[1].cpu_nsec, None);
+    }
+
+    #[test]
+    fn cpu_percent_needs_a_previous_refresh() {
+        let second = Some(Duration::from_secs(1));
+        assert_eq!(cpu_percent(None, Some(500_000_000), second), None);
+        assert_eq!(cpu_percent(Some(0), Some(500_000_000), second), Some(50.0));
+    }
+}
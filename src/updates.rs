@@ -0,0 +1,193 @@
+use crate::alerts::{Alert, Severity};
+use crate::config::Config;
+use crate::fl;
+use crate::window::Message;
+use cosmic::iced::Color;
+use cosmic::widget;
+use cosmic::Element;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// Package metadata is refreshed by the distribution's own timers a few times a day
+const REFRESH_EVERY: Duration = Duration::from_secs(60 * 60);
+pub const ALERT_PREFIX: &str = "updates-";
+/// Written by Debian and Ubuntu packages that need a reboot
+const REBOOT_REQUIRED_FILES: [&str; 2] = ["/run/reboot-required", "/var/run/reboot-required"];
+const MODULES: &str = "/usr/lib/modules";
+const OS_RELEASE: &str = "/etc/os-release";
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct UpdateStatus {
+    pub reboot_required: bool,
+    /// `None` when the distribution is unknown and no command was configured
+    pub security_updates: Option<usize>,
+}
+
+/// Checks for a pending reboot and security updates on a background thread.
+pub struct UpdateMonitor {
+    status: Arc<Mutex<UpdateStatus>>,
+    last_run: Option<Instant>,
+}
+
+impl UpdateMonitor {
+    pub fn new() -> Self {
+        Self {
+            status: Arc::new(Mutex::new(UpdateStatus::default())),
+            last_run: None,
+        }
+    }
+
+    /// Starts a new check when due and returns alerts for `Alerts::sync` when
+    /// `notify_updates` is set.
+    pub fn poll(&mut self, config: &Config) -> Vec<Alert> {
+        if self
+            .last_run
+            .map_or(true, |time| time.elapsed() > REFRESH_EVERY)
+        {
+            self.last_run = Some(Instant::now());
+            let status = self.status.clone();
+            let reboot_command = config.reboot_required_command.clone();
+            let updates_command = config.security_updates_command.clone();
+            thread::spawn(move || {
+                let result = UpdateStatus {
+                    reboot_required: reboot_required(&reboot_command),
+                    security_updates: security_updates(&updates_command),
+                };
+                if let Ok(mut status) = status.lock() {
+                    *status = result;
+                }
+            });
+        }
+
+        if !config.notify_updates {
+            return Vec::new();
+        }
+        let status = self.status();
+        let mut alerts = Vec::new();
+        if status.reboot_required {
+            alerts.push(Alert {
+                key: format!("{}reboot", ALERT_PREFIX),
+                summary: fl!("reboot-required"),
+                body: fl!("reboot-required-body"),
+                severity: Severity::Warning,
+            });
+        }
+        if let Some(count) = status.security_updates.filter(|count| *count > 0) {
+            alerts.push(Alert {
+                key: format!("{}security", ALERT_PREFIX),
+                summary: fl!("security-updates", count = count),
+                body: fl!("security-updates-body"),
+                severity: Severity::Warning,
+            });
+        }
+        alerts
+    }
+
+    fn status(&self) -> UpdateStatus {
+        self.status.lock().map(|status| *status).unwrap_or_default()
+    }
+
+    /// Shown in the popup header while a reboot or security updates are pending.
+    pub fn badge(&self, theme: &cosmic::cosmic_theme::Theme) -> Option<Element<Message>> {
+        let status = self.status();
+        let text = match (status.reboot_required, status.security_updates) {
+            (true, _) => fl!("reboot-required"),
+            (false, Some(count)) if count > 0 => fl!("security-updates", count = count),
+            _ => return None,
+        };
+        Some(
+            widget::text(text)
+                .style(cosmic::theme::Text::Color(Color::from(
+                    theme.destructive_color(),
+                )))
+                .into(),
+        )
+    }
+}
+
+/// `command` exits with a failure status when a reboot is needed, like
+/// `needs-restarting -r`. Without one the distribution's marker is checked.
+fn reboot_required(command: &str) -> bool {
+    if !command.is_empty() {
+        return Command::new("sh")
+            .arg("-c")
+            .arg(command)
+            .status()
+            .is_ok_and(|status| !status.success());
+    }
+    if REBOOT_REQUIRED_FILES
+        .iter()
+        .any(|file| Path::new(file).exists())
+    {
+        return true;
+    }
+    // Fedora and RHEL
+    if let Ok(status) = Command::new("needs-restarting").arg("-r").status() {
+        return !status.success();
+    }
+    // Arch removes the running kernel's modules when upgrading it
+    Path::new(MODULES).exists()
+        && fs::read_to_string("/proc/sys/kernel/osrelease")
+            .is_ok_and(|release| !Path::new(MODULES).join(release.trim()).exists())
+}
+
+/// Number of lines `command` prints, one per pending security update. Without one a
+/// command is picked for the distribution.
+fn security_updates(command: &str) -> Option<usize> {
+    let command = if command.is_empty() {
+        let os_release = fs::read_to_string(OS_RELEASE).ok()?;
+        default_updates_command(&os_release)?
+    } else {
+        command
+    };
+    let output = Command::new("sh").arg("-c").arg(command).output().ok()?;
+    Some(
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .count(),
+    )
+}
+
+/// Lists security updates with the package manager of the distribution in `os-release`,
+/// matching `ID` and `ID_LIKE`.
+fn default_updates_command(os_release: &str) -> Option<&'static str> {
+    let ids = os_release
+        .lines()
+        .filter_map(|line| {
+            line.strip_prefix("ID=")
+                .or_else(|| line.strip_prefix("ID_LIKE="))
+        })
+        .flat_map(|value| value.trim_matches('"').split_whitespace())
+        .collect::<Vec<_>>();
+    if ids.iter().any(|id| *id == "debian" || *id == "ubuntu") {
+        Some("apt-get --simulate upgrade 2>/dev/null | grep '^Inst' | grep -i security")
+    } else if ids.iter().any(|id| *id == "fedora" || *id == "rhel") {
+        Some("dnf --quiet updateinfo list --security 2>/dev/null")
+    } else if ids
+        .iter()
+        .any(|id| id.starts_with("opensuse") || *id == "suse")
+    {
+        Some("zypper --quiet list-patches --category security 2>/dev/null | grep '^[^-]*|' | tail -n +2")
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn picks_the_command_by_distribution() {
+        let pop = "NAME=\"Pop!_OS\"\nID=pop\nID_LIKE=\"ubuntu debian\"\n";
+        assert!(default_updates_command(pop).is_some_and(|command| command.starts_with("apt-get")));
+        let fedora = "NAME=\"Fedora Linux\"\nID=fedora\n";
+        assert!(default_updates_command(fedora).is_some_and(|command| command.starts_with("dnf")));
+        assert_eq!(default_updates_command("ID=arch\n"), None);
+    }
+}
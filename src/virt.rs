@@ -0,0 +1,123 @@
+use crate::config::Config;
+use crate::fl;
+use crate::window::Message;
+use cosmic::iced::Length;
+use cosmic::widget;
+use cosmic::Element;
+use human_bytes::human_bytes;
+use std::fs;
+use std::process::Command;
+use std::sync::OnceLock;
+
+/// Needs debugfs, which is usually only readable by root
+const BALLOON_DEBUGFS: &str = "/sys/kernel/debug/virtio-balloon";
+const MEMINFO: &str = "/proc/meminfo";
+
+/// The hypervisor the system runs under, e.g. `kvm`, `vmware` or `wsl`, as named by
+/// `systemd-detect-virt`. `None` on bare metal and in containers, which see the host's
+/// hardware.
+pub fn hypervisor() -> Option<&'static str> {
+    static HYPERVISOR: OnceLock<Option<String>> = OnceLock::new();
+    HYPERVISOR
+        .get_or_init(|| {
+            // WSL is reported as a container, but runs in a utility VM
+            detect_virt(&["--vm"]).or_else(|| detect_virt(&[]).filter(|virt| virt == "wsl"))
+        })
+        .as_deref()
+}
+
+/// Whether readings should be adapted to a virtual machine.
+pub fn is_guest(config: &Config) -> bool {
+    config.adapt_to_vm && hypervisor().is_some()
+}
+
+fn detect_virt(args: &[&str]) -> Option<String> {
+    let output = Command::new("systemd-detect-virt")
+        .args(args)
+        .output()
+        .ok()?;
+    parse_detect_virt(&String::from_utf8_lossy(&output.stdout))
+}
+
+fn parse_detect_virt(output: &str) -> Option<String> {
+    Some(output.trim())
+        .filter(|virt| !virt.is_empty() && *virt != "none")
+        .map(str::to_string)
+}
+
+/// Memory the host reclaimed from this guest through the virtio balloon.
+#[derive(Default)]
+pub struct BalloonMonitor {
+    /// Bytes held by the balloon
+    inflated: Option<u64>,
+    /// Bytes left to the guest
+    total: u64,
+}
+
+impl BalloonMonitor {
+    pub fn refresh(&mut self) {
+        self.inflated = fs::read_to_string(BALLOON_DEBUGFS)
+            .ok()
+            .and_then(|stats| parse_balloon(&stats));
+        self.total = fs::read_to_string(MEMINFO)
+            .ok()
+            .and_then(|meminfo| {
+                meminfo
+                    .lines()
+                    .find_map(|line| line.strip_prefix("MemTotal:"))
+                    .and_then(|kib| kib.trim().trim_end_matches("kB").trim().parse::<u64>().ok())
+            })
+            .map(|kib| kib * 1024)
+            .unwrap_or_default();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inflated.is_none()
+    }
+
+    pub fn view(&self) -> Element<Message> {
+        widget::text(fl!(
+            "balloon",
+            inflated = human_bytes(self.inflated.unwrap_or_default() as f64),
+            total = human_bytes(self.total as f64)
+        ))
+        .width(Length::Fill)
+        .into()
+    }
+}
+
+/// Parses the debugfs stats of `virtio_balloon`, e.g. `inflated_kb: 1048576`. Older
+/// kernels only report pages.
+fn parse_balloon(stats: &str) -> Option<u64> {
+    let field = |name: &str| -> Option<u64> {
+        stats.lines().find_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            (key.trim() == name).then(|| value.trim().parse().ok())?
+        })
+    };
+    field("inflated_kb")
+        .map(|kib| kib * 1024)
+        .or_else(|| field("inflated_pages").map(|pages| pages * 4096))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bare_metal_is_not_a_guest() {
+        assert_eq!(parse_detect_virt("none\n"), None);
+        assert_eq!(parse_detect_virt(""), None);
+        assert_eq!(parse_detect_virt("kvm\n"), Some("kvm".to_string()));
+    }
+
+    #[test]
+    fn reads_the_inflated_size() {
+        assert_eq!(
+            parse_balloon("inflated_pages        :   262144\ninflated_kb           :  1048576\n"),
+            Some(1024 * 1024 * 1024)
+        );
+        assert_eq!(parse_balloon("inflated_pages: 2\n"), Some(8192));
+        assert_eq!(parse_balloon(""), None);
+    }
+}
@@ -0,0 +1,235 @@
+use crate::fl;
+use crate::layout;
+use crate::window::Message;
+use cosmic::iced::{Alignment, Length};
+use cosmic::widget;
+use cosmic::Element;
+use human_bytes::human_bytes;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use virt::connect::Connect;
+use virt::domain::Domain;
+use virt::sys;
+
+const REFRESH_EVERY: Duration = Duration::from_secs(2);
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum VmAction {
+    Start,
+    Pause,
+    Resume,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum VmState {
+    Running,
+    Paused,
+    Stopped,
+    Other,
+}
+
+#[derive(Clone, Debug)]
+pub struct VmUsage {
+    pub name: String,
+    pub state: VmState,
+    /// Share of the whole machine's CPU time
+    pub cpu_percent: f64,
+    /// Bytes currently assigned to the domain
+    pub memory: u64,
+}
+
+struct Shared {
+    domains: Result<Vec<VmUsage>, String>,
+    /// CPU time in nanoseconds per domain at the previous query
+    previous: HashMap<String, (u64, Instant)>,
+}
+
+impl Default for Shared {
+    fn default() -> Self {
+        Self {
+            domains: Ok(Vec::new()),
+            previous: HashMap::new(),
+        }
+    }
+}
+
+/// libvirt domains and their usage, queried on a background thread.
+pub struct VmMonitor {
+    shared: Arc<Mutex<Shared>>,
+    last_run: Option<Instant>,
+    /// Set while a query runs, a hung libvirt daemon would otherwise pile up threads
+    querying: Arc<AtomicBool>,
+}
+
+impl VmMonitor {
+    pub fn new() -> Self {
+        Self {
+            shared: Arc::new(Mutex::new(Shared::default())),
+            last_run: None,
+            querying: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    pub fn refresh(&mut self, uri: &str) {
+        if self
+            .last_run
+            .is_some_and(|time| time.elapsed() < REFRESH_EVERY)
+        {
+            return;
+        }
+        if self.querying.swap(true, Ordering::AcqRel) {
+            return;
+        }
+        self.last_run = Some(Instant::now());
+
+        let shared = self.shared.clone();
+        let querying = self.querying.clone();
+        let uri = uri.to_string();
+        thread::spawn(move || {
+            update_domains(&shared, &uri);
+            querying.store(false, Ordering::Release);
+        });
+    }
+
+    /// Runs a quick action and queries the domains again once it completed.
+    pub fn run(&self, uri: &str, name: String, action: VmAction) {
+        let shared = self.shared.clone();
+        let uri = uri.to_string();
+        thread::spawn(move || {
+            if let Err(err) = run_action(&uri, &name, action) {
+                tracing::error!("failed to {:?} domain {}: {}", action, name, err);
+            }
+            update_domains(&shared, &uri);
+        });
+    }
+
+    pub fn view(&self) -> Element<Message> {
+        let mut column = widget::column()
+            .spacing(4)
+            .width(Length::Fill)
+            .push(widget::text(fl!("virtual-machines")));
+
+        let domains = self
+            .shared
+            .lock()
+            .map(|shared| shared.domains.clone())
+            .unwrap_or_else(|err| Err(err.to_string()));
+        match domains {
+            Err(err) => column = column.push(widget::text(fl!("vms-unavailable", error = err))),
+            Ok(domains) => {
+                for vm in domains {
+                    let mut cells: Vec<Element<Message>> = vec![widget::text(vm.name.clone())
+                        .width(Length::Fill)
+                        .horizontal_alignment(layout::start_alignment())
+                        .into()];
+                    let action = match vm.state {
+                        VmState::Running => {
+                            cells.push(widget::text(format!("{:.1}%", vm.cpu_percent)).into());
+                            cells.push(widget::text(human_bytes(vm.memory as f64)).into());
+                            Some((fl!("vm-pause"), VmAction::Pause))
+                        }
+                        VmState::Paused => {
+                            cells.push(widget::text(fl!("vm-paused")).into());
+                            Some((fl!("vm-resume"), VmAction::Resume))
+                        }
+                        VmState::Stopped => {
+                            cells.push(widget::text(fl!("vm-stopped")).into());
+                            Some((fl!("vm-start"), VmAction::Start))
+                        }
+                        VmState::Other => None,
+                    };
+                    if let Some((label, action)) = action {
+                        cells.push(
+                            widget::button::standard(label)
+                                .on_press(Message::VmAction(vm.name, action))
+                                .into(),
+                        );
+                    }
+                    column = column.push(
+                        layout::directional_row(cells)
+                            .spacing(8)
+                            .align_items(Alignment::Center),
+                    );
+                }
+            }
+        }
+
+        column.into()
+    }
+}
+
+/// Queries without holding the lock so the popup never waits on libvirt.
+fn update_domains(shared: &Mutex<Shared>, uri: &str) {
+    let Ok(mut previous) = shared.lock().map(|shared| shared.previous.clone()) else {
+        return;
+    };
+    let result = query_domains(uri, &mut previous);
+    if let Err(err) = &result {
+        tracing::warn!("failed to query libvirt domains: {}", err);
+    }
+    if let Ok(mut shared) = shared.lock() {
+        shared.domains = result;
+        shared.previous = previous;
+    }
+}
+
+fn query_domains(
+    uri: &str,
+    previous: &mut HashMap<String, (u64, Instant)>,
+) -> Result<Vec<VmUsage>, String> {
+    let mut conn = Connect::open(Some(uri)).map_err(|err| err.to_string())?;
+    let cpus = std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1) as f64;
+    let now = Instant::now();
+
+    let mut domains = conn
+        .list_all_domains(0)
+        .map_err(|err| err.to_string())?
+        .into_iter()
+        .filter_map(|domain| {
+            let name = domain.get_name().ok()?;
+            let info = domain.get_info().ok()?;
+            let cpu_percent = match previous.get(&name) {
+                Some((cpu_time, time)) => {
+                    let elapsed = now.duration_since(*time).as_nanos() as f64;
+                    info.cpu_time.saturating_sub(*cpu_time) as f64 / (elapsed * cpus) * 100.0
+                }
+                None => 0.0,
+            };
+            previous.insert(name.clone(), (info.cpu_time, now));
+
+            Some(VmUsage {
+                name,
+                state: match info.state {
+                    sys::VIR_DOMAIN_RUNNING => VmState::Running,
+                    sys::VIR_DOMAIN_PAUSED => VmState::Paused,
+                    sys::VIR_DOMAIN_SHUTOFF => VmState::Stopped,
+                    _ => VmState::Other,
+                },
+                cpu_percent,
+                // libvirt reports KiB
+                memory: info.memory * 1024,
+            })
+        })
+        .collect::<Vec<_>>();
+    domains.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let _ = conn.close();
+    Ok(domains)
+}
+
+fn run_action(uri: &str, name: &str, action: VmAction) -> Result<(), virt::error::Error> {
+    let mut conn = Connect::open(Some(uri))?;
+    let domain = Domain::lookup_by_name(&conn, name)?;
+    let result = match action {
+        VmAction::Start => domain.create().map(|_| ()),
+        VmAction::Pause => domain.suspend().map(|_| ()),
+        VmAction::Resume => domain.resume().map(|_| ()),
+    };
+    let _ = conn.close();
+    result
+}
@@ -0,0 +1,182 @@
+use crate::fl;
+use crate::layout;
+use crate::state;
+use crate::window::Message;
+use chrono::{Days, Local, NaiveDate};
+use cosmic::iced::{Alignment, Length};
+use cosmic::iced_widget::ProgressBar;
+use cosmic::widget;
+use cosmic::Element;
+use human_bytes::human_bytes;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+const WEEKLY_FILE: &str = "weekly-usage.bin";
+const DAYS: u64 = 7;
+const BAR_WIDTH: f32 = 120.0;
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+struct DayTotals {
+    /// Sum of the CPU usage samples in percent, divided by `samples` for the average
+    cpu_sum: f64,
+    samples: u64,
+    /// Highest memory usage in percent
+    peak_memory: f64,
+    /// Bytes received and transmitted over every interface
+    transferred: u64,
+}
+
+impl DayTotals {
+    fn average_cpu(&self) -> f64 {
+        if self.samples == 0 {
+            return 0.0;
+        }
+        self.cpu_sum / self.samples as f64
+    }
+}
+
+/// Average CPU, peak memory and network traffic per day for the last week, persisted.
+pub struct WeeklyReport {
+    days: BTreeMap<NaiveDate, DayTotals>,
+    /// Network counters at the previous sample, since boot
+    last_transferred: Option<u64>,
+    dirty: bool,
+}
+
+impl WeeklyReport {
+    pub fn load() -> Self {
+        Self {
+            days: state::load(WEEKLY_FILE).unwrap_or_default(),
+            last_transferred: None,
+            dirty: false,
+        }
+    }
+
+    pub fn save(&mut self) {
+        if !self.dirty {
+            return;
+        }
+        match state::save(WEEKLY_FILE, &self.days) {
+            Ok(()) => self.dirty = false,
+            Err(err) => tracing::warn!("failed to save weekly usage: {}", err),
+        }
+    }
+
+    /// Adds a sample to today, `transferred` counts every interface since boot.
+    pub fn update(&mut self, cpu: f64, memory: Option<f64>, transferred: u64) {
+        // Counters restart after a reboot, traffic from before the first sample is unknown
+        let traffic = self
+            .last_transferred
+            .map(|last| transferred.saturating_sub(last))
+            .unwrap_or_default();
+        self.last_transferred = Some(transferred);
+        self.record(Local::now().date_naive(), cpu, memory, traffic);
+    }
+
+    fn record(&mut self, today: NaiveDate, cpu: f64, memory: Option<f64>, traffic: u64) {
+        let day = self.days.entry(today).or_default();
+        day.cpu_sum += cpu;
+        day.samples += 1;
+        if let Some(memory) = memory {
+            day.peak_memory = day.peak_memory.max(memory);
+        }
+        day.transferred += traffic;
+
+        let first = today - Days::new(DAYS - 1);
+        self.days.retain(|date, _| *date >= first);
+        self.dirty = true;
+    }
+
+    /// Totals over the whole week: average CPU, peak memory and bytes transferred.
+    fn summary(&self) -> (f64, f64, u64) {
+        let samples = self.days.values().map(|day| day.samples).sum::<u64>();
+        let cpu_sum = self.days.values().map(|day| day.cpu_sum).sum::<f64>();
+        (
+            if samples == 0 {
+                0.0
+            } else {
+                cpu_sum / samples as f64
+            },
+            self.days
+                .values()
+                .map(|day| day.peak_memory)
+                .fold(0.0, f64::max),
+            self.days.values().map(|day| day.transferred).sum(),
+        )
+    }
+
+    pub fn view(&self) -> Element<Message> {
+        let (cpu, memory, transferred) = self.summary();
+        let mut column = widget::column()
+            .spacing(4)
+            .width(Length::Fill)
+            .push(widget::text(fl!("weekly-report")))
+            .push(widget::text(fl!(
+                "weekly-summary",
+                cpu = format!("{:.0}", cpu),
+                memory = format!("{:.0}", memory),
+                transferred = human_bytes(transferred as f64)
+            )));
+
+        // Oldest first, reading like the charts
+        for (date, day) in &self.days {
+            column = column.push(
+                layout::directional_row(vec![
+                    widget::text(date.format("%a %d").to_string())
+                        .width(Length::Fill)
+                        .horizontal_alignment(layout::start_alignment())
+                        .into(),
+                    ProgressBar::new(0.0..=100.0, day.average_cpu() as f32)
+                        .width(Length::Fixed(BAR_WIDTH))
+                        .height(Length::Fixed(8.0))
+                        .into(),
+                    widget::text(format!("{:.0}%", day.average_cpu())).into(),
+                    widget::text(human_bytes(day.transferred as f64)).into(),
+                ])
+                .spacing(8)
+                .align_items(Alignment::Center),
+            );
+        }
+
+        column.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn weekly() -> WeeklyReport {
+        WeeklyReport {
+            days: BTreeMap::new(),
+            last_transferred: None,
+            dirty: false,
+        }
+    }
+
+    #[test]
+    fn summary_spans_the_whole_week() {
+        let mut weekly = weekly();
+        let monday = NaiveDate::from_ymd_opt(2024, 5, 6).unwrap();
+
+        weekly.record(monday, 20.0, Some(40.0), 1000);
+        weekly.record(monday, 40.0, Some(60.0), 500);
+        weekly.record(monday + Days::new(1), 90.0, None, 0);
+
+        assert_eq!(weekly.days[&monday].average_cpu(), 30.0);
+        assert_eq!(weekly.summary(), (50.0, 60.0, 1500));
+    }
+
+    #[test]
+    fn record_only_keeps_seven_days() {
+        let mut weekly = weekly();
+        let first = NaiveDate::from_ymd_opt(2024, 5, 1).unwrap();
+
+        for offset in 0..10 {
+            weekly.record(first + Days::new(offset), 10.0, None, 0);
+        }
+
+        assert_eq!(weekly.days.len(), 7);
+        assert_eq!(weekly.days.keys().next(), Some(&(first + Days::new(3))));
+    }
+}
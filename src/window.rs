@@ -1,12 +1,68 @@
+use crate::actions;
+use crate::alerts::{Alerts, Section, Severity};
+use crate::asahi::SmcMonitor;
+use crate::audio::AudioMonitor;
+use crate::badge::Badge;
+use crate::boot::BootInfo;
+use crate::budget::DataBudget;
+use crate::busy::BusyDetector;
+use crate::cgroups::{CgroupMonitor, UserMonitor};
 use crate::chart;
-use crate::chart::SystemChart;
-use crate::config::{Config, CONFIG_VERSION};
+use crate::chart::{ChartStyle, SystemChart};
+use crate::clock::ClockMonitor;
+use crate::config::{self, ChartPalette, Config, Profile, CONFIG_VERSION};
+use crate::connections::ConnectionMonitor;
+use crate::containers::ContainerMonitor;
+use crate::cpufreq::{self, CpuFrequency};
+use crate::daily::DailyUsage;
+use crate::detect::Hardware;
+use crate::disks::DiskMonitor;
+use crate::events::{EventKind, EventLog};
+use crate::export::{self, ExportFormat};
+#[cfg(feature = "prometheus")]
+use crate::exporter;
+use crate::filesystems::{self, FilesystemMonitor};
+use crate::fl;
+use crate::flash::{self, PanelFlash};
+use crate::gpu::{GpuCoolingMonitor, GpuProcesses};
+use crate::health::HealthMonitor;
+use crate::history;
+use crate::interrupts::InterruptMonitor;
+use crate::layout;
+use crate::mdstat::RaidMonitor;
+use crate::metrics;
+use crate::mqtt::{self, MqttSettings};
+use crate::network::NetworkTotals;
+use crate::peripherals::PeripheralMonitor;
+use crate::pods::PodMonitor;
+#[cfg(feature = "pools")]
+use crate::pools::PoolMonitor;
+use crate::power;
+use crate::processes::{ProcessList, ProcessSort};
+use crate::report;
+use crate::sensors::{self, SensorMonitor};
+use crate::smart::SmartMonitor;
+#[cfg(feature = "sqlite")]
+use crate::store::{self, Store};
+use crate::swap::CompressedSwap;
+use crate::throttle::ThrottleMonitor;
+use crate::units::{self, UnitMonitor};
+use crate::updates::{self, UpdateMonitor};
+use crate::virt::BalloonMonitor;
+#[cfg(feature = "libvirt")]
+use crate::vms::{VmAction, VmMonitor};
+use crate::weekly::WeeklyReport;
+use chrono::Local;
 use cosmic::app::Core;
+use cosmic::cosmic_config::CosmicConfigEntry;
 use cosmic::cosmic_theme::palette::WithAlpha;
+use cosmic::iced::event::wayland;
 use cosmic::iced::wayland::popup::{destroy_popup, get_popup};
+use cosmic::iced::widget::scrollable::{self, RelativeOffset};
 use cosmic::iced::window::Id;
 use cosmic::iced::{self, Command, Limits};
-use cosmic::iced::{Alignment, Length};
+use cosmic::iced::{Alignment, Color, Length};
+use cosmic::iced_futures::futures::channel::oneshot;
 use cosmic::iced_futures::Subscription;
 use cosmic::iced_style::application;
 use cosmic::Element;
@@ -14,23 +70,154 @@ use cosmic::Theme;
 use cosmic::{cosmic_config, widget};
 use cosmic_time::Duration;
 use plotters::style::RGBColor;
+use std::collections::{BTreeSet, HashMap};
+use std::path::PathBuf;
+use std::thread;
 
 pub const ID: &str = "app.arara.CosmicAppletSysStatus";
+const SAVE_HISTORY_EVERY: Duration = Duration::from_secs(30);
+const POPUP_MAX_WIDTH: f32 = 475.0;
+/// Until the panel's output reported its size
+const POPUP_MAX_HEIGHT: f32 = 1080.0;
+/// Left free of the output's height for the panel and the gap to the popup
+const POPUP_OUTPUT_MARGIN: f32 = 96.0;
+// Room left for the header pinned above the scrolled sections
+const POPUP_HEADER_HEIGHT: f32 = 48.0;
+const DEFAULT_CHART_STYLE: ChartStyle = ChartStyle {
+    accent: RGBColor(0x63, 0xd0, 0xdf),
+    grid: RGBColor(0x5c, 0x5c, 0x5c),
+    label: RGBColor(0xc4, 0xc4, 0xc4),
+    palette: ChartPalette::Accent,
+};
 
 pub struct Window {
     core: Core,
     config: Config,
-    #[allow(dead_code)]
     config_handler: Option<cosmic_config::Config>,
     popup: Option<Id>,
+    windowed: bool,
+    /// Width the charts are flowed into, the popup grows to its maximum width
+    content_width: f32,
+    /// Tallest the popup may grow, from the height of the panel's output
+    popup_max_height: f32,
+    /// Shown at the top of the popup after clicking "Show details" on an alert
+    focused: Option<Section>,
+    /// Chart shown first after clicking the single metric on the panel
+    focused_chart: Option<&'static str>,
     icon_name: String,
     chart: chart::SystemChart,
+    /// Discharging, as reported by UPower
+    on_battery: bool,
+    boot: BootInfo,
+    network: NetworkTotals,
+    busy: BusyDetector,
+    daily: DailyUsage,
+    weekly: WeeklyReport,
+    #[cfg(feature = "sqlite")]
+    store: Option<Store>,
+    events: EventLog,
+    note: String,
+    cpu_frequency: CpuFrequency,
+    /// Active power-profiles-daemon profile, `None` when the daemon is not running
+    power_profile: Option<String>,
+    budget: DataBudget,
+    filesystems: FilesystemMonitor,
+    compressed_swap: CompressedSwap,
+    balloon: BalloonMonitor,
+    alerts: Alerts,
+    cgroups: CgroupMonitor,
+    interrupts: InterruptMonitor,
+    users: UserMonitor,
+    processes: ProcessList,
+    connections: ConnectionMonitor,
+    gpu_processes: GpuProcesses,
+    gpu_cooling: GpuCoolingMonitor,
+    /// Only found on Raspberry Pis and other ARM boards
+    throttle: Option<ThrottleMonitor>,
+    /// Only found on Apple Silicon Macs running Asahi
+    smc: Option<SmcMonitor>,
+    peripherals: PeripheralMonitor,
+    audio: AudioMonitor,
+    // Kept here so they survive the list being rebuilt on every sample
+    process_filter: String,
+    process_sort: ProcessSort,
+    process_tree: bool,
+    /// PIDs whose children are shown in the process tree
+    expanded_processes: BTreeSet<u32>,
+    disks: DiskMonitor,
+    smart: SmartMonitor,
+    pods: PodMonitor,
+    containers: ContainerMonitor,
+    health: HealthMonitor,
+    flash: PanelFlash,
+    clock: ClockMonitor,
+    updates: UpdateMonitor,
+    units: UnitMonitor,
+    raid: RaidMonitor,
+    sensors: SensorMonitor,
+    editing_sensors: bool,
+    #[cfg(feature = "pools")]
+    pools: PoolMonitor,
+    #[cfg(feature = "libvirt")]
+    vms: VmMonitor,
+    export_status: Option<String>,
+    /// What the last hardware detection found
+    hardware_status: Option<String>,
+    #[cfg(feature = "prometheus")]
+    exporter: Option<exporter::Exporter>,
+    mqtt: Option<mqtt::Publisher>,
 }
 
 #[derive(Clone, Debug)]
 pub enum Message {
     Config(Config),
-    TogglePopup,
+    /// Opens the popup on the given chart, or closes it
+    TogglePopup(Option<&'static str>),
+    PopupClosed(Id),
+    /// Switches the popup between the quick glance and the full view
+    ExpandPopup(bool),
+    ScrollToTop,
+    /// A window or popup surface was resized to the given width
+    Resized(Id, f32),
+    /// Name and logical height of an output
+    OutputHeight(String, f32),
+    /// A surface moved to an output with a different, possibly fractional, scale
+    ScaleFactorChanged(Id, f64),
+    OnBattery(bool),
+    /// `true` right before suspending, `false` after resuming
+    Sleep(bool),
+    NoteInput(String),
+    AddNote,
+    PowerProfile(String),
+    SetPowerProfile(String),
+    /// Sent once a power profile change was requested
+    PowerProfileRequested,
+    SaveHistory,
+    ExportHistory(ExportFormat),
+    /// Path of the written file or why the export failed
+    HistoryExported(Result<PathBuf, String>),
+    CopyReport,
+    DetectHardware,
+    ApplyProfile(Profile),
+    ResetNetworkTotals,
+    FilterProcesses(String),
+    SortProcesses(ProcessSort),
+    /// Nests processes under their parents
+    ProcessTree(bool),
+    ExpandProcess(u32, bool),
+    SetGovernor(String),
+    RestartUnit(String),
+    EditSensors(bool),
+    RenameSensor(String, String),
+    RegroupSensor(String, String),
+    #[cfg(feature = "libvirt")]
+    VmAction(String, VmAction),
+    /// Index into `quick_actions`
+    RunAction(usize),
+    /// The compositor let the popup draw a frame, only with `frame_time_metric`
+    Frame(std::time::Instant),
+    /// Next frame of the panel flash
+    Flash(cosmic_time::Instant),
     Tick,
 }
 
@@ -38,6 +225,475 @@ pub enum Message {
 pub struct Flags {
     pub config_handler: Option<cosmic_config::Config>,
     pub config: Config,
+    /// Run as a regular window instead of a panel applet, for development
+    pub windowed: bool,
+}
+
+impl Window {
+    fn popup_content(&self) -> Element<Message> {
+        if !self.config.popup_expanded {
+            return self.glance_content();
+        }
+
+        #[allow(unused_variables)]
+        let cosmic::cosmic_theme::Spacing {
+            space_none, // 0
+            space_xxxs, // 4
+            space_xxs,  // 8
+            space_xs,   // 12
+            space_s,    // 16
+            space_m,    // 24
+            space_l,    // 32
+            space_xl,   // 48
+            space_xxl,  // 64
+            space_xxxl, // 128
+        } = self.core.system_theme().cosmic().spacing;
+
+        // let mut cols = widget::column::with_capacity(2).width(Length::Fill);
+
+        // let cpu_info = self.system.cpus().first().unwrap().brand();
+        // let cpu_usage = self.system.global_cpu_info().cpu_usage();
+        // cols = cols
+        //     .push(backend::cpu_widget(cpu_info, cpu_usage))
+        //     .push(backend::memory_widget(
+        //         self.system.used_memory(),
+        //         self.system.total_memory(),
+        //     ));
+
+        // let mut labels = self
+        //     .components
+        //     .iter()
+        //     .map(|v| (v.label().to_string(), v.temperature()))
+        //     .collect::<Vec<(String, f32)>>();
+
+        // labels.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        // for (label, temp) in labels {
+        //     cols = cols.push(
+        //         widget::text(format!("{} {}°C", label, temp.trunc() as u32))
+        //             .apply(widget::container)
+        //             .padding(12)
+        //             .apply(Element::from),
+        //     );
+        // }
+
+        let export_row = layout::directional_row(vec![
+            widget::text(fl!("export-history")).into(),
+            widget::button::standard(fl!("export-csv"))
+                .on_press(Message::ExportHistory(ExportFormat::Csv))
+                .into(),
+            widget::button::standard(fl!("export-json"))
+                .on_press(Message::ExportHistory(ExportFormat::Json))
+                .into(),
+            widget::button::standard(fl!("copy-report"))
+                .on_press(Message::CopyReport)
+                .into(),
+        ])
+        .spacing(space_xxs)
+        .align_items(Alignment::Center);
+
+        let mut gpu_details = widget::column().spacing(10).width(Length::Fill);
+        if !self.gpu_cooling.is_empty() {
+            gpu_details = gpu_details.push(self.gpu_cooling.view());
+        }
+        if self.config.show_gpu_processes {
+            gpu_details = gpu_details.push(self.gpu_processes.view());
+        }
+        let mut details = HashMap::new();
+        details.insert("GPU", gpu_details.into());
+        let mut memory_details = widget::column().spacing(10).width(Length::Fill);
+        if self.config.show_compressed_swap && !self.compressed_swap.is_empty() {
+            memory_details = memory_details.push(self.compressed_swap.view());
+        }
+        if self.config.show_balloon && !self.balloon.is_empty() {
+            memory_details = memory_details.push(self.balloon.view());
+        }
+        details.insert("Memory", memory_details.into());
+        if self.config.audio_metrics && !self.audio.is_empty() {
+            details.insert("XRuns", self.audio.view());
+        }
+
+        // Stays in place while the sections below scroll
+        let mut header = vec![self.boot.view()];
+        if let Some(throttle) = self.throttle.as_ref().filter(|t| t.is_throttled()) {
+            header.push(throttle.badge(self.core.system_theme().cosmic()));
+        }
+        if self.config.show_updates {
+            if let Some(badge) = self.updates.badge(self.core.system_theme().cosmic()) {
+                header.push(badge);
+            }
+        }
+        header.push(widget::horizontal_space(Length::Fill).into());
+        header.push(
+            widget::button::standard(fl!("scroll-to-top"))
+                .on_press(Message::ScrollToTop)
+                .into(),
+        );
+        header.push(
+            widget::button::standard(fl!("collapse"))
+                .on_press(Message::ExpandPopup(false))
+                .into(),
+        );
+        let header = layout::directional_row(header)
+            .spacing(space_xxs)
+            .align_items(Alignment::Center);
+
+        let mut content = widget::column()
+            .spacing(10)
+            .align_items(Alignment::Start)
+            .width(Length::Shrink)
+            .height(Length::Shrink);
+        if let Some(focused) = self.focused.and_then(|section| self.section_view(section)) {
+            content = content.push(focused);
+        }
+        if !self.config.quick_actions.is_empty() {
+            content = content.push(actions::view(&self.config.quick_actions));
+        }
+        content = content
+            .push(
+                self.chart
+                    .view(details, self.content_width, self.focused_chart),
+            )
+            .push(self.network.view());
+        if !self.busy.is_empty() {
+            content = content.push(self.busy.view(&self.config));
+        }
+        if self.config.daily_load_threshold > 0 {
+            content = content.push(self.daily.view(&self.config));
+        }
+        if self.config.weekly_report {
+            content = content.push(self.weekly.view());
+        }
+        if let Some(active) = &self.power_profile {
+            content = content.push(power_profile_row(active));
+        }
+        if !self.peripherals.is_empty() {
+            content = content.push(self.peripherals.view());
+        }
+        if self.config.show_cpu_frequency {
+            content = content.push(self.cpu_frequency.view());
+        }
+        if self.config.show_interrupts {
+            content = content.push(self.interrupts.view());
+        }
+        if let Some(throttle) = &self.throttle {
+            content = content.push(throttle.view());
+        }
+        if let Some(smc) = &self.smc {
+            content = content.push(smc.view());
+        }
+        if let Some(sensors) = self.unfocused_section(Section::Temperatures) {
+            content = content.push(sensors);
+        }
+        if self.config.show_cgroups {
+            content = content.push(self.cgroups.view());
+        }
+        if self.config.show_users {
+            content = content.push(self.users.view());
+        }
+        if self.config.show_processes {
+            content = content.push(self.processes.view(
+                &self.process_filter,
+                self.process_sort,
+                self.process_tree,
+                &self.expanded_processes,
+            ));
+        }
+        if self.config.show_connections {
+            content = content.push(self.connections.view());
+        }
+        if self.config.show_disks {
+            content = content.push(self.disks.view());
+        }
+        if self.config.show_filesystems {
+            content = content.push(self.filesystems.view());
+        }
+        if let Some(smart) = self.unfocused_section(Section::Smart) {
+            content = content.push(smart);
+        }
+        if let Some(raid) = self.unfocused_section(Section::Raid) {
+            content = content.push(raid);
+        }
+        if let Some(pools) = self.unfocused_section(Section::Pools) {
+            content = content.push(pools);
+        }
+        #[cfg(feature = "libvirt")]
+        if self.config.show_vms {
+            content = content.push(self.vms.view());
+        }
+        if self.config.show_pods {
+            content = content.push(self.pods.view());
+        }
+        if self.config.show_containers {
+            content = content.push(self.containers.view());
+        }
+        if !self.config.watched_units.is_empty() {
+            content = content.push(self.units.view());
+        }
+        if self.config.show_clock_sync {
+            content = content.push(self.clock.view());
+        }
+        if !self.config.health_checks.is_empty() {
+            content = content.push(self.health.view(
+                &self.config.health_checks,
+                self.core.system_theme().cosmic(),
+            ));
+        }
+        content = content.push(self.events.view(&self.note));
+        content = content.push(export_row);
+        if let Some(budget) = self.unfocused_section(Section::DataBudget) {
+            content = content.push(budget);
+        }
+        if let Some(status) = &self.export_status {
+            content = content.push(widget::text(status.as_str()));
+        }
+        content = content.push(profile_row(self.config.profile));
+        content = content.push(
+            widget::button::standard(fl!("detect-hardware")).on_press(Message::DetectHardware),
+        );
+        if let Some(status) = &self.hardware_status {
+            content = content.push(widget::text(status.as_str()));
+        }
+
+        let sections = widget::container(
+            widget::scrollable(content)
+                .id(popup_scrollable())
+                .height(Length::Shrink),
+        )
+        .max_height(self.popup_max_height - POPUP_HEADER_HEIGHT);
+
+        let chart_container = widget::container(widget::column().push(header).push(sections))
+            .width(Length::Fill)
+            .height(Length::Shrink)
+            .padding(5)
+            .center_x()
+            .center_y();
+
+        // let content = widget::column::with_children(vec![cols.into(), chart_container.into()])
+        //     .padding([space_xxs, space_xxxs])
+        //     .spacing(space_m);
+
+        chart_container.into()
+    }
+
+    /// Current values or the sparkline strip, with a button growing the popup into the
+    /// full view.
+    fn glance_content(&self) -> Element<Message> {
+        let glance = if self.config.glance_sparklines {
+            let mut rows = self.chart.sparklines();
+            if let Some(hottest) = self.sensors.hottest() {
+                let history = self.sensors.hottest_history();
+                let max = history.iter().copied().fold(100.0, f64::max);
+                rows.push((
+                    fl!("temperature"),
+                    format!("{:.0}°C", hottest),
+                    self.chart.sparkline(history, 0.0..max),
+                ));
+            }
+            chart::sparkline_strip(rows)
+        } else {
+            self.chart.glance_view()
+        };
+        let content = widget::column()
+            .spacing(10)
+            .width(Length::Fill)
+            .push(glance)
+            .push(widget::button::standard(fl!("expand")).on_press(Message::ExpandPopup(true)));
+
+        widget::container(content)
+            .width(Length::Fill)
+            .padding(12)
+            .into()
+    }
+
+    /// The chart of the metric on the panel, when it shows only one.
+    fn panel_focus(&self) -> Option<&'static str> {
+        self.config
+            .panel_values
+            .then(|| self.config.panel_metric.source())
+            .flatten()
+    }
+
+    /// Whether the full view with charts and detail sections is on screen.
+    fn is_popup_visible(&self) -> bool {
+        (self.windowed || self.popup.is_some()) && self.config.popup_expanded
+    }
+
+    /// Whether to run the collectors that scan every process, cgroup or socket, or spawn
+    /// tools: only for the full view, and not on battery.
+    fn collect_details(&self) -> bool {
+        self.is_popup_visible() && !self.on_battery
+    }
+
+    fn open_popup(&mut self) -> Command<cosmic::app::Message<Message>> {
+        let new_id = Id::unique();
+        self.popup.replace(new_id);
+        // Until the popup surface reports its own scale
+        self.chart.set_scale_factor(self.core.scale_factor() as f64);
+        self.chart.set_visible(self.is_popup_visible());
+        let mut popup_settings =
+            self.core
+                .applet
+                .get_popup_settings(Id::MAIN, new_id, None, None, None);
+        popup_settings.positioner.size_limits = Limits::NONE
+            .max_width(POPUP_MAX_WIDTH)
+            .min_width(300.0)
+            .min_height(100.0)
+            .max_height(self.popup_max_height);
+        get_popup(popup_settings)
+    }
+
+    /// The sections alerts link to, `None` while there is nothing to show.
+    fn section_view(&self, section: Section) -> Option<Element<Message>> {
+        match section {
+            Section::Temperatures => (!self.sensors.is_empty()).then(|| {
+                self.sensors.view(
+                    &self.config,
+                    self.core.system_theme().cosmic(),
+                    self.editing_sensors,
+                )
+            }),
+            Section::Smart => self.config.smart_enabled.then(|| self.smart.view()),
+            // Only shown on machines that actually have md arrays
+            Section::Raid => (!self.raid.is_empty()).then(|| self.raid.view()),
+            #[cfg(feature = "pools")]
+            Section::Pools => (!self.pools.is_empty()).then(|| self.pools.view()),
+            #[cfg(not(feature = "pools"))]
+            Section::Pools => None,
+            Section::DataBudget => (self.config.data_budget_bytes > 0).then(|| {
+                widget::text(fl!(
+                    "data-budget",
+                    interface = self.config.data_budget_interface.as_str(),
+                    usage = self.budget.usage(&self.config)
+                ))
+                .into()
+            }),
+        }
+    }
+
+    /// Every section except the focused one, which is already shown at the top.
+    fn unfocused_section(&self, section: Section) -> Option<Element<Message>> {
+        if self.focused == Some(section) {
+            return None;
+        }
+        self.section_view(section)
+    }
+
+    fn record_alert_events(&mut self) {
+        let raised = self.alerts.take_raised();
+        if raised.is_empty() {
+            return;
+        }
+        for alert in raised {
+            self.events.record(EventKind::Alert, alert.summary);
+        }
+        self.chart.set_markers(self.events.markers());
+    }
+
+    /// Hands freshly collected samples to the enabled integrations.
+    fn publish_samples(&self) {
+        #[cfg(feature = "prometheus")]
+        if let Some(exporter) = &self.exporter {
+            exporter.publish(self.chart.latest());
+        }
+        if let Some(mqtt) = &self.mqtt {
+            mqtt.publish(self.chart.latest());
+        }
+    }
+
+    /// Memory usage in percent, whichever scale the memory chart uses.
+    fn memory_percent(&self) -> Option<f64> {
+        self.chart.current("memory").or_else(|| {
+            let used = self.chart.current("memory_used")?;
+            let total = self
+                .chart
+                .current("memory_total")
+                .filter(|total| *total > 0.0)?;
+            Some(used / total * 100.0)
+        })
+    }
+
+    /// Newest value of a series of `panel_metric` with its unit. Memory is a percentage
+    /// also while `memory_scale` plots it in GiB.
+    fn panel_value(&self, series: &str) -> Option<(f64, &str)> {
+        match series {
+            "memory" => Some((self.memory_percent()?, "%")),
+            series => Some((self.chart.current(series)?, self.chart.unit(series)?)),
+        }
+    }
+
+    /// Enables the optional sections matching the hardware and saves the result.
+    fn detect_hardware(&mut self) {
+        let hardware = Hardware::probe();
+        hardware.apply(&mut self.config);
+        self.config.hardware_detected = true;
+        self.save_config();
+        self.hardware_status = Some(hardware.summary());
+    }
+
+    /// Saves and applies several settings changed at once, e.g. by a profile.
+    fn save_config(&mut self) {
+        if let Some(config_handler) = &self.config_handler {
+            if let Err(err) = self.config.write_entry(config_handler) {
+                tracing::error!("failed to save config: {}", err);
+            }
+        }
+        self.chart.set_config(&self.config);
+        self.sync_exporter();
+        self.sync_mqtt();
+    }
+
+    /// Starts, restarts or stops the Prometheus endpoint to match the config.
+    fn sync_exporter(&mut self) {
+        #[cfg(feature = "prometheus")]
+        {
+            if !self.config.prometheus_enabled {
+                self.exporter = None;
+                return;
+            }
+            if let Some(exporter) = &self.exporter {
+                if exporter.port() == self.config.prometheus_port && exporter.is_running() {
+                    return;
+                }
+            }
+
+            // Release the old port before binding the new one
+            self.exporter = None;
+            self.exporter = match exporter::Exporter::spawn(self.config.prometheus_port) {
+                Ok(exporter) => Some(exporter),
+                Err(err) => {
+                    tracing::error!("failed to start prometheus exporter thread: {}", err);
+                    None
+                }
+            };
+        }
+    }
+
+    /// Connects, reconnects or disconnects the MQTT publisher to match the config.
+    fn sync_mqtt(&mut self) {
+        if !self.config.mqtt_enabled {
+            self.mqtt = None;
+            return;
+        }
+
+        let settings = MqttSettings {
+            broker_url: self.config.mqtt_broker_url.clone(),
+            topic: self.config.mqtt_topic.clone(),
+            username: self.config.mqtt_username.clone(),
+            password: self.config.mqtt_password.clone(),
+        };
+        if self.mqtt.as_ref().map(|mqtt| mqtt.settings()) == Some(&settings) {
+            return;
+        }
+
+        self.mqtt = match mqtt::Publisher::connect(settings) {
+            Ok(publisher) => Some(publisher),
+            Err(err) => {
+                tracing::error!("failed to set up mqtt publishing: {}", err);
+                None
+            }
+        };
+    }
 }
 
 impl cosmic::Application for Window {
@@ -60,30 +716,110 @@ impl cosmic::Application for Window {
     ) -> (Self, Command<cosmic::app::Message<Self::Message>>) {
         let config = flags.config;
 
-        let theme = core.applet.theme().expect("applet them could not be get");
-        let accent_color = theme
-            .cosmic()
-            .accent_color()
-            .into_format::<u8, u8>()
-            .without_alpha();
-        let chart_color = RGBColor(accent_color.red, accent_color.green, accent_color.blue);
-        println!("{:?}", accent_color);
+        let chart_style = match core.applet.theme() {
+            Some(theme) => chart_style(theme.cosmic(), config.chart_palette),
+            None => {
+                tracing::warn!("applet theme unavailable, using default chart colors");
+                DEFAULT_CHART_STYLE
+            }
+        };
+
+        let mut chart = SystemChart::new(chart_style, &config);
+        if let Some(history) = history::load() {
+            chart.restore(history);
+        }
 
-        let window = Window {
+        let mut window = Window {
             core,
             config,
             config_handler: flags.config_handler,
             popup: None,
+            windowed: flags.windowed,
+            content_width: POPUP_MAX_WIDTH,
+            popup_max_height: POPUP_MAX_HEIGHT,
+            focused: None,
+            focused_chart: None,
             icon_name: ID.to_string(),
-            chart: SystemChart::new(chart_color),
+            chart,
+            on_battery: false,
+            boot: BootInfo::load(),
+            network: NetworkTotals::new(),
+            busy: BusyDetector::default(),
+            daily: DailyUsage::load(),
+            weekly: WeeklyReport::load(),
+            #[cfg(feature = "sqlite")]
+            store: Store::open(),
+            events: EventLog::default(),
+            note: String::new(),
+            cpu_frequency: CpuFrequency::default(),
+            power_profile: None,
+            budget: DataBudget::load(),
+            filesystems: FilesystemMonitor::load(),
+            compressed_swap: CompressedSwap::default(),
+            balloon: BalloonMonitor::default(),
+            alerts: Alerts::default(),
+            cgroups: CgroupMonitor::default(),
+            interrupts: InterruptMonitor::default(),
+            users: UserMonitor::default(),
+            processes: ProcessList::new(),
+            connections: ConnectionMonitor::default(),
+            gpu_processes: GpuProcesses::new(),
+            gpu_cooling: GpuCoolingMonitor::new(),
+            throttle: ThrottleMonitor::detect(),
+            smc: SmcMonitor::detect(),
+            peripherals: PeripheralMonitor::default(),
+            audio: AudioMonitor::default(),
+            process_filter: String::new(),
+            process_sort: ProcessSort::default(),
+            process_tree: false,
+            expanded_processes: BTreeSet::new(),
+            disks: DiskMonitor::default(),
+            smart: SmartMonitor::new(),
+            pods: PodMonitor::new(),
+            containers: ContainerMonitor::new(),
+            health: HealthMonitor::new(),
+            flash: PanelFlash::new(),
+            clock: ClockMonitor::new(),
+            updates: UpdateMonitor::new(),
+            units: UnitMonitor::new(),
+            raid: RaidMonitor::default(),
+            sensors: SensorMonitor::default(),
+            editing_sensors: false,
+            #[cfg(feature = "pools")]
+            pools: PoolMonitor::new(),
+            #[cfg(feature = "libvirt")]
+            vms: VmMonitor::new(),
+            export_status: None,
+            hardware_status: None,
+            #[cfg(feature = "prometheus")]
+            exporter: None,
+            mqtt: None,
         };
 
+        if !window.config.hardware_detected {
+            if config::is_unsaved() {
+                window.detect_hardware();
+            } else {
+                // Configured before detection existed, don't override the user's choices
+                window.config.hardware_detected = true;
+                window.save_config();
+            }
+        }
+        window.sync_exporter();
+        window.sync_mqtt();
+        // There is no popup to open, the charts are always on screen
+        if window.windowed {
+            window
+                .chart
+                .set_scale_factor(window.core.scale_factor() as f64);
+            window.chart.set_visible(window.config.popup_expanded);
+        }
+
         (window, Command::none())
     }
 
     fn update(&mut self, message: Self::Message) -> Command<cosmic::app::Message<Self::Message>> {
         // Helper for updating config values efficiently
-        #[allow(unused_macros)]
         macro_rules! config_set {
             ($name: ident, $value: expr) => {
                 match &self.config_handler {
@@ -91,13 +827,17 @@ impl cosmic::Application for Window {
                         match paste::paste! { self.config.[<set_ $name>](config_handler, $value) } {
                             Ok(_) => {}
                             Err(err) => {
-                                eprintln!("failed to save config {:?}: {}", stringify!($name), err);
+                                tracing::error!(
+                                    "failed to save config {:?}: {}",
+                                    stringify!($name),
+                                    err
+                                );
                             }
                         }
                     }
                     None => {
                         self.config.$name = $value;
-                        eprintln!(
+                        tracing::warn!(
                             "failed to save config {:?}: no config handler",
                             stringify!($name),
                         );
@@ -107,28 +847,333 @@ impl cosmic::Application for Window {
         }
 
         match message {
-            Message::Tick => self.chart.update(),
+            Message::Tick => {
+                if self.chart.update() {
+                    self.alerts
+                        .set_quiet_hours(&self.config, Local::now().time());
+                    if let Some(cpu) = self.chart.current("cpu") {
+                        self.daily
+                            .update(&self.config, cpu, self.chart.sample_every());
+                        if self.busy.update(&self.config, cpu) {
+                            let bands = self
+                                .busy
+                                .periods(&self.config)
+                                .iter()
+                                .map(|period| (period.started_at, period.ended_at))
+                                .collect();
+                            self.chart.set_bands(bands);
+                        }
+                    }
+                    self.network.refresh();
+                    if self.config.weekly_report {
+                        if let Some(cpu) = self.chart.current("cpu") {
+                            self.weekly.update(
+                                cpu,
+                                self.memory_percent(),
+                                self.network.boot_total_all(),
+                            );
+                        }
+                    }
+                    for alert in self.budget.update(&self.config, &self.network) {
+                        self.alerts.raise(alert);
+                    }
+                    if self.config.smart_enabled {
+                        let smart_alerts = self.smart.poll();
+                        self.alerts.sync("smart-", smart_alerts);
+                    }
+                    // Also while the popup is closed, the estimate needs a history
+                    if self.config.show_filesystems {
+                        if let Some(alerts) = self.filesystems.refresh(&self.config) {
+                            self.alerts.sync(filesystems::ALERT_PREFIX, alerts);
+                        }
+                    }
+                    if self.config.show_clock_sync {
+                        let clock_alerts = self.clock.poll();
+                        self.alerts.sync("clock-", clock_alerts);
+                    }
+                    if self.config.show_updates {
+                        let update_alerts = self.updates.poll(&self.config);
+                        self.alerts.sync(updates::ALERT_PREFIX, update_alerts);
+                    }
+                    let temperature_alerts = self.sensors.refresh(&self.config);
+                    self.alerts.sync(sensors::ALERT_PREFIX, temperature_alerts);
+                    let raid_alerts = self.raid.refresh();
+                    self.alerts.sync("raid-", raid_alerts);
+                    #[cfg(feature = "pools")]
+                    {
+                        let pool_alerts = self.pools.poll();
+                        self.alerts.sync("pool-", pool_alerts);
+                    }
+                    let critical = self.config.panel_flash
+                        && flash::is_critical(
+                            &self.config,
+                            self.alerts.ongoing(),
+                            self.memory_percent(),
+                            self.sensors.hottest(),
+                        );
+                    self.flash.set_active(critical, self.config.reduced_motion);
+                    // Scanning every cgroup is only worth it while somebody looks at it
+                    if self.config.show_cgroups && self.collect_details() {
+                        self.cgroups.refresh();
+                    }
+                    if self.config.show_cpu_frequency && self.is_popup_visible() {
+                        self.cpu_frequency.refresh();
+                    }
+                    if self.config.show_interrupts && self.collect_details() {
+                        self.interrupts.refresh();
+                    }
+                    if self.config.show_users && self.collect_details() {
+                        self.users.refresh();
+                    }
+                    if self.config.show_processes && self.collect_details() {
+                        self.processes.refresh();
+                    }
+                    if self.config.show_compressed_swap && self.is_popup_visible() {
+                        self.compressed_swap.refresh();
+                    }
+                    if self.config.show_balloon && self.is_popup_visible() {
+                        self.balloon.refresh();
+                    }
+                    if self.config.show_disks && self.collect_details() {
+                        self.disks.refresh();
+                    }
+                    if self.config.show_connections && self.collect_details() {
+                        self.connections.refresh();
+                    }
+                    if self.config.audio_metrics && self.is_popup_visible() {
+                        self.audio.refresh();
+                    }
+                    if self.config.show_gpu_processes && self.is_popup_visible() {
+                        self.gpu_processes.refresh();
+                    }
+                    if self.is_popup_visible() {
+                        self.gpu_cooling.refresh();
+                        if let Some(throttle) = &mut self.throttle {
+                            throttle.refresh();
+                        }
+                        if let Some(smc) = &mut self.smc {
+                            smc.refresh();
+                        }
+                        self.peripherals.refresh();
+                    }
+                    if self.config.show_pods && self.collect_details() {
+                        self.pods.poll(&self.config.pods_context);
+                    }
+                    if self.config.show_containers && self.collect_details() {
+                        self.containers.poll();
+                    }
+                    if !self.config.watched_units.is_empty() && self.is_popup_visible() {
+                        self.units.poll(&self.config.watched_units);
+                    }
+                    // Also while the popup is closed, for the dot on the panel
+                    if !self.config.health_checks.is_empty() {
+                        self.health.poll(&self.config.health_checks);
+                    }
+                    #[cfg(feature = "libvirt")]
+                    if self.config.show_vms && self.is_popup_visible() {
+                        self.vms.refresh(&self.config.libvirt_uri);
+                    }
+                    self.record_alert_events();
+                    self.publish_samples();
+                    #[cfg(feature = "sqlite")]
+                    if let Some(store) = &mut self.store {
+                        store.record(chrono::Utc::now().timestamp_millis(), self.chart.latest());
+                    }
+                }
+                // "Show details" was clicked on an alert notification
+                if let Some(section) = self.alerts.take_activated() {
+                    self.focused = Some(section);
+                    config_set!(popup_expanded, true);
+                    let scroll = scrollable::snap_to(popup_scrollable(), RelativeOffset::START);
+                    if self.windowed || self.popup.is_some() {
+                        self.chart.set_visible(self.is_popup_visible());
+                        return scroll;
+                    }
+                    return Command::batch(vec![self.open_popup(), scroll]);
+                }
+            }
+            Message::OnBattery(on_battery) => {
+                self.on_battery = on_battery;
+                self.chart.set_on_battery(on_battery);
+            }
+            Message::Sleep(suspending) => {
+                let kind = if suspending {
+                    EventKind::Suspend
+                } else {
+                    EventKind::Resume
+                };
+                self.events.record(kind, String::new());
+                self.chart.set_markers(self.events.markers());
+                if !suspending {
+                    self.chart.insert_gap();
+                    self.processes.reset_rates();
+                }
+            }
+            Message::NoteInput(note) => self.note = note,
+            Message::AddNote => {
+                let note = std::mem::take(&mut self.note);
+                if !note.trim().is_empty() {
+                    self.events.record(EventKind::Note, note.trim().to_string());
+                    self.chart.set_markers(self.events.markers());
+                }
+            }
+            Message::PowerProfile(profile) => self.power_profile = Some(profile),
+            Message::SetPowerProfile(profile) => {
+                return Command::perform(power::set_power_profile(profile), |_| {
+                    cosmic::app::Message::App(Message::PowerProfileRequested)
+                });
+            }
+            Message::PowerProfileRequested => {}
+            Message::SaveHistory => {
+                if let Err(err) = history::save(&self.chart.history()) {
+                    tracing::warn!("failed to save history: {}", err);
+                }
+                self.budget.save();
+                self.daily.save();
+                self.weekly.save();
+                self.filesystems.save();
+                #[cfg(feature = "sqlite")]
+                if let Some(store) = &mut self.store {
+                    store.flush(chrono::Utc::now().timestamp_millis());
+                }
+            }
+            Message::ResetNetworkTotals => self.network.reset(),
+            Message::FilterProcesses(filter) => self.process_filter = filter,
+            Message::SortProcesses(sort) => self.process_sort = sort,
+            Message::ProcessTree(tree) => self.process_tree = tree,
+            Message::ExpandProcess(pid, true) => {
+                self.expanded_processes.insert(pid);
+            }
+            Message::ExpandProcess(pid, false) => {
+                self.expanded_processes.remove(&pid);
+            }
+            Message::SetGovernor(governor) => cpufreq::set_governor(governor),
+            Message::RestartUnit(unit) => units::restart(unit),
+            Message::EditSensors(editing) => self.editing_sensors = editing,
+            Message::RenameSensor(id, name) => {
+                let labels = sensors::update_label(&self.config, id, |label| label.name = name);
+                config_set!(sensor_labels, labels);
+            }
+            Message::RegroupSensor(id, group) => {
+                let labels = sensors::update_label(&self.config, id, |label| label.group = group);
+                config_set!(sensor_labels, labels);
+            }
+            #[cfg(feature = "libvirt")]
+            Message::VmAction(name, action) => self.vms.run(&self.config.libvirt_uri, name, action),
+            Message::Frame(at) => metrics::record_frame(at),
+            Message::Flash(now) => self.flash.now(now),
+            Message::RunAction(index) => {
+                if let Some(action) = self.config.quick_actions.get(index) {
+                    actions::run(action.clone());
+                }
+            }
+            Message::CopyReport => {
+                let report = report::markdown(self.chart.latest(), &self.events, Local::now());
+                return iced::clipboard::write(report);
+            }
+            Message::DetectHardware => self.detect_hardware(),
+            Message::ApplyProfile(profile) => {
+                profile.apply(&mut self.config);
+                self.save_config();
+            }
+            Message::ExportHistory(format) => {
+                // The long-term history can be a year of rows, read and write it off the
+                // UI thread. The charts' own history is the fallback
+                let history = self.chart.history();
+                #[cfg(feature = "sqlite")]
+                let stored = self
+                    .store
+                    .as_ref()
+                    .and_then(Store::path)
+                    .map(|path| path.to_path_buf());
+                let (sender, receiver) = oneshot::channel();
+                thread::spawn(move || {
+                    #[cfg(feature = "sqlite")]
+                    let history = match stored {
+                        Some(path) => {
+                            store::read_history(&path, chrono::Utc::now().timestamp_millis())
+                                .unwrap_or_else(|err| {
+                                    tracing::warn!("failed to read long-term history: {}", err);
+                                    history
+                                })
+                        }
+                        None => history,
+                    };
+                    let _ = sender
+                        .send(export::export(&history, format).map_err(|err| err.to_string()));
+                });
+                return Command::perform(receiver, |result| {
+                    cosmic::app::Message::App(Message::HistoryExported(
+                        result.unwrap_or_else(|_| Err("export thread panicked".to_string())),
+                    ))
+                });
+            }
+            Message::HistoryExported(result) => {
+                self.export_status = Some(match result {
+                    Ok(path) => fl!("export-done", path = path.display().to_string()),
+                    Err(err) => {
+                        tracing::error!("failed to export history: {}", err);
+                        fl!("export-failed", error = err)
+                    }
+                });
+            }
             Message::Config(config) => {
                 if config != self.config {
-                    self.config = config
+                    self.config = config;
+                    self.chart.set_config(&self.config);
+                    self.chart.set_style(chart_style(
+                        self.core.system_theme().cosmic(),
+                        self.config.chart_palette,
+                    ));
+                    self.sync_exporter();
+                    self.sync_mqtt();
                 }
             }
-            Message::TogglePopup => {
+            Message::TogglePopup(chart) => {
                 return if let Some(p) = self.popup.take() {
+                    self.focused = None;
+                    self.focused_chart = None;
+                    self.chart.set_visible(false);
                     destroy_popup(p)
                 } else {
-                    let new_id = Id::unique();
-                    self.popup.replace(new_id);
-                    let mut popup_settings =
-                        self.core
-                            .applet
-                            .get_popup_settings(Id::MAIN, new_id, None, None, None);
-                    popup_settings.positioner.size_limits = Limits::NONE
-                        .max_width(475.0)
-                        .min_width(300.0)
-                        .min_height(200.0)
-                        .max_height(1080.0);
-                    get_popup(popup_settings)
+                    self.focused_chart = chart;
+                    // The quick glance has no charts to show first
+                    if chart.is_some() {
+                        config_set!(popup_expanded, true);
+                    }
+                    self.open_popup()
+                };
+            }
+            Message::ExpandPopup(expanded) => {
+                config_set!(popup_expanded, expanded);
+                self.chart.set_visible(self.is_popup_visible());
+            }
+            Message::ScrollToTop => {
+                return scrollable::snap_to(popup_scrollable(), RelativeOffset::START);
+            }
+            Message::Resized(id, width) => {
+                // The panel icon has a surface of its own, only follow the content
+                if self.windowed || self.popup == Some(id) {
+                    self.content_width = width;
+                }
+            }
+            Message::OutputHeight(name, height) => {
+                if name == self.core.applet.output_name {
+                    self.popup_max_height = (height - POPUP_OUTPUT_MARGIN).max(100.0);
+                }
+            }
+            Message::ScaleFactorChanged(id, scale_factor) => {
+                // The popup may sit on another output than the panel icon
+                if self.windowed || self.popup == Some(id) {
+                    self.chart.set_scale_factor(scale_factor);
+                }
+            }
+            Message::PopupClosed(id) => {
+                if self.popup.as_ref() == Some(&id) {
+                    self.popup = None;
+                    self.focused = None;
+                    self.focused_chart = None;
+                    self.chart.set_visible(false);
                 }
             }
         }
@@ -137,82 +1182,103 @@ impl cosmic::Application for Window {
     }
 
     fn view<'a>(&'a self) -> Element<Self::Message> {
-        self.core
-            .applet
-            .icon_button(&self.icon_name)
-            .on_press(Message::TogglePopup)
-            .into()
-    }
-
-    fn view_window(&self, _id: Id) -> Element<Self::Message> {
-        #[allow(unused_variables)]
-        let cosmic::cosmic_theme::Spacing {
-            space_none, // 0
-            space_xxxs, // 4
-            space_xxs,  // 8
-            space_xs,   // 12
-            space_s,    // 16
-            space_m,    // 24
-            space_l,    // 32
-            space_xl,   // 48
-            space_xxl,  // 64
-            space_xxxl, // 128
-        } = self.core.system_theme().cosmic().spacing;
-
-        // let mut cols = widget::column::with_capacity(2).width(Length::Fill);
-
-        // let cpu_info = self.system.cpus().first().unwrap().brand();
-        // let cpu_usage = self.system.global_cpu_info().cpu_usage();
-        // cols = cols
-        //     .push(backend::cpu_widget(cpu_info, cpu_usage))
-        //     .push(backend::memory_widget(
-        //         self.system.used_memory(),
-        //         self.system.total_memory(),
-        //     ));
-
-        // let mut labels = self
-        //     .components
-        //     .iter()
-        //     .map(|v| (v.label().to_string(), v.temperature()))
-        //     .collect::<Vec<(String, f32)>>();
-
-        // labels.sort_by(|(a, _), (b, _)| a.cmp(b));
+        if self.windowed {
+            return self.popup_content();
+        }
 
-        // for (label, temp) in labels {
-        //     cols = cols.push(
-        //         widget::text(format!("{} {}°C", label, temp.trunc() as u32))
-        //             .apply(widget::container)
-        //             .padding(12)
-        //             .apply(Element::from),
-        //     );
-        // }
+        let health_down = self.health.any_down();
+        let ongoing = self
+            .config
+            .panel_alert_badge
+            .then(|| self.alerts.ongoing())
+            .flatten();
+        if !self.config.panel_values && !health_down && ongoing.is_none() {
+            return self.flash.view(
+                self.core
+                    .applet
+                    .icon_button(&self.icon_name)
+                    .on_press(Message::TogglePopup(self.panel_focus()))
+                    .into(),
+            );
+        }
 
-        let content = widget::column()
-            .spacing(10)
-            .align_items(Alignment::Start)
-            .width(Length::Shrink)
-            .height(Length::Shrink)
-            .push(self.chart.view());
+        let horizontal = self.core.applet.is_horizontal();
+        let icon = widget::icon::from_name(self.icon_name.as_str())
+            .size(self.core.applet.suggested_size(true).0)
+            .symbolic(true);
+        let icon: Element<Message> = match ongoing {
+            Some((count, severity)) => {
+                let theme = self.core.system_theme().cosmic();
+                let color = match severity {
+                    Severity::Critical => theme.destructive_color(),
+                    Severity::Warning => theme.warning_color(),
+                };
+                Badge::new(icon, count, Color::from(color)).into()
+            }
+            None => icon.into(),
+        };
+        // Vertical panels are narrow: drop the unit and stack the values below the icon
+        let mut children: Vec<Element<Message>> = vec![icon];
+        if self.config.panel_values {
+            children.extend(
+                self.config
+                    .panel_metric
+                    .series()
+                    .iter()
+                    .filter_map(|series| self.panel_value(series))
+                    .map(|(value, unit)| {
+                        if horizontal {
+                            widget::text(chart::format_value(value, unit)).into()
+                        } else {
+                            widget::text(chart::format_value(value, "")).size(10).into()
+                        }
+                    }),
+            );
+        }
+        // A failing health check, whether or not values are shown
+        if health_down {
+            children.push(
+                widget::text("●")
+                    .size(if horizontal { 14 } else { 10 })
+                    .style(cosmic::theme::Text::Color(Color::from(
+                        self.core.system_theme().cosmic().destructive_color(),
+                    )))
+                    .into(),
+            );
+        }
 
-        let chart_container = widget::container(content)
-            .width(Length::Fill)
-            .height(Length::Shrink)
-            .padding(5)
-            .center_x()
-            .center_y();
+        let content: Element<Message> = if horizontal {
+            widget::row::with_children(children)
+                .spacing(4)
+                .align_items(Alignment::Center)
+                .into()
+        } else {
+            widget::column::with_children(children)
+                .spacing(2)
+                .align_items(Alignment::Center)
+                .into()
+        };
 
-        // let content = widget::column::with_children(vec![cols.into(), chart_container.into()])
-        //     .padding([space_xxs, space_xxxs])
-        //     .spacing(space_m);
+        self.flash.view(
+            widget::button(content)
+                .padding(self.core.applet.suggested_padding(true))
+                .style(cosmic::theme::Button::AppletIcon)
+                .on_press(Message::TogglePopup(self.panel_focus()))
+                .into(),
+        )
+    }
 
-        self.core.applet.popup_container(chart_container).into()
+    fn view_window(&self, _id: Id) -> Element<Self::Message> {
+        self.core
+            .applet
+            .popup_container(self.popup_content())
+            .into()
     }
 
     fn subscription(&self) -> Subscription<Self::Message> {
-        const FPS: u64 = 60;
-        let ticks = iced::time::every(Duration::from_millis(1000 / FPS))
-            .map(|_| Message::Tick)
-            .map(|_| Message::Tick);
+        // Every message triggers a redraw, so only tick when a sample is due; the charts
+        // clear their canvas caches only when they received new data
+        let ticks = iced::time::every(self.chart.sample_every()).map(|_| Message::Tick);
 
         struct ConfigSubscription;
         let config = cosmic_config::config_subscription(
@@ -222,18 +1288,145 @@ impl cosmic::Application for Window {
         )
         .map(|update| {
             if !update.errors.is_empty() {
-                eprintln!(
+                tracing::error!(
                     "errors loading config {:?}: {:?}",
-                    update.keys, update.errors
+                    update.keys,
+                    update.errors
                 );
             }
             Message::Config(update.config)
         });
 
-        Subscription::batch(vec![config, ticks])
+        let power = power::on_battery_subscription().map(Message::OnBattery);
+        let power_profile = power::power_profile_subscription().map(Message::PowerProfile);
+        let sleep = power::sleep_subscription().map(Message::Sleep);
+        let save_history = iced::time::every(SAVE_HISTORY_EVERY).map(|_| Message::SaveHistory);
+        let surface_events = iced::event::listen_with(|event, _status| match event {
+            iced::Event::Window(id, iced::window::Event::Resized { width, .. }) => {
+                Some(Message::Resized(id, width as f32))
+            }
+            iced::Event::Window(id, iced::window::Event::ScaleFactorChanged { scale_factor }) => {
+                Some(Message::ScaleFactorChanged(id, scale_factor))
+            }
+            iced::Event::PlatformSpecific(iced::event::PlatformSpecific::Wayland(
+                wayland::Event::Output(
+                    wayland::OutputEvent::Created(Some(info))
+                    | wayland::OutputEvent::InfoUpdate(info),
+                    _,
+                ),
+            )) => {
+                let (_, height) = info.logical_size?;
+                Some(Message::OutputHeight(info.name?, height as f32))
+            }
+            _ => None,
+        });
+
+        let mut subscriptions = vec![
+            config,
+            ticks,
+            power,
+            power_profile,
+            sleep,
+            save_history,
+            surface_events,
+            self.flash.subscription().map(Message::Flash),
+        ];
+        // Keeps the popup redrawing every frame, so only while somebody looks at it
+        if self.config.frame_time_metric && self.is_popup_visible() {
+            subscriptions.push(iced::window::frames().map(Message::Frame));
+        }
+        Subscription::batch(subscriptions)
+    }
+
+    fn system_theme_update(
+        &mut self,
+        _keys: &[&'static str],
+        new_theme: &cosmic::cosmic_theme::Theme,
+    ) -> Command<cosmic::app::Message<Self::Message>> {
+        self.chart
+            .set_style(chart_style(new_theme, self.config.chart_palette));
+        Command::none()
+    }
+
+    fn on_close_requested(&self, id: Id) -> Option<Message> {
+        Some(Message::PopupClosed(id))
     }
 
     fn style(&self) -> Option<<Theme as application::StyleSheet>::Style> {
+        if self.windowed {
+            return None;
+        }
         Some(cosmic::applet::style())
     }
 }
+
+fn popup_scrollable() -> scrollable::Id {
+    scrollable::Id::new("popup-sections")
+}
+
+fn power_profile_row(active: &str) -> Element<'static, Message> {
+    let mut cells: Vec<Element<Message>> = vec![widget::text(fl!("power-profile"))
+        .width(Length::Fill)
+        .horizontal_alignment(layout::start_alignment())
+        .into()];
+    for profile in power::POWER_PROFILES {
+        let label = match profile {
+            "power-saver" => fl!("power-saver"),
+            "balanced" => fl!("balanced"),
+            _ => fl!("performance"),
+        };
+        let button = if profile == active {
+            widget::button::suggested(label)
+        } else {
+            widget::button::standard(label)
+        };
+        cells.push(
+            button
+                .on_press(Message::SetPowerProfile(profile.to_string()))
+                .into(),
+        );
+    }
+    layout::directional_row(cells)
+        .spacing(8)
+        .align_items(Alignment::Center)
+        .into()
+}
+
+fn profile_row(active: Profile) -> Element<'static, Message> {
+    let mut cells: Vec<Element<Message>> = vec![widget::text(fl!("profile"))
+        .width(Length::Fill)
+        .horizontal_alignment(layout::start_alignment())
+        .into()];
+    for profile in Profile::PRESETS {
+        let label = match profile {
+            Profile::Desktop => fl!("profile-desktop"),
+            Profile::Laptop => fl!("profile-laptop"),
+            _ => fl!("profile-server"),
+        };
+        let button = if profile == active {
+            widget::button::suggested(label)
+        } else {
+            widget::button::standard(label)
+        };
+        cells.push(button.on_press(Message::ApplyProfile(profile)).into());
+    }
+    layout::directional_row(cells)
+        .spacing(8)
+        .align_items(Alignment::Center)
+        .into()
+}
+
+fn chart_style(theme: &cosmic::cosmic_theme::Theme, palette: ChartPalette) -> ChartStyle {
+    // Grid and labels follow the background so they stay readable on light and dark themes
+    ChartStyle {
+        accent: rgb_color(theme.accent_color()),
+        grid: rgb_color(theme.background.divider),
+        label: rgb_color(theme.background.on),
+        palette,
+    }
+}
+
+fn rgb_color(color: cosmic::cosmic_theme::palette::Srgba) -> RGBColor {
+    let color = color.into_format::<u8, u8>().without_alpha();
+    RGBColor(color.red, color.green, color.blue)
+}